@@ -0,0 +1,181 @@
+use {
+    crate::{Entry, Errors},
+    proc_macro2::TokenStream as TokenStream2,
+    quote::quote,
+    syn::{
+        parse::ParseStream, spanned::Spanned, Attribute, Data, DeriveInput, Expr, Fields, Ident,
+        Token,
+    },
+};
+
+/// The name of the attribute used by the derive macro.
+const ATTRIBUTE: &str = "char_escape";
+
+/// A unit variant and the rule it stands for.
+struct Variant {
+    name: Ident,
+    entry: Entry,
+}
+
+/// Expands `#[derive(CharEscape)]`.
+pub fn char_escape(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let mut errors = Errors::default();
+
+    let escape_char = escape_char(&input.attrs, &mut errors);
+
+    let Data::Enum(data) = &input.data else {
+        return Err(syn::Error::new(
+            input.ident.span(),
+            "`CharEscape` can only be derived for enums",
+        ));
+    };
+
+    let mut variants = Vec::new();
+    for variant in &data.variants {
+        if !matches!(variant.fields, Fields::Unit) {
+            errors.push(
+                variant.ident.span(),
+                "`CharEscape` requires unit variants".to_owned(),
+            );
+            continue;
+        }
+
+        let mut entries = variant
+            .attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident(ATTRIBUTE))
+            .map(|attr| (attr, attr.parse_args::<Entry>()));
+        match (entries.next(), entries.next()) {
+            (Some((_, Ok(entry))), None) => variants.push(Variant {
+                name: variant.ident.clone(),
+                entry,
+            }),
+            (Some((_, Err(error))), None) => errors.push(error.span(), error.to_string()),
+            (None, _) => errors.push(
+                variant.ident.span(),
+                format!("missing `#[{ATTRIBUTE}(unescaped => escaped)]` attribute"),
+            ),
+            (Some(_), Some((attr, _))) => errors.push(
+                attr.span(),
+                format!("more than one `#[{ATTRIBUTE}]` attribute"),
+            ),
+        }
+    }
+
+    errors.finish()?;
+
+    Ok(expand(input, escape_char.as_ref(), &variants))
+}
+
+/// Parses the optional `#[char_escape(escape_char = ...)]` attribute of the enum.
+fn escape_char(attrs: &[Attribute], errors: &mut Errors) -> Option<Expr> {
+    let mut escape_char = None;
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident(ATTRIBUTE)) {
+        let result = attr.parse_args_with(|input: ParseStream| {
+            let name: Ident = input.parse()?;
+            if name != "escape_char" {
+                return Err(syn::Error::new(
+                    name.span(),
+                    format!("unknown option `{name}`, expected `escape_char`"),
+                ));
+            }
+            input.parse::<Token![=]>()?;
+            input.parse::<Expr>()
+        });
+        match result {
+            Ok(_) if escape_char.is_some() => errors.push(
+                attr.span(),
+                "`escape_char` is specified more than once".to_owned(),
+            ),
+            Ok(expr) => escape_char = Some(expr),
+            Err(error) => errors.push(error.span(), error.to_string()),
+        }
+    }
+
+    escape_char
+}
+
+/// Generates the constant, functions and conversions for the enum.
+fn expand(input: &DeriveInput, escape_char: Option<&Expr>, variants: &[Variant]) -> TokenStream2 {
+    let name = &input.ident;
+    let vis = &input.vis;
+    let (impl_generics, type_generics, where_clause) = input.generics.split_for_impl();
+
+    let names: Vec<_> = variants.iter().map(|variant| &variant.name).collect();
+    let unescaped: Vec<_> = variants
+        .iter()
+        .map(|variant| &variant.entry.unescaped)
+        .collect();
+    let escaped: Vec<_> = variants
+        .iter()
+        .map(|variant| &variant.entry.escaped)
+        .collect();
+    let escape_char = escape_char.map(|escape_char| quote!(escape_char = #escape_char,));
+
+    quote! {
+        impl #impl_generics #name #type_generics #where_clause {
+            /// An `Escaper` with a rule for every variant.
+            #vis const ESCAPER: ::char_escape::Escaper<'static> = ::char_escape::escaper! {
+                #escape_char
+                rules = [#(#unescaped => #escaped),*],
+            };
+
+            /// Returns the unescaped `char` of this variant.
+            #vis const fn unescaped(self) -> ::std::primitive::char {
+                match self {
+                    #(Self::#names => #unescaped,)*
+                }
+            }
+
+            /// Returns the `char` that this variant is escaped as.
+            #vis const fn escaped(self) -> ::std::primitive::char {
+                match self {
+                    #(Self::#names => #escaped,)*
+                }
+            }
+
+            /// Returns the variant with the given unescaped `char`.
+            #vis const fn from_unescaped(
+                c: ::std::primitive::char,
+            ) -> ::std::option::Option<Self> {
+                #(
+                    if c == #unescaped {
+                        return ::std::option::Option::Some(Self::#names);
+                    }
+                )*
+                ::std::option::Option::None
+            }
+
+            /// Returns the variant that is escaped as the given `char`.
+            #vis const fn from_escaped(
+                c: ::std::primitive::char,
+            ) -> ::std::option::Option<Self> {
+                #(
+                    if c == #escaped {
+                        return ::std::option::Option::Some(Self::#names);
+                    }
+                )*
+                ::std::option::Option::None
+            }
+        }
+
+        impl #impl_generics ::std::convert::From<#name #type_generics>
+            for ::std::primitive::char #where_clause
+        {
+            fn from(value: #name #type_generics) -> Self {
+                value.unescaped()
+            }
+        }
+
+        impl #impl_generics ::std::convert::TryFrom<::std::primitive::char>
+            for #name #type_generics #where_clause
+        {
+            type Error = ::std::primitive::char;
+
+            /// Returns the variant with the given unescaped `char`, or the `char` if there is none.
+            fn try_from(c: ::std::primitive::char) -> ::std::result::Result<Self, Self::Error> {
+                Self::from_unescaped(c).ok_or(c)
+            }
+        }
+    }
+}
@@ -0,0 +1,530 @@
+//! Procedural macros for [`char_escape`](https://docs.rs/char_escape).
+//!
+//! Use them through the `proc-macro` feature of `char_escape`, which re-exports them in
+//! `char_escape::proc_macros`.
+
+#![deny(clippy::all)]
+#![warn(clippy::cargo)]
+#![warn(clippy::pedantic)]
+#![warn(missing_docs)]
+#![allow(clippy::must_use_candidate)]
+#![allow(clippy::cargo_common_metadata)]
+
+mod derive;
+
+use {
+    proc_macro::TokenStream,
+    proc_macro2::{Span, TokenStream as TokenStream2},
+    quote::{quote, ToTokens},
+    syn::{
+        bracketed,
+        ext::IdentExt,
+        parse::{Parse, ParseStream},
+        parse_macro_input,
+        punctuated::Punctuated,
+        spanned::Spanned,
+        token::Bracket,
+        DeriveInput, Expr, ExprLit, ExprRange, Ident, Lit, RangeLimits, Token,
+    },
+};
+
+/// Creates an `Escaper`, like `char_escape::escaper!`, but with precise error messages.
+///
+/// Accepts the same syntax as `char_escape::escaper!` and expands to it. Additionally, mistakes
+/// that can be spotted without evaluating any constants are reported at the offending tokens,
+/// e.g. duplicate rules, empty ranges or unknown options. Rules that use constants are still
+/// checked when the `Escaper` is evaluated.
+///
+/// No lookup tables are generated at compile time, the macro expands to the same `Escaper` as
+/// `char_escape::escaper!`. Call `Escaper::compile` on the result to precompute them.
+///
+/// ```
+/// use char_escape_macros::escaper;
+///
+/// let escaper = escaper! {
+///     escape_char = '%',
+///     rules = [
+///         '\n' => 'n',
+///         '\0'..='\x08' => hex,
+///     ],
+/// };
+///
+/// assert_eq!(escaper.escape("%\n\0"), "%%%n%x{00}");
+/// ```
+///
+/// ```compile_fail
+/// use char_escape_macros::escaper;
+///
+/// let escaper = escaper! {
+///     '\n' => 'n',
+///     // error: more than one rule escapes '\n'
+///     '\n' => 'x',
+/// };
+/// ```
+#[proc_macro]
+pub fn escaper(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as EscaperInput);
+
+    match input.validate() {
+        Ok(()) => input.expand(),
+        Err(error) => {
+            // several errors are several statements, so they need to be wrapped in a block
+            let errors = error.to_compile_error();
+            quote!({ #errors })
+        }
+    }
+    .into()
+}
+
+/// Derives an `Escaper` and conversions for an enum whose variants stand for special [`char`]s.
+///
+/// Every variant must be a unit variant with a `#[char_escape(unescaped => escaped)]` attribute.
+/// The escape character can be set using `#[char_escape(escape_char = ...)]` on the enum and
+/// defaults to `'\\'`. The rules are checked like in `char_escape::escaper!`.
+///
+/// Generates:
+/// - an associated constant `ESCAPER` with a rule for every variant,
+/// - `const fn`s `unescaped()` and `escaped()` returning the [`char`]s of a variant,
+/// - `const fn`s `from_unescaped()` and `from_escaped()` returning the variant of a [`char`],
+/// - `From<Enum> for char` and `TryFrom<char> for Enum`, using the unescaped [`char`].
+///
+/// ```
+/// use char_escape_macros::CharEscape;
+///
+/// #[derive(Clone, Copy, Debug, PartialEq, CharEscape)]
+/// #[char_escape(escape_char = '%')]
+/// enum Special {
+///     #[char_escape('\n' => 'n')]
+///     Newline,
+///     #[char_escape('\t' => 't')]
+///     Tab,
+/// }
+///
+/// assert_eq!(Special::ESCAPER.escape("a\tb\n"), "a%tb%n");
+/// assert_eq!(Special::Tab.escaped(), 't');
+/// assert_eq!(Special::from_escaped('n'), Some(Special::Newline));
+/// assert_eq!(char::from(Special::Newline), '\n');
+/// assert_eq!(Special::try_from('x'), Err('x'));
+/// ```
+#[proc_macro_derive(CharEscape, attributes(char_escape))]
+pub fn char_escape(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    derive::char_escape(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// A single `unescaped => escaped` entry.
+struct Entry {
+    unescaped: Expr,
+    escaped: Expr,
+}
+
+impl Parse for Entry {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let unescaped = input.parse()?;
+        input.parse::<Token![=>]>()?;
+        let escaped = input.parse()?;
+
+        Ok(Self { unescaped, escaped })
+    }
+}
+
+impl ToTokens for Entry {
+    fn to_tokens(&self, tokens: &mut TokenStream2) {
+        let Self { unescaped, escaped } = self;
+        tokens.extend(quote!(#unescaped => #escaped));
+    }
+}
+
+/// A list of entries, bracketed unless it is the whole input of the macro.
+struct Rules {
+    bracket: Option<Bracket>,
+    entries: Punctuated<Entry, Token![,]>,
+}
+
+impl Parse for Rules {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let content;
+        let bracket = bracketed!(content in input);
+        let entries = content.parse_terminated(Entry::parse, Token![,])?;
+
+        Ok(Self {
+            bracket: Some(bracket),
+            entries,
+        })
+    }
+}
+
+/// One `name = value` option of the macro, or a flag.
+enum Setting {
+    NoImplicitEscape(Ident),
+    EscapeChar(Ident, Expr),
+    Extend(Ident, Expr),
+    /// `trailing_escape`, `fallback` or `case_insensitive`.
+    Behavior(&'static str, Ident, Expr),
+    Same(Ident, Rules),
+    Rules(Option<Ident>, Rules),
+}
+
+impl Setting {
+    /// Returns the span of the name of the option, or of the rules if the name is omitted.
+    fn span(&self) -> Span {
+        match self {
+            Self::NoImplicitEscape(name)
+            | Self::EscapeChar(name, _)
+            | Self::Extend(name, _)
+            | Self::Behavior(_, name, _)
+            | Self::Same(name, _)
+            | Self::Rules(Some(name), _) => name.span(),
+            Self::Rules(None, rules) => rules
+                .bracket
+                .map_or_else(Span::call_site, |bracket| bracket.span.open()),
+        }
+    }
+
+    /// Returns a string that is the same for all settings of the same kind.
+    fn kind(&self) -> &'static str {
+        match self {
+            Self::NoImplicitEscape(..) => "no_implicit_escape",
+            Self::EscapeChar(..) => "escape_char",
+            Self::Extend(..) => "extend",
+            Self::Behavior(kind, ..) => kind,
+            Self::Same(..) => "self",
+            Self::Rules(..) => "rules",
+        }
+    }
+}
+
+impl Parse for Setting {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        if input.peek(Bracket) {
+            return Ok(Self::Rules(None, input.parse()?));
+        }
+
+        let name = Ident::parse_any(input)?;
+        if name == "no_implicit_escape" {
+            return Ok(Self::NoImplicitEscape(name));
+        }
+
+        input.parse::<Token![=]>()?;
+        match name.to_string().as_str() {
+            "escape_char" => Ok(Self::EscapeChar(name, input.parse()?)),
+            "extend" => Ok(Self::Extend(name, input.parse()?)),
+            "trailing_escape" => Ok(Self::Behavior("trailing_escape", name, input.parse()?)),
+            "fallback" => Ok(Self::Behavior("fallback", name, input.parse()?)),
+            "case_insensitive" => Ok(Self::Behavior("case_insensitive", name, input.parse()?)),
+            "self" => {
+                let content;
+                let bracket = bracketed!(content in input);
+                let same = Punctuated::<Expr, Token![,]>::parse_terminated(&content)?;
+                let entries = same
+                    .into_iter()
+                    .map(|c| Entry {
+                        unescaped: c.clone(),
+                        escaped: c,
+                    })
+                    .collect();
+                let rules = Rules {
+                    bracket: Some(bracket),
+                    entries,
+                };
+                Ok(Self::Same(name, rules))
+            }
+            "rules" => Ok(Self::Rules(Some(name), input.parse()?)),
+            _ => Err(syn::Error::new(
+                name.span(),
+                format!(
+                    "unknown option `{name}`, expected `no_implicit_escape`, `escape_char`, \
+                     `extend`, `trailing_escape`, `fallback`, `case_insensitive`, `self` or \
+                     `rules`"
+                ),
+            )),
+        }
+    }
+}
+
+/// The whole input of the macro, in any of its forms.
+struct EscaperInput {
+    settings: Vec<Setting>,
+}
+
+impl Parse for EscaperInput {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut settings = Vec::new();
+
+        let named =
+            (input.peek(Ident::peek_any) && input.peek2(Token![=]) && !input.peek2(Token![=>]))
+                || input
+                    .fork()
+                    .call(Ident::parse_any)
+                    .is_ok_and(|name| name == "no_implicit_escape");
+        let positional = !named && {
+            let fork = input.fork();
+            fork.parse::<Expr>().is_ok() && fork.peek(Token![,])
+        };
+
+        if named || positional {
+            if positional {
+                let escape_char: Expr = input.parse()?;
+                input.parse::<Token![,]>()?;
+                let name = Ident::new("escape_char", escape_char.span());
+                settings.push(Setting::EscapeChar(name, escape_char));
+            }
+            let rest = Punctuated::<Setting, Token![,]>::parse_terminated(input)?;
+            settings.extend(rest);
+        } else {
+            let entries = Punctuated::parse_terminated(input)?;
+            settings.push(Setting::Rules(
+                None,
+                Rules {
+                    bracket: None,
+                    entries,
+                },
+            ));
+        }
+
+        Ok(Self { settings })
+    }
+}
+
+impl EscaperInput {
+    /// Returns the entries of all the rules.
+    fn entries(&self) -> impl Iterator<Item = &Entry> {
+        self.settings
+            .iter()
+            .filter_map(|setting| match setting {
+                Setting::Same(_, rules) | Setting::Rules(_, rules) => Some(rules.entries.iter()),
+                _ => None,
+            })
+            .flatten()
+    }
+
+    /// Returns the escape character, if it is a literal or implied.
+    ///
+    /// Returns [`None`] if the escape character is a constant or taken from an extended escaper.
+    fn escape_char(&self) -> Option<char> {
+        let mut escape_char = Some('\\');
+        for setting in &self.settings {
+            match setting {
+                Setting::EscapeChar(_, expr) => return char_literal(expr),
+                Setting::Extend(..) => escape_char = None,
+                Setting::NoImplicitEscape(..)
+                | Setting::Behavior(..)
+                | Setting::Same(..)
+                | Setting::Rules(..) => {}
+            }
+        }
+
+        escape_char
+    }
+
+    /// Checks everything that can be checked without evaluating constants.
+    fn validate(&self) -> syn::Result<()> {
+        let mut errors = Errors::default();
+
+        for (i, setting) in self.settings.iter().enumerate() {
+            if let Some(first) = self.settings[..i]
+                .iter()
+                .find(|other| other.kind() == setting.kind())
+            {
+                errors.push_with_note(
+                    setting.span(),
+                    format!("`{}` is specified more than once", setting.kind()),
+                    first.span(),
+                    "the first time is here",
+                );
+            }
+        }
+
+        let entries: Vec<&Entry> = self.entries().collect();
+        for (i, entry) in entries.iter().enumerate() {
+            if let Expr::Range(range) = &entry.unescaped {
+                check_range(range, &mut errors);
+            }
+
+            let earlier = &entries[..i];
+            if let Some(unescaped) = char_literal(&entry.unescaped) {
+                if let Some(first) = earlier
+                    .iter()
+                    .find(|first| char_literal(&first.unescaped) == Some(unescaped))
+                {
+                    errors.push_with_note(
+                        entry.unescaped.span(),
+                        format!("more than one rule escapes {unescaped:?}"),
+                        first.unescaped.span(),
+                        "the first one is here",
+                    );
+                }
+            }
+            if let Some(escaped) = escaped_literal(&entry.escaped) {
+                if let Some(first) = earlier
+                    .iter()
+                    .find(|first| escaped_literal(&first.escaped).as_ref() == Some(&escaped))
+                {
+                    errors.push_with_note(
+                        entry.escaped.span(),
+                        format!("more than one rule is escaped as {escaped:?}"),
+                        first.escaped.span(),
+                        "the first one is here",
+                    );
+                }
+                if let Some(prefix) = earlier.iter().find(|other| {
+                    escaped_literal(&other.escaped).is_some_and(|other| {
+                        other != escaped
+                            && (other.starts_with(&escaped) || escaped.starts_with(&other))
+                    })
+                }) {
+                    errors.push_with_note(
+                        entry.escaped.span(),
+                        "escape sequences must be prefix-free, but one of them starts the other"
+                            .to_owned(),
+                        prefix.escaped.span(),
+                        "the other one is here",
+                    );
+                }
+            }
+        }
+
+        if let Some(escape_char) = self.escape_char() {
+            let overridden = entries
+                .iter()
+                .any(|entry| char_literal(&entry.unescaped) == Some(escape_char));
+            let no_implicit_escape = self.settings.iter().find_map(|setting| match setting {
+                Setting::NoImplicitEscape(name) => Some(name),
+                _ => None,
+            });
+            let all_literal = entries.iter().all(|entry| {
+                char_literal(&entry.unescaped).is_some()
+                    || matches!(entry.unescaped, Expr::Range(_))
+            });
+            if let Some(name) = no_implicit_escape {
+                if !overridden && all_literal {
+                    errors.push(
+                        name.span(),
+                        format!("there is no rule for the escape character {escape_char:?}"),
+                    );
+                }
+            } else if !overridden {
+                for entry in &entries {
+                    let Some(escaped) = escaped_literal(&entry.escaped) else {
+                        continue;
+                    };
+                    let message = if escaped == String::from(escape_char) {
+                        "the implicit rule for the escape character is also escaped as"
+                    } else if escaped.starts_with(escape_char) {
+                        "this escape sequence starts with the one of the implicit rule for the \
+                         escape character,"
+                    } else {
+                        continue;
+                    };
+                    errors.push(entry.escaped.span(), format!("{message} {escape_char:?}"));
+                }
+            }
+        }
+
+        errors.finish()
+    }
+
+    /// Expands to an invocation of the declarative `escaper!` macro.
+    ///
+    /// Rules may use constants, which cannot be evaluated here, so the rules are passed on
+    /// unchanged instead of being turned into a table.
+    fn expand(&self) -> TokenStream2 {
+        let mut options = Vec::new();
+        let mut entries = Vec::new();
+        for setting in &self.settings {
+            match setting {
+                Setting::NoImplicitEscape(name) => options.push(quote!(#name)),
+                Setting::EscapeChar(_, expr) => options.push(quote!(escape_char = #expr)),
+                Setting::Extend(_, expr) => options.push(quote!(extend = #expr)),
+                Setting::Behavior(_, name, expr) => options.push(quote!(#name = #expr)),
+                Setting::Same(_, rules) => {
+                    let same = rules.entries.iter().map(|entry| &entry.unescaped);
+                    options.push(quote!(self = [#(#same),*]));
+                }
+                Setting::Rules(_, rules) => entries.extend(rules.entries.iter()),
+            }
+        }
+
+        quote! {
+            ::char_escape::escaper! {
+                #(#options,)*
+                rules = [#(#entries),*],
+            }
+        }
+    }
+}
+
+/// Checks that a range with literal bounds is not empty.
+fn check_range(range: &ExprRange, errors: &mut Errors) {
+    let (Some(start), Some(end)) = (
+        range.start.as_deref().and_then(char_literal),
+        range.end.as_deref().and_then(char_literal),
+    ) else {
+        return;
+    };
+
+    match range.limits {
+        RangeLimits::Closed(_) if start > end => errors.push(
+            range.span(),
+            format!("the range from {start:?} to {end:?} is empty"),
+        ),
+        RangeLimits::HalfOpen(_) => errors.push(
+            range.limits.span(),
+            "only inclusive ranges are supported, use `..=`".to_owned(),
+        ),
+        RangeLimits::Closed(_) => {}
+    }
+}
+
+/// Returns the escape sequence given by a [`char`] or string literal.
+///
+/// A [`char`] and a string consisting of only that [`char`] result in the same escape sequence.
+fn escaped_literal(expr: &Expr) -> Option<String> {
+    match expr {
+        Expr::Lit(ExprLit {
+            lit: Lit::Str(s), ..
+        }) => Some(s.value()),
+        Expr::Group(group) => escaped_literal(&group.expr),
+        Expr::Paren(paren) => escaped_literal(&paren.expr),
+        _ => char_literal(expr).map(String::from),
+    }
+}
+
+/// Returns the value of a [`char`] literal.
+fn char_literal(expr: &Expr) -> Option<char> {
+    match expr {
+        Expr::Lit(ExprLit {
+            lit: Lit::Char(c), ..
+        }) => Some(c.value()),
+        Expr::Group(group) => char_literal(&group.expr),
+        Expr::Paren(paren) => char_literal(&paren.expr),
+        _ => None,
+    }
+}
+
+/// Collects all errors, so they can be reported at once.
+#[derive(Default)]
+struct Errors(Option<syn::Error>);
+
+impl Errors {
+    fn push(&mut self, span: Span, message: String) {
+        let error = syn::Error::new(span, message);
+        match &mut self.0 {
+            Some(errors) => errors.combine(error),
+            None => self.0 = Some(error),
+        }
+    }
+
+    /// Adds an error and a second one pointing to a related location.
+    fn push_with_note(&mut self, span: Span, message: String, note_span: Span, note: &str) {
+        self.push(span, message);
+        self.push(note_span, note.to_owned());
+    }
+
+    fn finish(self) -> syn::Result<()> {
+        self.0.map_or(Ok(()), Err)
+    }
+}
@@ -0,0 +1,86 @@
+use {
+    crate::{Escaper, UnescapeError},
+    std::borrow::Cow,
+};
+
+/// Several [`Escaper`]s applied one after another.
+///
+/// Escaping applies the [`Escaper`]s in the order they were added, unescaping applies them in
+/// reverse order. This is useful for layered encodings, e.g. when a field is escaped for a record
+/// format and the record is then escaped for a transport format.
+///
+/// Created by [`Escaper::then()`].
+///
+/// ```
+/// # use char_escape::escaper;
+/// #
+/// let field = escaper! {
+///     escape_char = '\\',
+///     rules = [',' => 'c'],
+/// };
+/// let transport = escaper! {
+///     escape_char = '%',
+///     rules = ['\\' => 'b'],
+/// };
+///
+/// let chain = field.then(transport);
+///
+/// assert_eq!(chain.escape("a,b"), "a%bcb");
+/// assert_eq!(chain.unescape("a%bcb").expect("is properly escaped"), "a,b");
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Chain<'a> {
+    layers: Vec<Escaper<'a>>,
+}
+
+impl<'a> Chain<'a> {
+    /// Create a new [`Chain`] consisting of the given [`Escaper`]s.
+    ///
+    /// The first [`Escaper`] is applied first when escaping.
+    pub fn new(layers: Vec<Escaper<'a>>) -> Self {
+        Self { layers }
+    }
+
+    /// Appends another [`Escaper`] that is applied after all the others when escaping.
+    #[must_use]
+    pub fn then(mut self, other: Escaper<'a>) -> Self {
+        self.layers.push(other);
+        self
+    }
+
+    /// Returns the [`Escaper`]s in the order they are applied when escaping.
+    pub fn layers(&self) -> &[Escaper<'a>] {
+        &self.layers
+    }
+
+    /// Escapes a string using every [`Escaper`] in order.
+    ///
+    /// If no [`Escaper`] changes the string, `s` is returned as is.
+    pub fn escape<'s>(&self, s: &'s str) -> Cow<'s, str> {
+        let mut escaped = Cow::Borrowed(s);
+        for layer in &self.layers {
+            escaped = match escaped {
+                Cow::Borrowed(s) => layer.escape(s),
+                Cow::Owned(s) if layer.needs_escaping(&s) => Cow::Owned(layer.escape(&s).into()),
+                owned @ Cow::Owned(_) => owned,
+            };
+        }
+
+        escaped
+    }
+
+    /// Unescapes a string using every [`Escaper`] in reverse order.
+    ///
+    /// # Errors
+    ///
+    /// If one of the intermediate strings is not properly escaped. The position of the error
+    /// refers to the string that was passed to the failing [`Escaper`].
+    pub fn unescape(&self, s: &str) -> Result<String, UnescapeError> {
+        let mut unescaped = s.to_owned();
+        for layer in self.layers.iter().rev() {
+            unescaped = layer.unescape(&unescaped)?;
+        }
+
+        Ok(unescaped)
+    }
+}
@@ -0,0 +1,136 @@
+use {
+    crate::Notation,
+    core::{fmt, ops::RangeInclusive},
+};
+
+/// A set of [`char`]s.
+///
+/// See [`ClassRule`] for more information.
+#[derive(Clone, Copy)]
+pub enum CharClass {
+    /// The ASCII control characters, i.e. `'\0'` to `'\x1f'` and `'\x7f'`.
+    AsciiControl,
+    /// All control characters, see [`char::is_control()`].
+    Control,
+    /// All [`char`]s that are not ASCII.
+    NonAscii,
+    /// All [`char`]s except the printable ASCII characters `' '` to `'~'`.
+    NonPrintable,
+    /// All whitespace characters, see [`char::is_whitespace()`].
+    Whitespace,
+    /// All [`char`]s from `start` to `end`, both inclusive.
+    Range {
+        #[allow(missing_docs)]
+        start: char,
+        #[allow(missing_docs)]
+        end: char,
+    },
+    /// All [`char`]s for which the function returns `true`.
+    Predicate(fn(char) -> bool),
+}
+
+impl CharClass {
+    /// Returns `true` if `c` is part of this class.
+    ///
+    /// ```
+    /// # use char_escape::CharClass;
+    /// #
+    /// assert!(CharClass::AsciiControl.contains('\0'));
+    /// assert!(!CharClass::AsciiControl.contains('\u{85}'));
+    /// assert!(CharClass::from('a'..='z').contains('q'));
+    /// assert!(CharClass::Predicate(|c| c.is_ascii_digit()).contains('7'));
+    /// ```
+    pub fn contains(&self, c: char) -> bool {
+        match self {
+            Self::AsciiControl => c.is_ascii_control(),
+            Self::Control => c.is_control(),
+            Self::NonAscii => !c.is_ascii(),
+            Self::NonPrintable => !crate::fallback::is_safe(c),
+            Self::Whitespace => c.is_whitespace(),
+            Self::Range { start, end } => (*start..=*end).contains(&c),
+            Self::Predicate(predicate) => predicate(c),
+        }
+    }
+}
+
+impl fmt::Debug for CharClass {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::AsciiControl => f.write_str("AsciiControl"),
+            Self::Control => f.write_str("Control"),
+            Self::NonAscii => f.write_str("NonAscii"),
+            Self::NonPrintable => f.write_str("NonPrintable"),
+            Self::Whitespace => f.write_str("Whitespace"),
+            Self::Range { start, end } => f
+                .debug_struct("Range")
+                .field("start", start)
+                .field("end", end)
+                .finish(),
+            Self::Predicate(predicate) => f.debug_tuple("Predicate").field(predicate).finish(),
+        }
+    }
+}
+
+/// Predicates are compared by address, see [`core::ptr::fn_addr_eq()`].
+impl PartialEq for CharClass {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Range { start, end }, Self::Range { start: s, end: e }) => {
+                (start, end) == (s, e)
+            }
+            (Self::Predicate(a), Self::Predicate(b)) => core::ptr::fn_addr_eq(*a, *b),
+            _ => core::mem::discriminant(self) == core::mem::discriminant(other),
+        }
+    }
+}
+
+impl Eq for CharClass {}
+
+impl From<RangeInclusive<char>> for CharClass {
+    fn from(range: RangeInclusive<char>) -> Self {
+        Self::Range {
+            start: *range.start(),
+            end: *range.end(),
+        }
+    }
+}
+
+/// Defines how a whole class of [`char`]s should be escaped.
+///
+/// Every [`char`] in `class` is escaped using `notation`, unless a [`Rule`](crate::Rule) or
+/// [`SequenceRule`](crate::SequenceRule) applies to it.
+///
+/// See [`Escaper::with_class_rules()`](crate::Escaper::with_class_rules) for more information.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ClassRule {
+    #[allow(missing_docs)]
+    pub class: CharClass,
+    #[allow(missing_docs)]
+    pub notation: Notation,
+}
+
+impl ClassRule {
+    /// Create a new [`ClassRule`] that escapes every [`char`] in `range` using `notation`.
+    ///
+    /// This is a shorthand that can be used in `const` contexts.
+    ///
+    /// ```
+    /// # use char_escape::{escaper, ClassRule, Escaper, Notation};
+    /// #
+    /// const ESCAPER: Escaper<'static> = escaper! {
+    ///     '\t' => 't',
+    /// }
+    /// .with_class_rules(&[ClassRule::range('\0'..='\x1f', Notation::Hex)]);
+    ///
+    /// assert_eq!(ESCAPER.escape("\0\t\x1f "), r"\x{00}\t\x{1f} ");
+    /// ```
+    pub const fn range(range: RangeInclusive<char>, notation: Notation) -> Self {
+        Self {
+            class: CharClass::Range {
+                start: *range.start(),
+                end: *range.end(),
+            },
+            notation,
+        }
+    }
+}
@@ -0,0 +1,551 @@
+use {
+    crate::{
+        scan::{AsciiSet, ByteSearch},
+        Decoded, EscapeSequence, Escaper, RejectedChar, TrailingEscape, UnescapeError,
+        DEFAULT_REPLACEMENT, MAX_UTF8_LEN,
+    },
+    core::{convert::Infallible, fmt},
+    std::{borrow::Cow, io},
+};
+
+/// An [`Escaper`] with precomputed lookup tables.
+///
+/// [`Escaper`] looks up the rules for every [`char`] it escapes. [`CompiledEscaper`] does this
+/// once for every ASCII [`char`] when it is created and escapes ASCII [`char`]s using a single
+/// table lookup. Up to 8 rules for other [`char`]s are compared one by one, more are sorted and
+/// found using a binary search.
+///
+/// If at most three ASCII [`char`]s are escaped and nothing else, they are searched a whole block
+/// of bytes at a time, like [`Escaper::escape()`] does. If only ASCII [`char`]s are escaped, the
+/// bytes of other [`char`]s are skipped without decoding them. With the `simd` feature, the bytes
+/// that may need to be escaped are searched 16 bytes at a time using SSSE3 on `x86_64` CPUs that
+/// support it.
+///
+/// When unescaping, the [`char`] that an escape character followed by a single [`char`] stands
+/// for is found using a binary search in a sorted table.
+///
+/// This pays off if many or long strings are escaped or unescaped with the same rules, especially
+/// if there are many rules.
+///
+/// Create it using [`Escaper::compile()`]. It escapes and unescapes strings exactly like the
+/// underlying [`Escaper`] and offers the same methods for escaping and unescaping.
+///
+/// ```
+/// # use char_escape::presets::JSON;
+/// #
+/// let json = JSON.compile();
+///
+/// assert_eq!(json.escape("\"Grüße\"\n\u{1}"), r#"\"Grüße\"\n\u0001"#);
+/// assert_eq!(json.escape("nothing to do"), JSON.escape("nothing to do"));
+/// assert_eq!(json.unescape(r"\u00e4\n").expect("is properly escaped"), "ä\n");
+/// ```
+#[derive(Clone, Debug)]
+pub struct CompiledEscaper<'a> {
+    escaper: Escaper<'a>,
+    ascii: [AsciiEntry<'a>; 128],
+    /// The bytes at which a [`char`] may need to be escaped.
+    search: ByteSearch,
+    non_ascii: NonAsciiRules<'a>,
+    /// The escaped forms of the ASCII [`char`]s, including the escape character.
+    sequences: String,
+    /// The [`char`]s that the escape character followed by the escaped [`char`] of a [`Rule`], a
+    /// [`PositionalRule`] or an alias stands for, sorted by the escaped [`char`].
+    ///
+    /// [`Rule`]: crate::Rule
+    /// [`PositionalRule`]: crate::PositionalRule
+    unescape_table: Vec<(char, char)>,
+}
+
+/// The number of non-ASCII rules up to which they are compared one by one.
+const LINEAR_LIMIT: usize = 8;
+
+/// The escape sequences of the non-ASCII [`char`]s that a [`Rule`] or a [`SequenceRule`]
+/// applies to.
+///
+/// [`Rule`]: crate::Rule
+/// [`SequenceRule`]: crate::SequenceRule
+#[derive(Clone, Debug)]
+enum NonAsciiRules<'a> {
+    /// At most [`LINEAR_LIMIT`] rules, compared one by one.
+    Linear(Vec<(char, EscapeSequence<'a>)>),
+    /// The rules sorted by [`char`], found using a binary search.
+    Sorted(Vec<(char, EscapeSequence<'a>)>),
+}
+
+impl<'a> NonAsciiRules<'a> {
+    /// Picks the lookup that suits the number of `rules`.
+    fn new(mut rules: Vec<(char, EscapeSequence<'a>)>) -> Self {
+        rules.sort_unstable_by_key(|&(c, _)| c);
+        rules.dedup_by_key(|&mut (c, _)| c);
+        if rules.len() <= LINEAR_LIMIT {
+            Self::Linear(rules)
+        } else {
+            Self::Sorted(rules)
+        }
+    }
+
+    /// Returns the escape sequence of `c`, if one of the rules applies to it.
+    fn get(&self, c: char) -> Option<EscapeSequence<'a>> {
+        match self {
+            Self::Linear(rules) => rules
+                .iter()
+                .find(|&&(unescaped, _)| unescaped == c)
+                .map(|&(_, sequence)| sequence),
+            Self::Sorted(rules) => rules
+                .binary_search_by_key(&c, |&(unescaped, _)| unescaped)
+                .ok()
+                .map(|index| rules[index].1),
+        }
+    }
+}
+
+/// What happens to an ASCII [`char`] when escaping it.
+#[derive(Clone, Copy, Debug)]
+enum AsciiEntry<'a> {
+    /// The [`char`] is kept as it is.
+    Keep,
+    /// The [`char`] is replaced by `sequences[start..end]`, i.e. the escape character followed
+    /// by `sequence`.
+    Escape {
+        start: usize,
+        end: usize,
+        sequence: EscapeSequence<'a>,
+    },
+    /// The [`char`] is only escaped at some positions, see [`PositionalRule`].
+    ///
+    /// [`PositionalRule`]: crate::PositionalRule
+    Positional,
+}
+
+/// The replacement of a [`char`] found by [`CompiledEscaper::find()`].
+enum Found<'s, 'a> {
+    /// The escaped form including the escape character.
+    Table(&'s str),
+    /// The escape sequence without the escape character.
+    Sequence(EscapeSequence<'a>),
+}
+
+impl<'a> CompiledEscaper<'a> {
+    /// Precomputes the lookup tables of `escaper`.
+    pub(crate) fn new(escaper: Escaper<'a>) -> Self {
+        let mut ascii_mask = 0;
+        let mut ascii = [AsciiEntry::Keep; 128];
+        let mut sequences = String::new();
+        for (byte, entry) in (0..=127).zip(&mut ascii) {
+            let c = char::from(byte);
+            if let Some(sequence) = escaper.escape_of(c) {
+                let start = sequences.len();
+                sequences.push(escaper.escape_char());
+                sequences.push_str(&sequence);
+                *entry = AsciiEntry::Escape {
+                    start,
+                    end: sequences.len(),
+                    sequence,
+                };
+            } else if escaper
+                .positional_rules()
+                .iter()
+                .any(|rule| rule.unescaped == c)
+            {
+                *entry = AsciiEntry::Positional;
+            }
+            if !matches!(entry, AsciiEntry::Keep) {
+                ascii_mask |= 1 << byte;
+            }
+        }
+
+        let rules = escaper.rules().iter().map(|rule| rule.unescaped);
+        let sequence_rules = escaper.sequence_rules().iter().map(|rule| rule.unescaped);
+        let non_ascii: Vec<_> = rules
+            .chain(sequence_rules)
+            .filter(|c| !c.is_ascii())
+            .filter_map(|c| Some((c, escaper.escape_of(c)?)))
+            .collect();
+        let ascii_only = non_ascii.is_empty()
+            && escaper.class_rules().is_empty()
+            && escaper.fallback().notation().is_none()
+            && escaper
+                .positional_rules()
+                .iter()
+                .all(|rule| rule.unescaped.is_ascii());
+
+        let rules = escaper.rules().iter().chain(escaper.aliases());
+        let positional_rules = escaper.positional_rules().iter().map(|rule| rule.escaped);
+        let mut unescape_table: Vec<_> = rules
+            .map(|rule| rule.escaped)
+            .chain(positional_rules)
+            .filter_map(|escaped| Some((escaped, escaper.unescape_single(escaped)?)))
+            .collect();
+        unescape_table.sort_unstable_by_key(|&(escaped, _)| escaped);
+        unescape_table.dedup_by_key(|&mut (escaped, _)| escaped);
+
+        // if only ASCII `char`s are escaped, the bytes of other `char`s are skipped without
+        // decoding them
+        let search = match escaper.needles() {
+            Some(needles) => ByteSearch::Needles(needles),
+            None => ByteSearch::Set(AsciiSet::from_mask(ascii_mask, !ascii_only)),
+        };
+
+        Self {
+            escaper,
+            ascii,
+            search,
+            non_ascii: NonAsciiRules::new(non_ascii),
+            sequences,
+            unescape_table,
+        }
+    }
+
+    /// Returns the underlying [`Escaper`].
+    pub const fn escaper(&self) -> Escaper<'a> {
+        self.escaper
+    }
+
+    /// Escapes a string like [`Escaper::escape()`] does.
+    pub fn escape<'s>(&self, s: &'s str) -> Cow<'s, str> {
+        let Some((first, _, _)) = self.find(None, s) else {
+            return Cow::Borrowed(s);
+        };
+
+        let mut ret = String::with_capacity(2 * s.len());
+        ret.push_str(&s[..first]);
+        self.escape_into_after(s[..first].chars().next_back(), &s[first..], &mut ret);
+
+        Cow::Owned(ret)
+    }
+
+    /// Escapes a string like [`Escaper::escape_into()`] does.
+    pub fn escape_into(&self, s: &str, out: &mut String) {
+        self.escape_into_after(None, s, out);
+    }
+
+    /// Escapes a string like [`Escaper::try_escape()`] does.
+    ///
+    /// # Errors
+    ///
+    /// If `s` contains a [`char`] that is [rejected](crate::Fallback::Reject).
+    pub fn try_escape<'s>(&self, s: &'s str) -> Result<Cow<'s, str>, RejectedChar> {
+        if let Some((position, c)) = s.char_indices().find(|&(_, c)| self.escaper.rejects(c)) {
+            Err(RejectedChar { c, position })
+        } else {
+            Ok(self.escape(s))
+        }
+    }
+
+    /// Escapes a string like [`Escaper::escape_to_writer()`] does.
+    ///
+    /// # Errors
+    ///
+    /// Fails if writing to `writer` fails.
+    pub fn escape_to_writer<W: io::Write>(&self, s: &str, mut writer: W) -> io::Result<()> {
+        self.escape_pieces(None, s, |piece| writer.write_all(piece.as_bytes()))
+    }
+
+    /// Escapes a string like [`Escaper::escape_to_fmt()`] does.
+    ///
+    /// # Errors
+    ///
+    /// Fails if writing to `w` fails.
+    pub fn escape_to_fmt(&self, s: &str, w: &mut impl fmt::Write) -> fmt::Result {
+        self.escape_pieces(None, s, |piece| w.write_str(piece))
+    }
+
+    /// Computes the length in bytes of the escaped form of `s`, like [`Escaper::escaped_len()`]
+    /// does.
+    pub fn escaped_len(&self, s: &str) -> usize {
+        let mut len = 0;
+        let Ok(()) = self.escape_pieces(None, s, |piece| {
+            len += piece.len();
+            Ok::<_, Infallible>(())
+        });
+        len
+    }
+
+    /// Checks if `s` contains any [`char`] that needs to be escaped, like
+    /// [`Escaper::needs_escaping()`] does.
+    pub fn needs_escaping(&self, s: &str) -> bool {
+        self.find(None, s).is_some()
+    }
+
+    /// Returns the escape sequence of `c`, see [`Escaper::escape_of()`].
+    pub fn escape_of(&self, c: char) -> Option<EscapeSequence<'a>> {
+        match u8::try_from(c)
+            .ok()
+            .and_then(|byte| self.ascii.get(usize::from(byte)))
+        {
+            Some(AsciiEntry::Escape { sequence, .. }) => Some(*sequence),
+            Some(AsciiEntry::Keep | AsciiEntry::Positional) => None,
+            None => self
+                .non_ascii
+                .get(c)
+                .or_else(|| self.escaper.escape_by_class(c)),
+        }
+    }
+
+    /// Unescapes a string.
+    ///
+    /// See [`Escaper::unescape()`].
+    ///
+    /// # Errors
+    ///
+    /// If `s` is not properly escaped.
+    pub fn unescape(&self, s: &str) -> Result<String, UnescapeError> {
+        let mut ret = String::with_capacity(s.len());
+        self.unescape_into(s, &mut ret)?;
+        Ok(ret)
+    }
+
+    /// Unescapes a string, borrowing it if it contains no escape sequence.
+    ///
+    /// See [`Escaper::unescape_cow()`].
+    ///
+    /// # Errors
+    ///
+    /// If `s` is not properly escaped.
+    pub fn unescape_cow<'s>(&self, s: &'s str) -> Result<Cow<'s, str>, UnescapeError> {
+        if s.contains(self.escaper.escape_char()) {
+            self.unescape(s).map(Cow::Owned)
+        } else {
+            Ok(Cow::Borrowed(s))
+        }
+    }
+
+    /// Unescapes a string and appends the result to `out`.
+    ///
+    /// See [`Escaper::unescape_into()`].
+    ///
+    /// # Errors
+    ///
+    /// If `s` is not properly escaped. If an error occurs, `out` is left as it was before the
+    /// call.
+    pub fn unescape_into(&self, s: &str, out: &mut String) -> Result<(), UnescapeError> {
+        let original_len = out.len();
+        let result = self.unescape_into_inner(s, out);
+        if result.is_err() {
+            out.truncate(original_len);
+        }
+        result
+    }
+
+    /// Unescapes a string, replacing invalid escape sequences with `'\u{fffd}'`.
+    ///
+    /// See [`Escaper::unescape_lossy()`].
+    pub fn unescape_lossy(&self, s: &str) -> String {
+        let escape_char = self.escaper.escape_char();
+        let mut ret = String::with_capacity(s.len());
+        let mut i = 0;
+        while let Some(offset) = s[i..].find(escape_char) {
+            let start = i + offset;
+            ret.push_str(&s[i..start]);
+            i = start + escape_char.len_utf8();
+            match self.decode(&s[i..]) {
+                Decoded::Valid { unescaped, len } => {
+                    ret.push(unescaped);
+                    i += len;
+                }
+                Decoded::Invalid { len } => {
+                    ret.push_str(DEFAULT_REPLACEMENT);
+                    i += len;
+                }
+                Decoded::Incomplete => {
+                    match self.escaper.trailing_escape() {
+                        TrailingEscape::Error => ret.push_str(DEFAULT_REPLACEMENT),
+                        TrailingEscape::Drop => {}
+                        TrailingEscape::Keep => ret.push_str(&s[start..]),
+                    }
+                    return ret;
+                }
+            }
+        }
+        ret.push_str(&s[i..]);
+
+        ret
+    }
+
+    /// Checks if `s` is properly escaped.
+    ///
+    /// See [`Escaper::is_escaped()`].
+    pub fn is_escaped(&self, s: &str) -> bool {
+        let escape_char = self.escaper.escape_char();
+        let mut prev = None;
+        let mut i = 0;
+        loop {
+            let end = s[i..]
+                .find(escape_char)
+                .map_or(s.len(), |offset| i + offset);
+            let literal = &s[i..end];
+            if self.find(prev, literal).is_some() {
+                return false;
+            }
+            if end == s.len() {
+                return true;
+            }
+
+            i = end + escape_char.len_utf8();
+            match self.decode(&s[i..]) {
+                Decoded::Valid { unescaped, len } => {
+                    prev = Some(unescaped);
+                    i += len;
+                }
+                Decoded::Invalid { .. } => return false,
+                Decoded::Incomplete => {
+                    return self.escaper.trailing_escape() != TrailingEscape::Error
+                }
+            }
+        }
+    }
+
+    fn unescape_into_inner(&self, s: &str, out: &mut String) -> Result<(), UnescapeError> {
+        out.reserve(s.len());
+
+        let escape_char = self.escaper.escape_char();
+        let mut i = 0;
+        while let Some(offset) = s[i..].find(escape_char) {
+            let start = i + offset;
+            out.push_str(&s[i..start]);
+            i = start + escape_char.len_utf8();
+            match self.decode(&s[i..]) {
+                Decoded::Valid { unescaped, len } => {
+                    out.push(unescaped);
+                    i += len;
+                }
+                Decoded::Invalid { len } => {
+                    return Err(UnescapeError::Invalid {
+                        sequence: s[start..i + len].to_owned(),
+                        position: start,
+                    });
+                }
+                Decoded::Incomplete => {
+                    return match self.escaper.trailing_escape() {
+                        TrailingEscape::Error => Err(UnescapeError::Incomplete { position: start }),
+                        TrailingEscape::Drop => Ok(()),
+                        TrailingEscape::Keep => {
+                            out.push_str(&s[start..]);
+                            Ok(())
+                        }
+                    };
+                }
+            }
+        }
+        out.push_str(&s[i..]);
+
+        Ok(())
+    }
+
+    /// Decodes the escape sequence at the start of `rest`, the rest of the string following an
+    /// escape character, like `Escaper::decode()` does.
+    fn decode(&self, rest: &str) -> Decoded {
+        self.escaper
+            .decode_with(rest, true, |escaped| self.unescape_single(escaped))
+    }
+
+    /// Returns the [`char`] that the escape character followed by `escaped` stands for, like
+    /// `Escaper::unescape_single()` does.
+    fn unescape_single(&self, escaped: char) -> Option<char> {
+        match self
+            .unescape_table
+            .binary_search_by_key(&escaped, |&(escaped, _)| escaped)
+        {
+            Ok(index) => Some(self.unescape_table[index].1),
+            // `escaped` might differ from all escaped `char`s in its case
+            Err(_) if self.escaper.is_case_insensitive() => self.escaper.unescape_single(escaped),
+            Err(_) => None,
+        }
+    }
+
+    /// Escapes `s` like [`escape_into()`](Self::escape_into) does, as if `s` directly followed
+    /// `prev`.
+    fn escape_into_after(&self, prev: Option<char>, s: &str, out: &mut String) {
+        out.reserve(s.len());
+
+        let Ok(()) = self.escape_pieces(prev, s, |piece| {
+            out.push_str(piece);
+            Ok::<_, Infallible>(())
+        });
+    }
+
+    /// Calls `write` with consecutive pieces of the escaped form of `s`, as if `s` directly
+    /// followed `prev`.
+    fn escape_pieces<E>(
+        &self,
+        mut prev: Option<char>,
+        s: &str,
+        mut write: impl FnMut(&str) -> Result<(), E>,
+    ) -> Result<(), E> {
+        let mut escape_char = [0; MAX_UTF8_LEN];
+        let escape_char = self.escaper.escape_char().encode_utf8(&mut escape_char);
+
+        let mut start = 0;
+        while let Some((i, c, found)) = self.find(prev, &s[start..]) {
+            write(&s[start..start + i])?;
+            match found {
+                Found::Table(escaped) => write(escaped)?,
+                Found::Sequence(sequence) => {
+                    write(escape_char)?;
+                    write(&sequence)?;
+                }
+            }
+            start += i + c.len_utf8();
+            prev = Some(c);
+        }
+
+        write(&s[start..])
+    }
+
+    /// Returns the byte offset, the [`char`] and the replacement of the first [`char`] of `s`
+    /// that needs to be escaped, as if `s` directly followed `prev`.
+    fn find<'s>(&'s self, mut prev: Option<char>, s: &str) -> Option<(usize, char, Found<'s, 'a>)> {
+        let bytes = s.as_bytes();
+        let mut i = 0;
+        while let Some(offset) = self.search.find(&bytes[i..]) {
+            if offset > 0 {
+                let skipped = bytes[i + offset - 1];
+                // bytes that are not ASCII are only skipped if only ASCII `char`s are escaped, then
+                // positions only depend on whether `prev` is `'\n'` and any non-ASCII `char` will do
+                prev = Some(if skipped.is_ascii() {
+                    char::from(skipped)
+                } else {
+                    char::REPLACEMENT_CHARACTER
+                });
+            }
+            i += offset;
+            let byte = bytes[i];
+
+            let c = if let Some(&entry) = self.ascii.get(usize::from(byte)) {
+                let c = char::from(byte);
+                match entry {
+                    AsciiEntry::Keep => {}
+                    AsciiEntry::Escape { start, end, .. } => {
+                        return Some((i, c, Found::Table(&self.sequences[start..end])));
+                    }
+                    AsciiEntry::Positional => {
+                        if let Some(sequence) = self.escaper.escape_after(prev, c) {
+                            return Some((i, c, Found::Sequence(sequence)));
+                        }
+                    }
+                }
+                c
+            } else {
+                let c = s[i..].chars().next().expect("is a char boundary");
+                if let Some(sequence) = self.escape_non_ascii(prev, c) {
+                    return Some((i, c, Found::Sequence(sequence)));
+                }
+                c
+            };
+            prev = Some(c);
+            i += c.len_utf8();
+        }
+
+        None
+    }
+
+    /// Returns the escape sequence of the non-ASCII [`char`] `c` if it directly follows `prev`,
+    /// like `Escaper::escape_after()` does.
+    fn escape_non_ascii(&self, prev: Option<char>, c: char) -> Option<EscapeSequence<'a>> {
+        self.non_ascii.get(c).or_else(|| {
+            self.escaper
+                .escape_by_class(c)
+                .or_else(|| self.escaper.escape_positional(prev, c))
+        })
+    }
+}
@@ -0,0 +1,140 @@
+use {
+    crate::{Escaper, UnescapeError},
+    std::borrow::Cow,
+};
+
+/// Escaping and unescaping using rules that are known at compile time.
+///
+/// Usually implemented by a unit struct generated by [`static_escaper!`](crate::static_escaper).
+/// Since the rules are compiled into `match` expressions, there is no need to search through
+/// them at runtime. Only [`Rule`](crate::Rule)s are supported. For everything else, use
+/// [`ESCAPER`](Self::ESCAPER).
+///
+/// Implementations must be consistent with [`ESCAPER`](Self::ESCAPER), i.e. [`Escape::escape()`]
+/// and [`Escaper::escape()`] have the same result, and so do the other methods.
+pub trait Escape {
+    /// The escape character.
+    const ESCAPE_CHAR: char;
+
+    /// An [`Escaper`] using the same rules.
+    const ESCAPER: Escaper<'static>;
+
+    /// Returns the [`char`] that `c` is escaped as, or [`None`] if it is left as it is.
+    fn escaped(&self, c: char) -> Option<char>;
+
+    /// Returns the [`char`] that the escape character followed by `escaped` stands for, or
+    /// [`None`] if that is not a valid escape sequence.
+    fn unescaped(&self, escaped: char) -> Option<char>;
+
+    /// Check if the given string contains any [`char`] that needs to be escaped.
+    ///
+    /// See [`Escaper::needs_escaping()`].
+    fn needs_escaping(&self, s: &str) -> bool {
+        s.chars().any(|c| self.escaped(c).is_some())
+    }
+
+    /// Escapes a string.
+    ///
+    /// See [`Escaper::escape()`].
+    fn escape<'s>(&self, s: &'s str) -> Cow<'s, str> {
+        let Some(first) = s.find(|c| self.escaped(c).is_some()) else {
+            return Cow::Borrowed(s);
+        };
+
+        let mut ret = String::with_capacity(2 * s.len());
+        ret.push_str(&s[..first]);
+        self.escape_into(&s[first..], &mut ret);
+
+        Cow::Owned(ret)
+    }
+
+    /// Escapes a string and appends the result to `out`.
+    ///
+    /// See [`Escaper::escape_into()`].
+    fn escape_into(&self, s: &str, out: &mut String) {
+        for c in s.chars() {
+            match self.escaped(c) {
+                Some(escaped) => {
+                    out.push(Self::ESCAPE_CHAR);
+                    out.push(escaped);
+                }
+                None => out.push(c),
+            }
+        }
+    }
+
+    /// Unescapes a string.
+    ///
+    /// See [`Escaper::unescape()`].
+    ///
+    /// # Errors
+    ///
+    /// If `s` is not properly escaped.
+    fn unescape(&self, s: &str) -> Result<String, UnescapeError> {
+        let mut ret = String::with_capacity(s.len());
+        self.unescape_into(s, &mut ret)?;
+
+        Ok(ret)
+    }
+
+    /// Unescapes a string and appends the result to `out`.
+    ///
+    /// See [`Escaper::unescape_into()`].
+    ///
+    /// # Errors
+    ///
+    /// If `s` is not properly escaped. If an error occurs, `out` is left as it was before the
+    /// call.
+    ///
+    /// ```
+    /// # use char_escape::{static_escaper, Escape};
+    /// #
+    /// # static_escaper! {
+    /// #     struct Whitespace {
+    /// #         '\t' => 't',
+    /// #     }
+    /// # }
+    /// #
+    /// let mut buffer = String::from("untouched");
+    ///
+    /// assert!(Whitespace.unescape_into(r"a\tb\", &mut buffer).is_err());
+    /// assert_eq!(buffer, "untouched");
+    /// ```
+    fn unescape_into(&self, s: &str, out: &mut String) -> Result<(), UnescapeError> {
+        let original_len = out.len();
+        let result = unescape_into_inner(self, s, out);
+        if result.is_err() {
+            out.truncate(original_len);
+        }
+        result
+    }
+}
+
+fn unescape_into_inner<E: Escape + ?Sized>(
+    escape: &E,
+    s: &str,
+    out: &mut String,
+) -> Result<(), UnescapeError> {
+    let mut chars = s.char_indices();
+    while let Some((position, c)) = chars.next() {
+        if c != E::ESCAPE_CHAR {
+            out.push(c);
+            continue;
+        }
+
+        let Some((_, escaped)) = chars.next() else {
+            return Err(UnescapeError::Incomplete { position });
+        };
+        match escape.unescaped(escaped) {
+            Some(unescaped) => out.push(unescaped),
+            None => {
+                return Err(UnescapeError::Invalid {
+                    sequence: format!("{c}{escaped}"),
+                    position,
+                })
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,66 @@
+use {
+    crate::Notation,
+    core::fmt::{self, Display},
+    std::error::Error,
+};
+
+/// Determines what happens to [`char`]s outside of the safe alphabet that no rule applies to.
+///
+/// The safe alphabet consists of the printable ASCII [`char`]s, i.e. `' '` to `'~'`. Other
+/// [`char`]s are only affected by the fallback if there is no rule for them.
+///
+/// See [`Escaper::with_fallback()`](crate::Escaper::with_fallback) for more information.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum Fallback {
+    /// Leave the [`char`]s as they are.
+    #[default]
+    Keep,
+    /// Reject the [`char`]s.
+    ///
+    /// [`Escaper::try_escape()`](crate::Escaper::try_escape) fails if the string contains such a
+    /// [`char`]. The infallible escaping methods leave them as they are.
+    Reject,
+    /// Escape the [`char`]s using [`Notation::Hex`](crate::Notation::Hex).
+    Hex,
+    /// Escape the [`char`]s using [`Notation::Unicode`](crate::Notation::Unicode).
+    Unicode,
+}
+
+impl Fallback {
+    /// Returns the notation used to escape [`char`]s, if any.
+    pub(crate) const fn notation(self) -> Option<Notation> {
+        match self {
+            Self::Keep | Self::Reject => None,
+            Self::Hex => Some(Notation::Hex),
+            Self::Unicode => Some(Notation::Unicode),
+        }
+    }
+}
+
+/// Returns `true` if `c` is part of the safe alphabet.
+pub(crate) const fn is_safe(c: char) -> bool {
+    matches!(c, ' '..='~')
+}
+
+/// Results from escaping a [`char`] that is [rejected](Fallback::Reject).
+///
+/// See also [`Escaper::try_escape()`](crate::Escaper::try_escape).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RejectedChar {
+    /// The rejected [`char`].
+    pub c: char,
+    /// The byte offset of the rejected [`char`].
+    pub position: usize,
+}
+
+impl Display for RejectedChar {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "character {:?} at position {} can't be escaped",
+            self.c, self.position
+        )
+    }
+}
+
+impl Error for RejectedChar {}
@@ -9,7 +9,7 @@
 //!
 //! // Define some standard escape sequences.
 //! //
-//! // Note that only chars can be used in rules.
+//! // Note that chars can be escaped as chars or as strings.
 //! // Also note that this is const.
 //! const RULES: Escaper<'static> = char_escape::escaper! {
 //!     escape_char = '\\',
@@ -43,6 +43,8 @@
 //!  - the [`escape()`](Escaper::escape) method
 //!  - the [`unescape()`](Escaper::unescape) method
 //!  - the [`escaper!`] macro
+//!
+//! For common formats, there are ready-made escapers in the [`presets`] module.
 
 #![deny(clippy::all)]
 #![warn(clippy::cargo)]
@@ -53,9 +55,64 @@
 // TODO figure out why
 #![allow(clippy::cargo_common_metadata)]
 
+mod chain;
+mod class;
+mod compiled;
+mod escape;
+mod fallback;
+#[doc(hidden)]
+pub mod macros;
+mod merge;
+mod notation;
+mod oneway;
+mod owned;
+mod positional;
+pub mod presets;
+mod quote;
+mod replace;
+mod scan;
+#[cfg(feature = "smallvec")]
+mod small;
+mod stream;
+
+/// Procedural versions of the macros of this crate.
+///
+/// They report mistakes in the macro input at the offending tokens. Requires the `proc-macro`
+/// feature.
+#[cfg(feature = "proc-macro")]
+pub mod proc_macros {
+    pub use char_escape_macros::{escaper, CharEscape};
+}
+
+pub use {
+    chain::Chain,
+    class::{CharClass, ClassRule},
+    compiled::CompiledEscaper,
+    escape::Escape,
+    fallback::{Fallback, RejectedChar},
+    merge::{MergeError, RuleDiff},
+    notation::Notation,
+    oneway::{ConflictingRules, DecodeOnly, EncodeOnly},
+    owned::EscaperBuf,
+    positional::{Position, PositionalRule},
+    quote::Quoter,
+    replace::{Replacement, Replacer},
+    stream::{EscaperState, InvalidUtf8, UnescaperState},
+};
+
+#[cfg(feature = "smallvec")]
+pub use small::{SmallString, SMALL_STRING_LEN};
+
 use {
-    core::fmt::{self, Display},
-    std::error::Error,
+    crate::scan::{ByteSearch, Needles},
+    core::{
+        convert::Infallible,
+        fmt::{self, Display, Write as _},
+        iter::FusedIterator,
+        ops::{Deref, Range},
+        str::Chars,
+    },
+    std::{borrow::Cow, error::Error, io},
 };
 
 /// The quick and easy way to create an [`Escaper`].
@@ -75,14 +132,18 @@ use {
 /// #
 /// // Note that the escape character doesn't need to be specified, it defaults to '\\'.
 /// let escaper = escaper! {
+/// //  '\\' => '\\', (implicity added)
 ///     '\n' => 'n',
 ///     '\r' => 'r',
 ///     '\t' => 't',
-/// //  '\\' => '\\', (implicity added)
 /// };
 ///
 /// let a_lot_of_boilerplate = Escaper::new('\\', &[
 ///     Rule {
+///         unescaped: '\\',
+///         escaped: '\\',
+///     },
+///     Rule {
 ///         unescaped: '\n',
 ///         escaped: 'n',
 ///     },
@@ -94,10 +155,6 @@ use {
 ///         unescaped: '\t',
 ///         escaped: 't',
 ///     },
-///     Rule {
-///         unescaped: '\\',
-///         escaped: '\\',
-///     },
 /// ]).expect("rules are valid");
 ///
 /// assert_eq!(escaper, a_lot_of_boilerplate);
@@ -110,14 +167,18 @@ use {
 /// let escaper = escaper! {
 ///     escape_char = '#',
 ///     rules = [
+/// //      '#' => '#', (implicitly added)
 ///         '\n' => 'n',
 ///         '\t' => 't',
-/// //      '#' => '#', (implicitly added)
 ///     ],
 /// };
 /// #
 /// # let reference = Escaper::new('#', &[
 /// #     Rule {
+/// #         unescaped: '#',
+/// #         escaped: '#',
+/// #     },
+/// #     Rule {
 /// #         unescaped: '\n',
 /// #         escaped: 'n',
 /// #     },
@@ -125,10 +186,6 @@ use {
 /// #         unescaped: '\t',
 /// #         escaped: 't',
 /// #     },
-/// #     Rule {
-/// #         unescaped: '#',
-/// #         escaped: '#',
-/// #     },
 /// # ]).expect("rules are valid");
 /// #
 /// # assert_eq!(escaper, reference);
@@ -156,51 +213,576 @@ use {
 /// assert_eq!(escaper1, escaper2);
 /// assert_eq!(escaper2, escaper3);
 /// ```
+///
+/// The implicit rule for the escape character can be overridden by listing a rule for it. With
+/// `no_implicit_escape`, no implicit rule is added at all and compilation fails unless the rules
+/// contain one for the escape character.
+///
+/// ```
+/// # use char_escape::escaper;
+/// #
+/// let escaper = escaper! {
+///     no_implicit_escape,
+///     escape_char = '%',
+///     rules = [
+///         '%' => 'p',
+///         '\n' => 'n',
+///     ],
+/// };
+///
+/// assert_eq!(escaper.escape("100%\n"), "100%p%n");
+/// ```
+///
+/// ```compile_fail
+/// # use char_escape::escaper;
+/// #
+/// // error: escaper!: no_implicit_escape requires a rule for the escape character '\'
+/// let escaper = escaper! {
+///     no_implicit_escape,
+///     rules = ['\n' => 'n'],
+/// };
+/// ```
+///
+/// Instead of literals, any `const` expression of type [`char`] can be used, e.g. constants that
+/// are shared with a parser.
+///
+/// ```
+/// # use char_escape::escaper;
+/// #
+/// const DELIM: char = '|';
+/// const SEP: char = ';';
+///
+/// let escaper = escaper! {
+///     DELIM => 'd',
+///     SEP => 's',
+/// };
+///
+/// assert_eq!(escaper.escape("a|b;c"), r"a\db\sc");
+/// ```
+///
+/// A range of [`char`]s, or a single [`char`], can be escaped using a [`Notation`]. This adds a
+/// [`ClassRule`]. The notations [`hex`](Notation::Hex), [`ascii_hex`](Notation::AsciiHex),
+/// [`unicode`](Notation::Unicode), [`utf16`](Notation::Utf16) and [`percent`](Notation::Percent)
+/// can be written in lowercase.
+///
+/// ```
+/// # use char_escape::{escaper, Notation};
+/// #
+/// let escaper = escaper! {
+///     '\n' => 'n',
+///     '\u{0}'..='\u{8}' => hex,
+///     '\u{7f}' => Notation::Octal { max_digits: 3, strict: true },
+/// };
+///
+/// assert_eq!(escaper.escape("\0\n\x08\x7f"), r"\x{00}\n\x{08}\177");
+/// ```
+///
+/// A [`char`] can also be escaped as a string. This adds a [`SequenceRule`].
+///
+/// ```
+/// # use char_escape::escaper;
+/// #
+/// let escaper = escaper! {
+///     '\n' => 'n',
+///     '\0' => "x00",
+///     '\u{1b}' => "e[",
+/// };
+///
+/// assert_eq!(escaper.escape("\0\n\u{1b}"), r"\x00\n\e[");
+/// assert_eq!(escaper.unescape(r"\e[\x00").expect("is properly escaped"), "\u{1b}\0");
+/// ```
+///
+/// An existing `const` [`Escaper`], e.g. a preset, can be extended using `extend`. The rules
+/// listed in the macro are added to its rules and override them. Everything else, including the
+/// escape character, is taken from the extended [`Escaper`] unless specified.
+///
+/// ```
+/// # use char_escape::{escaper, Escaper};
+/// #
+/// const BASE: Escaper<'static> = escaper! {
+///     '\n' => 'n',
+///     '\t' => 't',
+/// };
+///
+/// const EXTENDED: Escaper<'static> = escaper! {
+///     extend = BASE,
+///     rules = [
+///         '%' => 'p',
+///         '\t' => 'T',
+///     ],
+/// };
+///
+/// assert_eq!(EXTENDED.escape("%\t\n"), r"\p\T\n");
+/// ```
+///
+/// The behavior that is configured using the builder methods of [`Escaper`] can be set using
+/// options as well: `trailing_escape` (see [`TrailingEscape`]), `fallback` (see [`Fallback`])
+/// and `case_insensitive`. The variants of [`TrailingEscape`] and [`Fallback`] can be written in
+/// lowercase.
+///
+/// ```
+/// # use char_escape::escaper;
+/// #
+/// let escaper = escaper! {
+///     trailing_escape = drop,
+///     fallback = hex,
+///     case_insensitive = true,
+///     rules = ['\n' => 'n'],
+/// };
+///
+/// assert_eq!(escaper.escape("\n\u{0}"), r"\n\x{00}");
+/// assert_eq!(escaper.unescape(r"\N\").expect("the trailing escape is dropped"), "\n");
+/// ```
+///
+/// [`char`]s that are escaped as themselves can be listed using `self`, like
+/// [`EscaperBuf::escape_same()`].
+///
+/// ```
+/// # use char_escape::escaper;
+/// #
+/// let escaper = escaper! {
+///     escape_char = '%',
+///     self = ['|', ';'],
+///     rules = ['\n' => 'n'],
+/// };
+///
+/// assert_eq!(escaper.escape("a|b;\n"), "a%|b%;%n");
+/// ```
+///
+/// Apart from overriding the rule for the escape character, every [`char`] may only appear once
+/// on each side of the rules, and no escape sequence may be the start of another one (see
+/// [`SequenceRule`]). Otherwise, compilation fails with a message naming the offending [`char`].
+/// The same applies to the rules of an extended [`Escaper`] that are not overridden.
+///
+/// ```compile_fail
+/// # use char_escape::escaper;
+/// #
+/// // error: escaper!: more than one rule escapes U+000A
+/// let escaper = escaper! {
+///     '\n' => 'n',
+///     '\n' => 'x',
+/// };
+/// ```
 #[macro_export]
 macro_rules! escaper {
-    ($(escape_char =)? $escape_char:literal, $(rules =)? [$($unescaped:literal => $escaped:literal),+ $(,)?] $(,)?) => {
+    (@parse [$($options:tt)*] escape_char = $escape_char:expr $(, $($rest:tt)*)?) => {
+        $crate::escaper!(@parse [$($options)* (escape_char $escape_char)] $($($rest)*)?)
+    };
+    (@parse [$($options:tt)*] extend = $base:expr $(, $($rest:tt)*)?) => {
+        $crate::escaper!(@parse [$($options)* (extend $base)] $($($rest)*)?)
+    };
+    (@parse [$($options:tt)*] trailing_escape = $value:expr $(, $($rest:tt)*)?) => {
+        $crate::escaper!(@parse [$($options)* (trailing_escape $value)] $($($rest)*)?)
+    };
+    (@parse [$($options:tt)*] fallback = $value:expr $(, $($rest:tt)*)?) => {
+        $crate::escaper!(@parse [$($options)* (fallback $value)] $($($rest)*)?)
+    };
+    (@parse [$($options:tt)*] case_insensitive = $value:expr $(, $($rest:tt)*)?) => {
+        $crate::escaper!(@parse [$($options)* (case_insensitive $value)] $($($rest)*)?)
+    };
+    (@parse [$($options:tt)*] no_implicit_escape $(, $($rest:tt)*)?) => {
+        $crate::escaper!(@parse [$($options)* (implicit_escape false)] $($($rest)*)?)
+    };
+    (@parse [$($options:tt)*] self = [$($same:expr),* $(,)?] $(, $($rest:tt)*)?) => {
+        $crate::escaper!(@parse [$($options)* (same $($same),*)] $($($rest)*)?)
+    };
+    (@parse [$($options:tt)*] $(rules =)? [$($unescaped:expr => $escaped:expr),* $(,)?] $(, $($rest:tt)*)?) => {
+        $crate::escaper!(@parse [$($options)* (rules $($unescaped => $escaped),*)] $($($rest)*)?)
+    };
+    (@parse [$($options:tt)*]) => {
+        $crate::escaper!(@build $($options)*)
+    };
+    (@option escape_char $escape_char:expr) => {
+        const __ESCAPE_CHAR: ::std::option::Option<::std::primitive::char> =
+            ::std::option::Option::Some($escape_char);
+    };
+    (@option extend $base:expr) => {
+        const __EXTEND: ::std::option::Option<$crate::Escaper<'static>> =
+            ::std::option::Option::Some($base);
+    };
+    (@option trailing_escape $value:expr) => {
+        const __TRAILING_ESCAPE: ::std::option::Option<$crate::TrailingEscape> =
+            ::std::option::Option::Some({
+                #[allow(unused_imports)]
+                use $crate::macros::trailing_escape::*;
+                $value
+            });
+    };
+    (@option fallback $value:expr) => {
+        const __FALLBACK: ::std::option::Option<$crate::Fallback> = ::std::option::Option::Some({
+            #[allow(unused_imports)]
+            use $crate::macros::fallback::*;
+            $value
+        });
+    };
+    (@option case_insensitive $value:expr) => {
+        const __CASE_INSENSITIVE: ::std::option::Option<::std::primitive::bool> =
+            ::std::option::Option::Some($value);
+    };
+    (@option implicit_escape $implicit:literal) => {
+        const __IMPLICIT_ESCAPE: ::std::primitive::bool = $implicit;
+    };
+    (@option same $($same:expr),*) => {
+        const __SAME: &[::std::primitive::char] = &[$($same),*];
+    };
+    (@option rules $($unescaped:expr => $escaped:expr),*) => {
+        const __ENTRIES: &[$crate::macros::Entry] = &[
+            $($crate::macros::Pair($unescaped, $escaped).entry(),)*
+        ];
+    };
+    (@build $(($($option:tt)*))*) => {
         {
-            const escape_char: ::std::primitive::char = $escape_char;
-            const NUM_RULES: ::std::primitive::usize = 1 + $crate::count_rules!($($unescaped => $escaped),+ ,);
-
-            const RULES: [$crate::Rule; NUM_RULES] = [
-                $(
-                    {
-                        const unescaped: ::std::primitive::char = $unescaped;
-                        const escaped: ::std::primitive::char = $escaped;
-
-                        $crate::Rule {
-                            unescaped,
-                            escaped,
-                        }
-                    },
-                )+
-                $crate::Rule {
-                    unescaped: escape_char,
-                    escaped: escape_char,
-                },
-            ];
+            // defaults, shadowed by the options that are specified
+            const __ESCAPE_CHAR: ::std::option::Option<::std::primitive::char> =
+                ::std::option::Option::None;
+            const __EXTEND: ::std::option::Option<$crate::Escaper<'static>> =
+                ::std::option::Option::None;
+            const __TRAILING_ESCAPE: ::std::option::Option<$crate::TrailingEscape> =
+                ::std::option::Option::None;
+            const __FALLBACK: ::std::option::Option<$crate::Fallback> = ::std::option::Option::None;
+            const __CASE_INSENSITIVE: ::std::option::Option<::std::primitive::bool> =
+                ::std::option::Option::None;
+            const __IMPLICIT_ESCAPE: ::std::primitive::bool = true;
+            const __SAME: &[::std::primitive::char] = &[];
+            const __ENTRIES: &[$crate::macros::Entry] = &[];
 
-            $crate::Escaper::new_unchecked(
-                escape_char,
-                &RULES,
-            )
+            {
+                #[allow(unused_imports)]
+                use $crate::macros::notations::*;
+
+                $($crate::escaper!(@option $($option)*);)*
+
+                const __ALL_ENTRIES: [$crate::macros::Entry; __SAME.len() + __ENTRIES.len()] =
+                    $crate::macros::with_same(__SAME, __ENTRIES);
+                const __BASE: $crate::Escaper<'static> = $crate::macros::base(__ESCAPE_CHAR, __EXTEND);
+
+                const __NUM_BASE_RULES: ::std::primitive::usize =
+                    $crate::macros::count_base_rules(&__BASE, __IMPLICIT_ESCAPE);
+                const __NUM_RULES: ::std::primitive::usize =
+                    __NUM_BASE_RULES + $crate::macros::count_rules(&__ALL_ENTRIES);
+                const __RULES: [$crate::Rule; __NUM_RULES] =
+                    $crate::macros::rules(&__BASE, __IMPLICIT_ESCAPE, &__ALL_ENTRIES);
+                const __NUM_SEQUENCE_RULES: ::std::primitive::usize = __BASE.sequence_rules().len()
+                    + $crate::macros::count_sequence_rules(&__ALL_ENTRIES);
+                // a static, since references to constants with destructors are not `'static`
+                static __SEQUENCE_RULES: [$crate::SequenceRule<'static>; __NUM_SEQUENCE_RULES] =
+                    $crate::macros::sequence_rules(__BASE.sequence_rules(), &__ALL_ENTRIES);
+                const __NUM_CLASS_RULES: ::std::primitive::usize =
+                    __BASE.class_rules().len() + $crate::macros::count_class_rules(&__ALL_ENTRIES);
+                const __CLASS_RULES: [$crate::ClassRule; __NUM_CLASS_RULES] =
+                    $crate::macros::class_rules(__BASE.class_rules(), &__ALL_ENTRIES);
+                const _: () = $crate::macros::check_rules(&__RULES, __NUM_BASE_RULES);
+                const _: () = $crate::macros::check_sequences(&__RULES, &__ALL_ENTRIES);
+                const _: () = $crate::macros::check_escape_char_rule(
+                    __BASE.escape_char(),
+                    &__RULES,
+                    __BASE.sequence_rules(),
+                    &__ALL_ENTRIES,
+                );
+
+                $crate::macros::configure(
+                    $crate::macros::extend(__BASE, &__RULES, &__SEQUENCE_RULES, &__CLASS_RULES),
+                    __TRAILING_ESCAPE,
+                    __FALLBACK,
+                    __CASE_INSENSITIVE,
+                )
+            }
+        }
+    };
+    (no_implicit_escape $($rest:tt)*) => {
+        $crate::escaper!(@parse [] no_implicit_escape $($rest)*)
+    };
+    ($option:ident = $($rest:tt)*) => {
+        $crate::escaper!(@parse [] $option = $($rest)*)
+    };
+    ($escape_char:expr, $($rest:tt)*) => {
+        $crate::escaper!(@parse [] escape_char = $escape_char, $($rest)*)
+    };
+    ($($unescaped:expr => $escaped:expr),+ $(,)?) => {
+        $crate::escaper!(@parse [] rules = [$($unescaped => $escaped),+])
+    };
+}
+
+/// Declares a `const` [`Escaper`] created by [`escaper!`].
+///
+/// Accepts a name, optionally preceded by attributes and a visibility, and the input of
+/// [`escaper!`] in braces. Several escapers can be declared at once, separated by `;`. The rules
+/// are appended to the documentation of the constant, so they show up in rustdoc.
+///
+/// ```
+/// use char_escape::declare_escaper;
+///
+/// declare_escaper! {
+///     /// Escapes log messages, so every message fits on one line.
+///     pub LOG_ESCAPER = {
+///         '\n' => 'n',
+///         '\r' => 'r',
+///     };
+///
+///     TSV_ESCAPER = { '\t' => 't', '\n' => 'n' };
+/// }
+///
+/// assert_eq!(LOG_ESCAPER.escape("a\nb"), r"a\nb");
+/// assert_eq!(TSV_ESCAPER.escape("a\tb"), r"a\tb");
+/// ```
+#[macro_export]
+macro_rules! declare_escaper {
+    ($($(#[$attr:meta])* $vis:vis $name:ident = { $($input:tt)* });+ $(;)?) => {
+        $(
+            $(#[$attr])*
+            #[doc = ""]
+            #[doc = "Declared using:"]
+            #[doc = ""]
+            #[doc = "```text"]
+            #[doc = ::core::stringify!($($input)*)]
+            #[doc = "```"]
+            $vis const $name: $crate::Escaper<'static> = $crate::escaper! { $($input)* };
+        )+
+    };
+}
+
+/// Declares a unit struct implementing [`Escape`] with the given rules.
+///
+/// Accepts a struct name, optionally preceded by attributes and a visibility, followed by rules
+/// in braces, either on their own or with an escape character like in [`escaper!`]. Only
+/// [`char`] literals are supported. The rules are checked like in [`escaper!`], which is also
+/// used to create [`Escape::ESCAPER`].
+///
+/// ```
+/// use char_escape::{static_escaper, Escape};
+///
+/// static_escaper! {
+///     /// Escapes line breaks and tabs.
+///     pub struct Whitespace {
+///         '\n' => 'n',
+///         '\t' => 't',
+///     }
+/// }
+///
+/// static_escaper! {
+///     struct Percent {
+///         escape_char = '%',
+///         rules = ['|' => 'p'],
+///     }
+/// }
+///
+/// assert_eq!(Whitespace.escape("a\tb\n"), r"a\tb\n");
+/// assert_eq!(Whitespace.unescape(r"a\tb\n").expect("is properly escaped"), "a\tb\n");
+/// assert_eq!(Percent.escape("100%|"), "100%%%p");
+/// assert_eq!(Percent.escape("100%|"), Percent::ESCAPER.escape("100%|"));
+/// ```
+#[macro_export]
+macro_rules! static_escaper {
+    (
+        $(#[$attr:meta])*
+        $vis:vis struct $name:ident {
+            escape_char = $escape_char:literal,
+            rules = [$($unescaped:literal => $escaped:literal),* $(,)?] $(,)?
+        }
+    ) => {
+        $(#[$attr])*
+        #[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+        $vis struct $name;
+
+        impl $crate::Escape for $name {
+            const ESCAPE_CHAR: ::std::primitive::char = $escape_char;
+
+            const ESCAPER: $crate::Escaper<'static> = $crate::escaper! {
+                escape_char = $escape_char,
+                rules = [$($unescaped => $escaped),*],
+            };
+
+            #[inline]
+            fn escaped(
+                &self,
+                c: ::std::primitive::char,
+            ) -> ::std::option::Option<::std::primitive::char> {
+                match c {
+                    $($unescaped => ::std::option::Option::Some($escaped),)*
+                    _ if c == $escape_char => ::std::option::Option::Some($escape_char),
+                    _ => ::std::option::Option::None,
+                }
+            }
+
+            #[inline]
+            fn unescaped(
+                &self,
+                escaped: ::std::primitive::char,
+            ) -> ::std::option::Option<::std::primitive::char> {
+                const OVERRIDDEN: ::std::primitive::bool = false $(|| $unescaped == $escape_char)*;
+                match escaped {
+                    $($escaped => ::std::option::Option::Some($unescaped),)*
+                    _ if !OVERRIDDEN && escaped == $escape_char => {
+                        ::std::option::Option::Some($escape_char)
+                    }
+                    _ => ::std::option::Option::None,
+                }
+            }
         }
     };
-    ($($unescaped:literal => $escaped:literal),+ $(,)?) => {
-        $crate::escaper!{
-            escape_char = '\\',
-            rules = [$($unescaped => $escaped),+],
+    (
+        $(#[$attr:meta])*
+        $vis:vis struct $name:ident {
+            $($unescaped:literal => $escaped:literal),* $(,)?
+        }
+    ) => {
+        $crate::static_escaper! {
+            $(#[$attr])*
+            $vis struct $name {
+                escape_char = '\\',
+                rules = [$($unescaped => $escaped),*],
+            }
         }
     };
 }
 
-#[doc(hidden)]
+/// Creates a [`DecodeOnly`] from a set of rules.
+///
+/// Accepts rules between [`char`]s, with or without an escape character, like [`escaper!`].
+/// The rules are used as they are: there is no implicit [`Rule`] for the escape character, so it
+/// needs to be listed if it can be escaped. Several rules may unescape to the same [`char`], but
+/// every escaped [`char`] may only appear once, which is checked at compile time.
+///
+/// ```
+/// # use char_escape::{decode_only, DecodeOnly};
+/// #
+/// const DECODER: DecodeOnly<'static> = decode_only! {
+///     escape_char = '%',
+///     rules = [
+///         ' ' => '_',
+///         ' ' => 's',
+///         '%' => '%',
+///     ],
+/// };
+///
+/// assert_eq!(DECODER.unescape("a%_b%sc%%").expect("is properly escaped"), "a b c%");
+/// ```
+///
+/// ```compile_fail
+/// # use char_escape::decode_only;
+/// #
+/// // error: decode_only!: more than one rule is escaped as 's'
+/// let decoder = decode_only! {
+///     ' ' => 's',
+///     '\t' => 's',
+/// };
+/// ```
+#[macro_export]
+macro_rules! decode_only {
+    (
+        escape_char = $escape_char:expr,
+        rules = [$($unescaped:expr => $escaped:expr),* $(,)?] $(,)?
+    ) => {{
+        const __RULES: &[$crate::Rule] = &[$($crate::Rule {
+            unescaped: $unescaped,
+            escaped: $escaped,
+        }),*];
+        const _: () = $crate::macros::check_decode_rules(__RULES);
+        $crate::DecodeOnly::new_unchecked($escape_char, __RULES)
+    }};
+    ($escape_char:expr, $($unescaped:expr => $escaped:expr),* $(,)?) => {
+        $crate::decode_only!(escape_char = $escape_char, rules = [$($unescaped => $escaped),*])
+    };
+    ($($unescaped:expr => $escaped:expr),* $(,)?) => {
+        $crate::decode_only!(escape_char = '\\', rules = [$($unescaped => $escaped),*])
+    };
+}
+
+/// Generates a test module checking that an [`Escaper`] behaves as expected.
+///
+/// For every string in `samples`, the tests check that unescaping its escaped form yields the
+/// original string and that its escaped form is [escaped](Escaper::is_escaped). For every string
+/// in the optional `invalid` list, they check that unescaping fails and that it is not considered
+/// escaped. Since unescaping leaves [`char`]s that should have been escaped as they are, strings
+/// that only contain such [`char`]s don't count as invalid.
+///
+/// The module is only compiled when testing and has access to the items of its parent module.
+/// `escaper` must evaluate to an `Escaper<'static>`, e.g. a `const`.
+///
+/// ```
+/// use char_escape::{escaper, escaper_tests, Escaper};
+///
+/// const ESCAPER: Escaper<'static> = escaper! {
+///     '\n' => 'n',
+///     '\t' => 't',
+/// };
+///
+/// escaper_tests! {
+///     mod escaper_tests {
+///         escaper = ESCAPER,
+///         samples = ["", "no escapes", "line\nbreak", "\\\t\\"],
+///         invalid = [r"\", r"\x"],
+///     }
+/// }
+/// ```
 #[macro_export]
-macro_rules! count_rules {
-    (,) => {0usize};
-    ($_unescaped:literal => $_escaped:literal $(, $unescaped:literal => $escaped:literal)* ,) => {
-        1usize + $crate::count_rules!($($unescaped => $escaped),* ,)
+macro_rules! escaper_tests {
+    (
+        $(#[$attr:meta])*
+        $vis:vis mod $name:ident {
+            escaper = $escaper:expr,
+            samples = [$($sample:expr),* $(,)?]
+            $(, invalid = [$($invalid:expr),* $(,)?])?
+            $(,)?
+        }
+    ) => {
+        $(#[$attr])*
+        #[cfg(test)]
+        $vis mod $name {
+            #[allow(unused_imports)]
+            use super::*;
+
+            const SAMPLES: &[&::std::primitive::str] = &[$($sample),*];
+            const INVALID: &[&::std::primitive::str] = &[$($($invalid),*)?];
+
+            fn escaper() -> $crate::Escaper<'static> {
+                $escaper
+            }
+
+            #[test]
+            fn samples_round_trip() {
+                let escaper = escaper();
+                for sample in SAMPLES {
+                    let escaped = escaper.escape(sample);
+                    match escaper.unescape(&escaped) {
+                        ::std::result::Result::Ok(unescaped) => ::std::assert_eq!(
+                            unescaped, *sample,
+                            "{:?} escaped as {:?} unescapes differently", sample, escaped,
+                        ),
+                        ::std::result::Result::Err(error) => ::std::panic!(
+                            "{:?} escaped as {:?} can't be unescaped: {}", sample, escaped, error,
+                        ),
+                    }
+                }
+            }
+
+            #[test]
+            fn escaped_samples_are_escaped() {
+                let escaper = escaper();
+                for sample in SAMPLES {
+                    let escaped = escaper.escape(sample);
+                    ::std::assert!(
+                        escaper.is_escaped(&escaped),
+                        "{:?} escaped as {:?} is not considered escaped", sample, escaped,
+                    );
+                }
+            }
+
+            #[test]
+            fn invalid_samples_are_rejected() {
+                let escaper = escaper();
+                for invalid in INVALID {
+                    ::std::assert!(
+                        escaper.unescape(invalid).is_err(),
+                        "{:?} unescapes without an error", invalid,
+                    );
+                    ::std::assert!(
+                        !escaper.is_escaped(invalid),
+                        "{:?} is considered escaped", invalid,
+                    );
+                }
+            }
+        }
     };
 }
 
@@ -217,27 +799,208 @@ pub struct Rule {
     pub escaped: char,
 }
 
-/// Escape and unescape strings.
+impl Rule {
+    /// Create a new [`Rule`] that escapes `c` to itself, e.g. `'|'` becomes `\|`.
+    ///
+    /// This is the common way to escape delimiters.
+    ///
+    /// ```
+    /// # use char_escape::Rule;
+    /// #
+    /// assert_eq!(Rule::same('|'), Rule {
+    ///     unescaped: '|',
+    ///     escaped: '|',
+    /// });
+    /// ```
+    pub const fn same(c: char) -> Self {
+        Self {
+            unescaped: c,
+            escaped: c,
+        }
+    }
+}
+
+impl From<(char, char)> for Rule {
+    /// Creates a [`Rule`] from a tuple of the form `(unescaped, escaped)`.
+    fn from((unescaped, escaped): (char, char)) -> Self {
+        Self { unescaped, escaped }
+    }
+}
+
+/// Defines how one specific [`char`] should be escaped using more than one [`char`].
 ///
-/// See the [crate-level documentation](crate) for more detailed information.
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
-pub struct Escaper<'a> {
-    escape_char: char,
-    rules: &'a [Rule],
+/// Escaping `unescaped` will yield the escape character followed by `escaped` and unescaping
+/// that escape sequence will yield `unescaped`.
+///
+/// The escaped forms of an [`Escaper`] must be _prefix-free_, i.e. neither the `escaped` string of
+/// a [`SequenceRule`] nor the `escaped` [`char`] of a [`Rule`] may be the start of the `escaped`
+/// string of another [`SequenceRule`]. Otherwise, unescaping can't always tell the escape
+/// sequences apart.
+///
+/// See [`Escaper::with_sequence_rules()`] for more information.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SequenceRule<'a> {
+    #[allow(missing_docs)]
+    pub unescaped: char,
+    #[allow(missing_docs)]
+    pub escaped: Cow<'a, str>,
 }
 
-impl<'a> Escaper<'a> {
-    /// Create a new [`Escaper`] without verifying that the `rules` are valid.
+impl<'a> SequenceRule<'a> {
+    /// Create a new [`SequenceRule`] that borrows `escaped`.
     ///
-    /// It is discouraged to use this function. Use the [`escaper!`] macro or [`Escaper::new()`]
-    /// instead.
+    /// This is a shorthand that can be used in `const` contexts.
     ///
-    /// If the `rules` don't contain a [`Rule`] for escaping the escape character, the
-    /// [`escape()`](Self::escape) and [`unescape()`](Self::unescape) methods will behave
-    /// incorrectly.
-    pub const fn new_unchecked(escape_char: char, rules: &'a [Rule]) -> Self {
-        Self { escape_char, rules }
-    }
+    /// ```
+    /// # use {char_escape::SequenceRule, std::borrow::Cow};
+    /// #
+    /// const NUL: SequenceRule<'static> = SequenceRule::new('\0', "x00");
+    ///
+    /// assert_eq!(NUL, SequenceRule {
+    ///     unescaped: '\0',
+    ///     escaped: Cow::Borrowed("x00"),
+    /// });
+    /// ```
+    pub const fn new(unescaped: char, escaped: &'a str) -> Self {
+        Self {
+            unescaped,
+            escaped: Cow::Borrowed(escaped),
+        }
+    }
+
+    /// Returns a [`SequenceRule`] that owns its `escaped` string.
+    pub fn into_owned(self) -> SequenceRule<'static> {
+        SequenceRule {
+            unescaped: self.unescaped,
+            escaped: Cow::Owned(self.escaped.into_owned()),
+        }
+    }
+}
+
+/// The escape sequence of a [`char`], without the leading escape character.
+///
+/// Returned by [`Escaper::escape_of()`]. It dereferences to a [`str`].
+#[derive(Clone, Copy)]
+pub struct EscapeSequence<'a> {
+    repr: SequenceRepr<'a>,
+}
+
+#[derive(Clone, Copy)]
+enum SequenceRepr<'a> {
+    Borrowed(&'a str),
+    Inline {
+        bytes: [u8; INLINE_SEQUENCE_LEN],
+        len: usize,
+    },
+}
+
+impl<'a> EscapeSequence<'a> {
+    const fn borrowed(s: &'a str) -> Self {
+        Self {
+            repr: SequenceRepr::Borrowed(s),
+        }
+    }
+
+    const fn inline(bytes: [u8; INLINE_SEQUENCE_LEN], len: usize) -> Self {
+        Self {
+            repr: SequenceRepr::Inline { bytes, len },
+        }
+    }
+
+    fn from_char(c: char) -> Self {
+        let mut bytes = [0; INLINE_SEQUENCE_LEN];
+        let len = c.encode_utf8(&mut bytes).len();
+
+        Self::inline(bytes, len)
+    }
+
+    /// Returns the escape sequence as a [`str`].
+    pub fn as_str(&self) -> &str {
+        match &self.repr {
+            SequenceRepr::Borrowed(s) => s,
+            SequenceRepr::Inline { bytes, len } => inline_str(&bytes[..*len]),
+        }
+    }
+}
+
+impl Deref for EscapeSequence<'_> {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl fmt::Debug for EscapeSequence<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl Display for EscapeSequence<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl PartialEq for EscapeSequence<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl Eq for EscapeSequence<'_> {}
+
+impl PartialEq<str> for EscapeSequence<'_> {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl PartialEq<&str> for EscapeSequence<'_> {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+/// Escape and unescape strings.
+///
+/// See the [crate-level documentation](crate) for more detailed information.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Escaper<'a> {
+    escape_char: char,
+    rules: &'a [Rule],
+    sequence_rules: &'a [SequenceRule<'a>],
+    class_rules: &'a [ClassRule],
+    positional_rules: &'a [PositionalRule],
+    aliases: &'a [Rule],
+    fallback: Fallback,
+    case_insensitive: bool,
+    trailing_escape: TrailingEscape,
+}
+
+impl<'a> Escaper<'a> {
+    /// Create a new [`Escaper`] without verifying that the `rules` are valid.
+    ///
+    /// It is discouraged to use this function. Use the [`escaper!`] macro, [`Escaper::new()`] or
+    /// [`Escaper::new_const()`] instead.
+    ///
+    /// If the `rules` don't contain a [`Rule`] for escaping the escape character, the
+    /// [`escape()`](Self::escape) and [`unescape()`](Self::unescape) methods will behave
+    /// incorrectly.
+    pub const fn new_unchecked(escape_char: char, rules: &'a [Rule]) -> Self {
+        Self {
+            escape_char,
+            rules,
+            sequence_rules: &[],
+            class_rules: &[],
+            positional_rules: &[],
+            aliases: &[],
+            fallback: Fallback::Keep,
+            case_insensitive: false,
+            trailing_escape: TrailingEscape::Error,
+        }
+    }
 
     /// Create a new [`Escaper`].
     ///
@@ -245,10 +1008,12 @@ impl<'a> Escaper<'a> {
     ///
     /// # Errors
     ///
-    /// If the provided `rules` don't contain a [`Rule`] for escaping the escape character.
+    /// If the provided `rules` don't contain a [`Rule`] for escaping the escape character or if
+    /// two of them have the same unescaped or escaped [`char`], see [`RuleSetError`]. To override
+    /// a [`Rule`] on purpose, use [`EscaperBuf::add_rule()`] instead.
     ///
     /// ```
-    /// # use char_escape::{Escaper, MissingEscapeCharRule, Rule};
+    /// # use char_escape::{ConflictingRules, Escaper, Rule, RuleSetError};
     /// #
     /// let result = Escaper::new('\\', &[
     ///     Rule {
@@ -261,257 +1026,2709 @@ impl<'a> Escaper<'a> {
     ///     },
     /// ]);
     ///
-    /// assert_eq!(result, Err(MissingEscapeCharRule::new()));
+    /// assert_eq!(result, Err(RuleSetError::MissingEscapeCharRule));
+    ///
+    /// let line_feed = Rule {
+    ///     unescaped: '\n',
+    ///     escaped: 'n',
+    /// };
+    /// let carriage_return = Rule {
+    ///     unescaped: '\r',
+    ///     escaped: 'n',
+    /// };
+    /// let rules = [line_feed, carriage_return, Rule::same('\\')];
+    /// let result = Escaper::new('\\', &rules);
+    ///
+    /// assert_eq!(result, Err(RuleSetError::DuplicateEscaped(ConflictingRules {
+    ///     first: line_feed,
+    ///     second: carriage_return,
+    /// })));
     /// ```
-    pub fn new(escape_char: char, rules: &'a [Rule]) -> Result<Self, MissingEscapeCharRule> {
-        if contains_escape_char_rule(escape_char, rules) {
-            Ok(Self { escape_char, rules })
-        } else {
-            Err(MissingEscapeCharRule::new())
+    pub const fn new(escape_char: char, rules: &'a [Rule]) -> Result<Self, RuleSetError> {
+        match validate_rules(escape_char, rules) {
+            Ok(()) => Ok(Self::new_unchecked(escape_char, rules)),
+            Err(error) => Err(error),
         }
     }
 
-    /// Returns a new [`String`] with the [`char`]s escaped according to the specified rules.
+    /// Create a new [`Escaper`] in a `const` context.
+    ///
+    /// Validates the `rules` like [`Escaper::new()`] does, so an invalid set of rules fails to
+    /// compile when used to initialize a `const` or `static`.
+    ///
+    /// # Panics
+    ///
+    /// If the `rules` are not valid, see [`RuleSetError`].
     ///
     /// ```
-    /// # use char_escape::escaper;
+    /// # use char_escape::{Escaper, Rule};
     /// #
-    /// let escaper = escaper! {
-    ///     escape_char = '%',
-    ///     rules = [
-    ///         'a' => 'a',
-    ///         'c' => 'c',
-    ///         'e' => 'e',
-    ///         'p' => 'p',
-    ///         's' => 's',
-    ///     ],
-    /// };
-    ///
-    /// let unescaped = "escaper.escape(\"escaper\")";
-    /// let escaped = "%e%s%c%a%p%er.%e%s%c%a%p%e(\"%e%s%c%a%p%er\")";
+    /// const ESCAPER: Escaper<'static> = Escaper::new_const('\\', &[
+    ///     Rule {
+    ///         unescaped: '\n',
+    ///         escaped: 'n',
+    ///     },
+    ///     Rule::same('\\'),
+    /// ]);
     ///
-    /// assert_eq!(escaper.escape(unescaped), escaped);
+    /// assert_eq!(ESCAPER.escape("a\nb"), r"a\nb");
     /// ```
     ///
-    /// The [`escape()`](Escaper::escape) method iterates over all [`char`]s in the provided string,
-    /// checks if there is a rule for escaping the [`char`] and if there is, it will be replaced by
-    /// the escape sequence defined by the first rule that matches.
+    /// ```compile_fail
+    /// # use char_escape::{Escaper, Rule};
+    /// #
+    /// // there is no rule for escaping '\\'
+    /// const ESCAPER: Escaper<'static> = Escaper::new_const('\\', &[Rule {
+    ///     unescaped: '\n',
+    ///     escaped: 'n',
+    /// }]);
+    /// # let _ = ESCAPER;
+    /// ```
+    pub const fn new_const(escape_char: char, rules: &'a [Rule]) -> Self {
+        match validate_rules(escape_char, rules) {
+            Ok(()) => Self::new_unchecked(escape_char, rules),
+            Err(RuleSetError::MissingEscapeCharRule) => {
+                panic!("no rule escapes the escape character")
+            }
+            Err(RuleSetError::DuplicateRule(_)) => panic!("a rule appears more than once"),
+            Err(RuleSetError::DuplicateUnescaped(_)) => {
+                panic!("two rules escape the same char differently")
+            }
+            Err(RuleSetError::DuplicateEscaped(_)) => {
+                panic!("two rules escape different chars the same way")
+            }
+        }
+    }
+
+    /// Adds rules whose escape sequences consist of more than one [`char`].
     ///
-    /// This means that if there exist multiple rules for escaping the same [`char`], the first one
-    /// in the list is used.
+    /// Any rules that were previously added using this method are replaced. When escaping, the
+    /// [`Rule`]s are checked before the [`SequenceRule`]s. When unescaping, the longest escape
+    /// sequence that matches is used.
     ///
     /// ```
-    /// # use char_escape::escaper;
+    /// # use char_escape::{escaper, Escaper, SequenceRule};
     /// #
-    /// let escaper = escaper! {
-    ///     'a' => 'a',
-    ///     // these rules aren't used
-    ///     'a' => 'b',
-    ///     'a' => 'c',
-    /// };
+    /// const SEQUENCE_RULES: &[SequenceRule<'static>] = &[
+    ///     SequenceRule::new('\0', "x00"),
+    ///     SequenceRule::new('\u{1b}', "e["),
+    /// ];
     ///
-    /// assert_eq!(escaper.escape("all apes are alive"), r"\all \apes \are \alive");
+    /// const ESCAPER: Escaper<'static> = escaper! {
+    ///     '\n' => 'n',
+    /// }
+    /// .with_sequence_rules(SEQUENCE_RULES);
+    ///
+    /// let unescaped = "\u{1b}\0\n";
+    /// let escaped = r"\e[\x00\n";
+    ///
+    /// assert_eq!(ESCAPER.escape(unescaped), escaped);
+    /// assert_eq!(ESCAPER.unescape(escaped).expect("is properly escaped"), unescaped);
     /// ```
+    #[must_use]
+    pub const fn with_sequence_rules(self, sequence_rules: &'a [SequenceRule<'a>]) -> Self {
+        Self {
+            sequence_rules,
+            ..self
+        }
+    }
+
+    /// Adds rules that escape whole classes of [`char`]s.
     ///
-    /// This behaviour can be used to override the default rule for the escape character, since the
-    /// [`escaper!`] macro always appends that rule to the end of the list of rules.
+    /// Any rules that were previously added using this method are replaced. The class rules are
+    /// checked after the [`Rule`]s and [`SequenceRule`]s, in the order they are given.
     ///
     /// ```
-    /// let escape_char_escaper = char_escape::escaper! {
-    ///     '\\' => '#',
-    /// };
+    /// # use char_escape::{escaper, CharClass, ClassRule, Escaper, Notation};
+    /// #
+    /// const ESCAPER: Escaper<'static> = escaper! {
+    ///     '\n' => 'n',
+    /// }
+    /// .with_class_rules(&[ClassRule {
+    ///     class: CharClass::AsciiControl,
+    ///     notation: Notation::Hex,
+    /// }]);
     ///
-    /// assert_eq!(escape_char_escaper.escape("\\"), "\\#");
+    /// let unescaped = "bell\x07\n";
+    /// let escaped = r"bell\x{07}\n";
+    ///
+    /// assert_eq!(ESCAPER.escape(unescaped), escaped);
+    /// assert_eq!(ESCAPER.unescape(escaped).expect("is properly escaped"), unescaped);
     /// ```
-    pub fn escape(&self, s: &str) -> String {
-        let mut ret = String::with_capacity(2 * s.len());
-
-        for c in s.chars() {
-            match self.escape_char(c) {
-                Some(escaped) => {
-                    ret.push(self.escape_char);
-                    ret.push(escaped);
-                }
-                None => ret.push(c),
-            }
+    #[must_use]
+    pub const fn with_class_rules(self, class_rules: &'a [ClassRule]) -> Self {
+        Self {
+            class_rules,
+            ..self
         }
+    }
 
-        debug_assert!(self.is_escaped(&ret));
-
-        ret
+    /// Adds rules that only apply at specific positions.
+    ///
+    /// Any rules that were previously added using this method are replaced. The positional rules
+    /// are checked after all other rules, so they only apply to [`char`]s that would otherwise be
+    /// left as they are. See [`PositionalRule`] for more information.
+    #[must_use]
+    pub const fn with_positional_rules(self, positional_rules: &'a [PositionalRule]) -> Self {
+        Self {
+            positional_rules,
+            ..self
+        }
     }
 
-    fn escape_char(&self, c: char) -> Option<char> {
-        Some(self.rules.iter().find(|rule| rule.unescaped == c)?.escaped)
+    /// Adds rules that are only used for unescaping.
+    ///
+    /// This allows alternative escape sequences for the same [`char`], while escaping always uses
+    /// the canonical escape sequence given by the [rules](Self::rules). Any aliases that were
+    /// previously added using this method are replaced. The [rules](Self::rules) take precedence
+    /// over the aliases.
+    ///
+    /// ```
+    /// # use char_escape::{escaper, Escaper, Rule};
+    /// #
+    /// const ESCAPER: Escaper<'static> = escaper! {
+    ///     '\n' => 'n',
+    /// }
+    /// .with_aliases(&[Rule {
+    ///     unescaped: '\n',
+    ///     escaped: 'N',
+    /// }]);
+    ///
+    /// assert_eq!(ESCAPER.escape("a\nb"), r"a\nb");
+    /// assert_eq!(ESCAPER.unescape(r"a\nb\Nc").expect("is properly escaped"), "a\nb\nc");
+    /// ```
+    #[must_use]
+    pub const fn with_aliases(self, aliases: &'a [Rule]) -> Self {
+        Self { aliases, ..self }
     }
 
-    /// Reverts what [`escape()`](Self::escape) does.
+    /// Sets what happens to [`char`]s outside of the safe alphabet that no rule applies to.
     ///
-    // TODO proptest
-    /// Escaping a string and then unescaping it is guaranteed to always result in the original
-    /// string if the rules are injective (meaning that no two different `chars` are mapped to the
-    /// same escape sequence).
+    /// By default, such [`char`]s are kept as they are. See [`Fallback`] for more information.
     ///
     /// ```
-    /// # use char_escape::escaper;
+    /// # use char_escape::{escaper, Escaper, Fallback};
     /// #
-    /// let escaper = escaper! {
-    ///     ' ' => 'w',
-    /// };
+    /// const ESCAPER: Escaper<'static> = escaper! {
+    ///     '\n' => 'n',
+    /// }
+    /// .with_fallback(Fallback::Hex);
     ///
-    /// let unescaped = escaper.unescape(r"S\wP\wA\wC\wE").expect("is unescapable");
+    /// let unescaped = "Grüße\n\0";
+    /// let escaped = r"Gr\x{fc}\x{df}e\n\x{00}";
     ///
-    /// assert_eq!(unescaped, "S P A C E");
+    /// assert_eq!(ESCAPER.escape(unescaped), escaped);
+    /// assert_eq!(ESCAPER.unescape(escaped).expect("is properly escaped"), unescaped);
     /// ```
+    #[must_use]
+    pub const fn with_fallback(self, fallback: Fallback) -> Self {
+        Self { fallback, ..self }
+    }
+
+    /// Returns the rules that escape whole classes of [`char`]s.
     ///
-    /// # Errors
+    /// See also [`with_class_rules()`](Self::with_class_rules).
+    pub const fn class_rules(&self) -> &'a [ClassRule] {
+        self.class_rules
+    }
+
+    /// Returns the rules that only apply at specific positions.
     ///
-    /// Fails if the string to escape contains an invalid escape sequence ...
+    /// See also [`with_positional_rules()`](Self::with_positional_rules).
+    pub const fn positional_rules(&self) -> &'a [PositionalRule] {
+        self.positional_rules
+    }
+
+    /// Returns the rules that are only used for unescaping.
+    ///
+    /// See also [`with_aliases()`](Self::with_aliases).
+    pub const fn aliases(&self) -> &'a [Rule] {
+        self.aliases
+    }
+
+    /// Returns what happens to [`char`]s outside of the safe alphabet that no rule applies to.
+    ///
+    /// See also [`with_fallback()`](Self::with_fallback).
+    pub const fn fallback(&self) -> Fallback {
+        self.fallback
+    }
+
+    /// Sets whether unescaping ignores the case of escape sequences.
+    ///
+    /// This applies to the escaped [`char`]s of the [rules](Self::rules), the
+    /// [sequence rules](Self::sequence_rules) and the [aliases](Self::aliases). Escaping still
+    /// uses the case given by the rules. If a [`char`] matches one rule exactly and another one
+    /// only when ignoring case, the exact match is used.
     ///
     /// ```
-    /// # use char_escape::{escaper, UnescapeError};
+    /// # use char_escape::{escaper, Escaper};
     /// #
-    /// let escaper = escaper! {
+    /// const ESCAPER: Escaper<'static> = escaper! {
     ///     '\n' => 'n',
     ///     '\t' => 't',
-    /// };
+    /// }
+    /// .with_case_insensitive(true);
     ///
-    /// let error1 = escaper.unescape(r"\nval\d escape sequence");
-    ///
-    /// assert_eq!(error1, Err(UnescapeError::Invalid(r"\d".to_string())));
+    /// assert_eq!(ESCAPER.escape("\t\n"), r"\t\n");
+    /// assert_eq!(ESCAPER.unescape(r"\T\N\t").expect("is properly escaped"), "\t\n\t");
     /// ```
+    #[must_use]
+    pub const fn with_case_insensitive(self, case_insensitive: bool) -> Self {
+        Self {
+            case_insensitive,
+            ..self
+        }
+    }
+
+    /// Returns `true` if unescaping ignores the case of escape sequences.
+    ///
+    /// See also [`with_case_insensitive()`](Self::with_case_insensitive).
+    pub const fn is_case_insensitive(&self) -> bool {
+        self.case_insensitive
+    }
+
+    /// Sets what happens when unescaping a string that ends in the middle of an escape sequence.
     ///
-    /// ... or if the string to escape ends with the escape character.
+    /// By default, unescaping fails with [`UnescapeError::Incomplete`]. Since [`Escaper`] is
+    /// [`Copy`], the policy can also be chosen for a single call.
     ///
     /// ```
-    /// # use char_escape::{escaper, UnescapeError};
-    /// #
-    /// # let escaper = escaper! {
-    /// #     '\n' => 'n',
-    /// #     '\t' => 't',
-    /// # };
+    /// # use char_escape::{escaper, Escaper, TrailingEscape, UnescapeError};
     /// #
-    /// let error2 = escaper.unescape(r"another failure\");
+    /// const ESCAPER: Escaper<'static> = escaper! {
+    ///     '\n' => 'n',
+    /// }
+    /// .with_trailing_escape(TrailingEscape::Keep);
     ///
-    /// assert_eq!(error2, Err(UnescapeError::Incomplete));
-    /// ```
+    /// assert_eq!(ESCAPER.unescape(r"a\nb\").expect("is kept"), "a\nb\\");
     ///
-    /// Note that [`unescape()`](Escaper::unescape) will _not_ fail if the provided string is not
-    /// properly escaped.
-    pub fn unescape(&self, s: &str) -> Result<String, UnescapeError> {
-        let mut ret = String::with_capacity(s.len());
-        let mut previous_was_escape_char = false;
-        for c in s.chars() {
-            if previous_was_escape_char {
-                ret.push(self.unescape_char(c)?);
-                previous_was_escape_char = false;
-            } else if c == self.escape_char {
-                previous_was_escape_char = true;
-            } else {
-                ret.push(c);
-            }
+    /// let dropping = ESCAPER.with_trailing_escape(TrailingEscape::Drop);
+    /// assert_eq!(dropping.unescape(r"a\nb\").expect("is dropped"), "a\nb");
+    ///
+    /// let strict = ESCAPER.with_trailing_escape(TrailingEscape::Error);
+    /// assert_eq!(strict.unescape(r"a\nb\"), Err(UnescapeError::Incomplete { position: 4 }));
+    /// ```
+    #[must_use]
+    pub const fn with_trailing_escape(self, trailing_escape: TrailingEscape) -> Self {
+        Self {
+            trailing_escape,
+            ..self
         }
+    }
 
-        if previous_was_escape_char {
-            Err(UnescapeError::Incomplete)
-        } else {
-            Ok(ret)
-        }
+    /// Returns what happens when unescaping a string that ends in the middle of an escape
+    /// sequence.
+    ///
+    /// See also [`with_trailing_escape()`](Self::with_trailing_escape).
+    pub const fn trailing_escape(&self) -> TrailingEscape {
+        self.trailing_escape
     }
 
-    fn unescape_char(&self, c: char) -> Result<char, UnescapeError> {
-        self.rules
-            .iter()
-            .find(|rule| rule.escaped == c)
-            .map_or_else(
-                || {
-                    Err(UnescapeError::Invalid(
-                        [self.escape_char, c].into_iter().collect(),
-                    ))
-                },
-                |rule| Ok(rule.unescaped),
-            )
+    /// Returns a [`Chain`] that first applies this [`Escaper`] and then `other` when escaping.
+    ///
+    /// See [`Chain`] for more information.
+    pub fn then(&self, other: Escaper<'a>) -> Chain<'a> {
+        Chain::new(vec![*self, other])
     }
 
-    /// Check if the given string is escaped.
+    /// Creates an [`EscaperBuf`] that owns a copy of the rules.
     ///
-    // TODO proptest
-    /// A string is considered escaped if it contains only valid escape sequences, it contains no
-    /// [`char`] that need to be escaped and it doesn't end with the escape character.
+    /// ```
+    /// # use char_escape::{escaper, EscaperBuf};
+    /// #
+    /// let escaper = escaper! {
+    ///     '\n' => 'n',
+    /// };
     ///
-    // TODO proptest
-    /// If a string is escaped it is guaranteed that [unescaping](Escaper::unescape) it will never
-    /// generate an error.
+    /// let owned: EscaperBuf = escaper.to_escaper_buf();
     ///
-    // TODO proptest
-    /// A string returned by [`escape()`](Self::escape) will always return true when tested if it
-    /// [`is_escaped()`](Self::is_escaped).
+    /// assert_eq!(owned.as_escaper(), escaper);
+    /// ```
+    pub fn to_escaper_buf(&self) -> EscaperBuf {
+        EscaperBuf::from(*self)
+    }
+
+    /// Creates an [`EscaperBuf`] in which the escaped and unescaped [`char`]s of every rule are
+    /// swapped.
     ///
-    /// # Examples
+    /// This is useful if the escaped form is the natural one and the unescaped form is derived
+    /// from it. Only the escape character and the [rules](Self::rules) are used, the other rule
+    /// kinds, the aliases and the fallback can't be inverted and are left out.
+    ///
+    /// # Errors
+    ///
+    /// If two rules have the same escaped [`char`] or no rule has the escape character as its
+    /// escaped [`char`].
     ///
     /// ```
-    /// # use char_escape::escaper;
+    /// # use char_escape::{escaper, Escaper, InverseError, Rule};
     /// #
     /// let escaper = escaper! {
-    ///     escape_char = '\\',
-    ///     rules = [
-    ///         '&' => 'a',
-    ///         '\\' => 'b',
-    ///         '%' => 'm',
-    ///         '|' => 'p',
-    ///         '/' => 's',
-    ///     ],
+    ///     '\n' => 'n',
     /// };
     ///
-    /// // contains invalid escape sequence
-    /// assert_eq!(escaper.is_escaped(r"\a  \b  \c  \d  \e"), false);
+    /// let inverse = escaper.inverse().expect("rules are invertible");
     ///
-    /// // ends with the escape character
-    /// assert_eq!(escaper.is_escaped(r"\a  \b  \m  \p  \s  \"), false);
+    /// // 'n' is escaped as the escape character followed by a line feed
+    /// assert_eq!(inverse.as_escaper().escape("no"), "\\\no");
+    /// assert_eq!(inverse.as_escaper().unescape("\\\no").expect("is properly escaped"), "no");
     ///
-    /// // contains a char that should be escaped
-    /// assert_eq!(escaper.is_escaped(r"\a  \b  \m  \|  \s"), false);
+    /// let rules = [('\n', 'n').into(), ('\r', 'n').into(), Rule::same('\\')];
+    /// let not_invertible = Escaper::new_unchecked('\\', &rules);
     ///
-    /// // and finally... the following is escaped
-    /// assert_eq!(escaper.is_escaped(r"\a  \b  \m  \p  \s"), true);
+    /// assert!(matches!(not_invertible.inverse(), Err(InverseError::ConflictingRules(_))));
     /// ```
-    pub fn is_escaped(&self, s: &str) -> bool {
-        let mut previous_was_escape_char = false;
-        for c in s.chars() {
-            #[allow(clippy::collapsible_else_if)]
-            if previous_was_escape_char {
-                if self
-                    .rules
-                    .iter()
-                    .map(|rule| rule.escaped)
-                    .any(|rule_c| rule_c == c)
-                {
-                    previous_was_escape_char = false;
-                } else {
-                    // invalid escape sequence => the string is not escaped
-                    return false;
-                }
-            } else {
-                if c == self.escape_char {
-                    previous_was_escape_char = true;
-                } else if self
-                    .rules
-                    .iter()
-                    .map(|rule| rule.unescaped)
-                    .any(|needs_to_be_escaped| c == needs_to_be_escaped)
-                {
-                    // character that needs to be escaped but isn't => the string is not escaped
-                    return false;
-                }
-            }
+    pub fn inverse(&self) -> Result<EscaperBuf, InverseError> {
+        let rules = merge::missing_from(self.rules, &[]);
+        if let Some(conflict) = oneway::find_conflict(&rules, |rule| rule.escaped)
+            .or_else(|| oneway::find_conflict(&rules, |rule| rule.unescaped))
+        {
+            return Err(InverseError::ConflictingRules(conflict));
         }
 
-        !previous_was_escape_char
+        let rules = rules
+            .iter()
+            .map(|rule| Rule {
+                unescaped: rule.escaped,
+                escaped: rule.unescaped,
+            })
+            .collect();
+
+        EscaperBuf::new(self.escape_char, rules).map_err(|_| InverseError::MissingEscapeCharRule)
+    }
+
+    /// Returns an [`EscaperBuf`] with the union of the [rules](Self::rules) of both
+    /// [`Escaper`]s.
+    ///
+    /// Rules that both [`Escaper`]s have are only kept once. Only the rules are merged, everything
+    /// else is taken from `self`.
+    ///
+    /// # Errors
+    ///
+    /// If the escape characters differ or a rule of `other` has the same unescaped or escaped
+    /// [`char`] as a different rule of `self`.
+    ///
+    /// ```
+    /// # use char_escape::{escaper, MergeError};
+    /// #
+    /// let tenant = escaper! { '\n' => 'n', '|' => '|' };
+    /// let defaults = escaper! { '\n' => 'n', '\t' => 't' };
+    ///
+    /// let merged = tenant.merged_with(&defaults).expect("rules don't conflict");
+    ///
+    /// assert_eq!(merged.rules().len(), 4);
+    /// assert_eq!(merged.as_escaper().escape("\t|\n"), r"\t\|\n");
+    ///
+    /// let conflicting = escaper! { '\t' => 'n' };
+    /// assert!(matches!(
+    ///     tenant.merged_with(&conflicting),
+    ///     Err(MergeError::ConflictingRules(_))
+    /// ));
+    /// ```
+    pub fn merged_with(&self, other: &Escaper<'_>) -> Result<EscaperBuf, MergeError> {
+        if self.escape_char != other.escape_char {
+            return Err(MergeError::DifferentEscapeChars {
+                first: self.escape_char,
+                second: other.escape_char,
+            });
+        }
+
+        let mut merged = EscaperBuf::from(*self);
+        for rule in merge::missing_from(other.rules, self.rules) {
+            if let Some(conflict) = merge::find_contradiction(self.rules, rule) {
+                return Err(MergeError::ConflictingRules(conflict));
+            }
+            merged.push_rule(rule);
+        }
+
+        Ok(merged)
+    }
+
+    /// Returns the [rules](Self::rules) that are present in only one of the [`Escaper`]s.
+    ///
+    /// ```
+    /// # use char_escape::{escaper, Rule};
+    /// #
+    /// let before = escaper! { '\n' => 'n', '\t' => 't' };
+    /// let after = escaper! { '\n' => 'n', '|' => '|' };
+    ///
+    /// let diff = before.diff(&after);
+    ///
+    /// assert_eq!(diff.added, [Rule::same('|')]);
+    /// assert_eq!(diff.removed, [Rule::from(('\t', 't'))]);
+    /// assert!(before.diff(&before).is_empty());
+    /// ```
+    pub fn diff(&self, other: &Escaper<'_>) -> RuleDiff {
+        RuleDiff {
+            added: merge::missing_from(other.rules, self.rules),
+            removed: merge::missing_from(self.rules, other.rules),
+        }
+    }
+
+    /// Returns the escape character.
+    ///
+    /// ```
+    /// # use char_escape::escaper;
+    /// #
+    /// let escaper = escaper! {
+    ///     escape_char = '%',
+    ///     rules = [
+    ///         '\n' => 'n',
+    ///     ],
+    /// };
+    ///
+    /// assert_eq!(escaper.escape_char(), '%');
+    /// ```
+    pub const fn escape_char(&self) -> char {
+        self.escape_char
+    }
+
+    /// Returns the rules used for escaping and unescaping.
+    ///
+    /// ```
+    /// # use char_escape::{escaper, Rule};
+    /// #
+    /// let escaper = escaper! {
+    ///     '\n' => 'n',
+    /// };
+    ///
+    /// assert_eq!(escaper.rules(), [
+    ///     Rule {
+    ///         unescaped: '\\',
+    ///         escaped: '\\',
+    ///     },
+    ///     Rule {
+    ///         unescaped: '\n',
+    ///         escaped: 'n',
+    ///     },
+    /// ]);
+    /// ```
+    pub const fn rules(&self) -> &'a [Rule] {
+        self.rules
+    }
+
+    /// Returns the rules whose escape sequences consist of more than one [`char`].
+    ///
+    /// See also [`with_sequence_rules()`](Self::with_sequence_rules).
+    pub const fn sequence_rules(&self) -> &'a [SequenceRule<'a>] {
+        self.sequence_rules
+    }
+
+    /// Returns the string with the [`char`]s escaped according to the specified rules.
+    ///
+    /// ```
+    /// # use char_escape::escaper;
+    /// #
+    /// let escaper = escaper! {
+    ///     escape_char = '%',
+    ///     rules = [
+    ///         'a' => 'a',
+    ///         'c' => 'c',
+    ///         'e' => 'e',
+    ///         'p' => 'p',
+    ///         's' => 's',
+    ///     ],
+    /// };
+    ///
+    /// let unescaped = "escaper.escape(\"escaper\")";
+    /// let escaped = "%e%s%c%a%p%er.%e%s%c%a%p%e(\"%e%s%c%a%p%er\")";
+    ///
+    /// assert_eq!(escaper.escape(unescaped), escaped);
+    /// ```
+    ///
+    /// The [`escape()`](Escaper::escape) method iterates over all [`char`]s in the provided string,
+    /// checks if there is a rule for escaping the [`char`] and if there is, it will be replaced by
+    /// the escape sequence defined by the last rule that matches.
+    ///
+    /// This means that if there exist multiple rules for escaping the same [`char`], later rules
+    /// override earlier ones, e.g. a user-defined rule appended to a preset. See also
+    /// [`effective_rule()`](Self::effective_rule).
+    ///
+    /// ```
+    /// # use char_escape::{escaper, EscaperBuf};
+    /// #
+    /// let mut escaper = EscaperBuf::from(escaper! {
+    ///     'a' => 'a',
+    /// });
+    /// // overrides the rule above
+    /// escaper.add_rule(('a', 'b').into()).expect("rules are valid");
+    ///
+    /// assert_eq!(escaper.as_escaper().escape("all apes"), r"\bll \bpes");
+    /// ```
+    ///
+    /// This behaviour can be used to override the default rule for the escape character, since the
+    /// [`escaper!`] macro always inserts that rule at the start of the list of rules.
+    ///
+    /// ```
+    /// let escape_char_escaper = char_escape::escaper! {
+    ///     '\\' => '#',
+    /// };
+    ///
+    /// assert_eq!(escape_char_escaper.escape("\\"), "\\#");
+    /// ```
+    ///
+    /// If `s` contains no [`char`] that needs to be escaped, `s` is returned as is and no memory is
+    /// allocated.
+    ///
+    /// ```
+    /// # use {char_escape::escaper, std::borrow::Cow};
+    /// #
+    /// let escaper = escaper! {
+    ///     '\n' => 'n',
+    /// };
+    ///
+    /// assert!(matches!(escaper.escape("nothing to do"), Cow::Borrowed("nothing to do")));
+    /// assert!(matches!(escaper.escape("line\n"), Cow::Owned(_)));
+    /// ```
+    ///
+    /// If only the [`Rule`]s and [`SequenceRule`]s escape anything and they only escape ASCII
+    /// [`char`]s, the [`char`]s to escape are searched byte by byte without decoding the string.
+    /// If they escape at most three ASCII [`char`]s, a whole block of bytes is compared at a time.
+    /// With the `simd` feature, a block consists of 16 bytes compared using SSE2 on `x86_64`, and
+    /// more ASCII [`char`]s are looked up 16 bytes at a time using SSSE3 on CPUs that support it.
+    pub fn escape<'s>(&self, s: &'s str) -> Cow<'s, str> {
+        let escaped = self.escape_cow(s);
+
+        debug_assert!(self.is_escaped(&escaped));
+
+        escaped
+    }
+
+    /// Escapes a string without checking that the result can be unescaped.
+    fn escape_cow<'s>(&self, s: &'s str) -> Cow<'s, str> {
+        let Some(first) = self.first_to_escape(s) else {
+            return Cow::Borrowed(s);
+        };
+
+        let mut ret = String::with_capacity(2 * s.len());
+        ret.push_str(&s[..first]);
+        self.escape_into_after(s[..first].chars().next_back(), &s[first..], &mut ret);
+
+        Cow::Owned(ret)
+    }
+
+    /// Returns the byte offset of the first [`char`] of `s` that needs to be escaped.
+    fn first_to_escape(&self, s: &str) -> Option<usize> {
+        if let Some(search) = self.byte_search() {
+            return search.find(s.as_bytes());
+        }
+
+        self.sequences(s)
+            .find(|(_, _, sequence)| sequence.is_some())
+            .map(|(i, _, _)| i)
+    }
+
+    /// Returns the byte offset, the [`char`] and its escape sequence (if it needs to be escaped)
+    /// for every [`char`] of `s`.
+    fn sequences<'s>(
+        &'s self,
+        s: &'s str,
+    ) -> impl Iterator<Item = (usize, char, Option<EscapeSequence<'a>>)> + 's {
+        let mut prev = None;
+        s.char_indices().map(move |(i, c)| {
+            let sequence = self.escape_after(prev, c);
+            prev = Some(c);
+            (i, c, sequence)
+        })
+    }
+
+    /// Returns the escape sequence of `c` if it directly follows `prev`, without the leading
+    /// escape character.
+    ///
+    /// `prev` is [`None`] at the start of the string.
+    fn escape_after(&self, prev: Option<char>, c: char) -> Option<EscapeSequence<'a>> {
+        self.escape_of(c)
+            .or_else(|| self.escape_positional(prev, c))
+    }
+
+    /// Returns the escape sequence of `c` according to the [`PositionalRule`]s, if it directly
+    /// follows `prev`.
+    fn escape_positional(&self, prev: Option<char>, c: char) -> Option<EscapeSequence<'a>> {
+        self.positional_rules
+            .iter()
+            .find(|rule| rule.unescaped == c && rule.position.matches(prev))
+            .map(|rule| EscapeSequence::from_char(rule.escaped))
+    }
+
+    /// Escapes a string like [`escape()`](Self::escape) does, unless it contains a [`char`] that is
+    /// [rejected](Fallback::Reject).
+    ///
+    /// # Errors
+    ///
+    /// If `s` contains a [`char`] outside of the safe alphabet that no rule applies to and the
+    /// fallback is [`Fallback::Reject`].
+    ///
+    /// ```
+    /// # use char_escape::{escaper, Fallback, RejectedChar};
+    /// #
+    /// let escaper = escaper! {
+    ///     '\n' => 'n',
+    /// }
+    /// .with_fallback(Fallback::Reject);
+    ///
+    /// assert_eq!(escaper.try_escape("a\nb").expect("is safe"), r"a\nb");
+    /// assert_eq!(
+    ///     escaper.try_escape("a\tb"),
+    ///     Err(RejectedChar { c: '\t', position: 1 }),
+    /// );
+    /// ```
+    pub fn try_escape<'s>(&self, s: &'s str) -> Result<Cow<'s, str>, RejectedChar> {
+        if let Some((position, c)) = s.char_indices().find(|&(_, c)| self.rejects(c)) {
+            Err(RejectedChar { c, position })
+        } else {
+            Ok(self.escape(s))
+        }
+    }
+
+    /// Returns `true` if escaping `c` fails because of [`Fallback::Reject`].
+    fn rejects(&self, c: char) -> bool {
+        self.fallback == Fallback::Reject && !fallback::is_safe(c) && self.escape_of(c).is_none()
+    }
+
+    /// Escapes `s` like [`escape()`](Self::escape) does and additionally collects statistics about
+    /// the escaped [`char`]s.
+    ///
+    /// ```
+    /// # use char_escape::escaper;
+    /// #
+    /// let escaper = escaper! {
+    ///     '\n' => 'n',
+    ///     '\t' => 't',
+    /// };
+    ///
+    /// let (escaped, stats) = escaper.escape_with_stats("a\tb\tc\n");
+    ///
+    /// assert_eq!(escaped, r"a\tb\tc\n");
+    /// assert_eq!(stats.escaped_chars, 3);
+    /// // in the same order as the rules: the implicit rule for '\\', '\n' and '\t'
+    /// assert_eq!(stats.rule_hits, [0, 1, 2]);
+    /// ```
+    pub fn escape_with_stats<'s>(&self, s: &'s str) -> (Cow<'s, str>, EscapeStats) {
+        let mut stats = EscapeStats {
+            escaped_chars: 0,
+            rule_hits: vec![0; self.rules.len()],
+            sequence_rule_hits: vec![0; self.sequence_rules.len()],
+        };
+
+        let mut escaped = None;
+        let mut start = 0;
+        let mut prev = None;
+        for (i, c) in s.char_indices() {
+            let sequence = if let Some(index) = self.rule_index_of(c) {
+                stats.rule_hits[index] += 1;
+                Some(EscapeSequence::from_char(self.rules[index].escaped))
+            } else if let Some(index) = self.sequence_rule_index_of(c) {
+                stats.sequence_rule_hits[index] += 1;
+                Some(EscapeSequence::borrowed(
+                    &self.sequence_rules[index].escaped,
+                ))
+            } else {
+                self.escape_by_class(c)
+                    .or_else(|| self.escape_positional(prev, c))
+            };
+            prev = Some(c);
+
+            if let Some(sequence) = sequence {
+                stats.escaped_chars += 1;
+                let ret = escaped.get_or_insert_with(|| String::with_capacity(2 * s.len()));
+                ret.push_str(&s[start..i]);
+                ret.push(self.escape_char);
+                ret.push_str(&sequence);
+                start = i + c.len_utf8();
+            }
+        }
+
+        let escaped = match escaped {
+            Some(mut ret) => {
+                ret.push_str(&s[start..]);
+                Cow::Owned(ret)
+            }
+            None => Cow::Borrowed(s),
+        };
+
+        debug_assert!(self.is_escaped(&escaped));
+
+        (escaped, stats)
+    }
+
+    /// Escapes `s` like [`escape()`](Self::escape) does, but appends the result to `out`.
+    ///
+    /// This allows reusing the same buffer for escaping many strings.
+    ///
+    /// ```
+    /// # use char_escape::escaper;
+    /// #
+    /// let escaper = escaper! {
+    ///     '\n' => 'n',
+    /// };
+    ///
+    /// let mut buffer = String::from("escaped: ");
+    /// escaper.escape_into("a\nb", &mut buffer);
+    ///
+    /// assert_eq!(buffer, "escaped: a\\nb");
+    /// ```
+    pub fn escape_into(&self, s: &str, out: &mut String) {
+        self.escape_into_after(None, s, out);
+    }
+
+    /// Escapes `s` like [`escape()`](Self::escape) does, but splits it into chunks that are
+    /// escaped in parallel using [`rayon`].
+    ///
+    /// Only strings of more than a megabyte are split, shorter ones are escaped like
+    /// [`escape()`](Self::escape) does. Requires the `rayon` feature.
+    ///
+    /// ```
+    /// # use char_escape::escaper;
+    /// #
+    /// let escaper = escaper! {
+    ///     '\n' => 'n',
+    /// };
+    ///
+    /// let log = "line\n".repeat(500_000);
+    ///
+    /// assert_eq!(escaper.escape_parallel(&log), escaper.escape(&log));
+    /// ```
+    #[cfg(feature = "rayon")]
+    pub fn escape_parallel<'s>(&self, s: &'s str) -> Cow<'s, str> {
+        use rayon::iter::{IntoParallelIterator, ParallelIterator};
+
+        if s.len() <= PARALLEL_CHUNK_LEN {
+            return self.escape(s);
+        }
+
+        let mut chunks = Vec::with_capacity(s.len() / PARALLEL_CHUNK_LEN + 1);
+        let mut start = 0;
+        while start < s.len() {
+            let mut end = (start + PARALLEL_CHUNK_LEN).min(s.len());
+            while !s.is_char_boundary(end) {
+                end += 1;
+            }
+            chunks.push(start..end);
+            start = end;
+        }
+
+        let escaped: Vec<Cow<str>> = chunks
+            .into_par_iter()
+            .map(|chunk| {
+                let prev = s[..chunk.start].chars().next_back();
+                let chunk = &s[chunk];
+                // a chunk is written as one piece if nothing in it is escaped
+                let mut pieces = 0;
+                let mut out = String::new();
+                let Ok(()) = self.escape_pieces(prev, chunk, |piece| {
+                    pieces += 1;
+                    out.push_str(piece);
+                    Ok::<_, Infallible>(())
+                });
+                if pieces > 1 {
+                    Cow::Owned(out)
+                } else {
+                    Cow::Borrowed(chunk)
+                }
+            })
+            .collect();
+
+        if escaped
+            .iter()
+            .all(|chunk| matches!(chunk, Cow::Borrowed(_)))
+        {
+            Cow::Borrowed(s)
+        } else {
+            Cow::Owned(escaped.concat())
+        }
+    }
+
+    /// Escapes `s` like [`escape()`](Self::escape) does, but returns a [`SmallString`].
+    ///
+    /// If the escaped string is at most [`SMALL_STRING_LEN`] bytes long, no memory is allocated.
+    /// Requires the `smallvec` feature.
+    ///
+    /// ```
+    /// # use char_escape::escaper;
+    /// #
+    /// let escaper = escaper! {
+    ///     '\n' => 'n',
+    /// };
+    ///
+    /// let escaped = escaper.escape_small("a\nb");
+    ///
+    /// assert_eq!(escaped, r"a\nb");
+    /// assert!(escaped.is_inline());
+    /// ```
+    #[cfg(feature = "smallvec")]
+    pub fn escape_small(&self, s: &str) -> SmallString {
+        let mut ret = SmallString::new();
+        let Ok(()) = self.escape_pieces(None, s, |piece| {
+            ret.push_str(piece);
+            Ok::<_, Infallible>(())
+        });
+        ret
+    }
+
+    /// Unescapes `s` like [`unescape()`](Self::unescape) does, but returns a [`SmallString`].
+    ///
+    /// If the unescaped string is at most [`SMALL_STRING_LEN`] bytes long, no memory is
+    /// allocated. Requires the `smallvec` feature.
+    ///
+    /// ```
+    /// # use char_escape::escaper;
+    /// #
+    /// let escaper = escaper! {
+    ///     '\n' => 'n',
+    /// };
+    ///
+    /// let unescaped = escaper.unescape_small(r"a\nb").expect("is properly escaped");
+    ///
+    /// assert_eq!(unescaped, "a\nb");
+    /// assert!(unescaped.is_inline());
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Fails under the same circumstances as [`unescape()`](Self::unescape).
+    #[cfg(feature = "smallvec")]
+    pub fn unescape_small(&self, s: &str) -> Result<SmallString, UnescapeError> {
+        let mut ret = SmallString::new();
+        for c in self.unescape_chars(s) {
+            ret.push(c?);
+        }
+        Ok(ret)
+    }
+
+    /// Precomputes lookup tables for escaping with these rules, see [`CompiledEscaper`].
+    ///
+    /// ```
+    /// # use char_escape::escaper;
+    /// #
+    /// let escaper = escaper! {
+    ///     '\n' => 'n',
+    /// }
+    /// .compile();
+    ///
+    /// assert_eq!(escaper.escape("a\nb"), r"a\nb");
+    /// ```
+    #[must_use]
+    pub fn compile(&self) -> CompiledEscaper<'a> {
+        CompiledEscaper::new(*self)
+    }
+
+    /// Escapes `s` like [`escape_into()`](Self::escape_into) does, as if `s` directly followed
+    /// `prev`.
+    pub(crate) fn escape_into_after(&self, prev: Option<char>, s: &str, out: &mut String) {
+        out.reserve(s.len());
+
+        let Ok(()) = self.escape_pieces(prev, s, |piece| {
+            out.push_str(piece);
+            Ok::<_, Infallible>(())
+        });
+    }
+
+    /// Escapes `s` like [`escape()`](Self::escape) does, but reuses the allocation of `s`.
+    ///
+    /// The escape sequences are inserted by moving the following bytes towards the end of the
+    /// string. If the capacity of `s` is large enough, no memory is allocated at all.
+    ///
+    /// ```
+    /// # use char_escape::escaper;
+    /// #
+    /// let escaper = escaper! {
+    ///     '\n' => 'n',
+    /// };
+    ///
+    /// let mut s = String::with_capacity(32);
+    /// s.push_str("one\ntwo\nthree");
+    /// let ptr = s.as_ptr();
+    ///
+    /// escaper.escape_in_place(&mut s);
+    ///
+    /// assert_eq!(s, r"one\ntwo\nthree");
+    /// assert_eq!(s.as_ptr(), ptr);
+    /// ```
+    ///
+    /// If an escape sequence is shorter than the [`char`] it replaces, the bytes can't be moved
+    /// towards the end, so the escaped string is created like [`escape()`](Self::escape) does.
+    ///
+    /// ```
+    /// # use char_escape::escaper;
+    /// #
+    /// let escaper = escaper! {
+    ///     '€' => 'e',
+    /// };
+    ///
+    /// let mut s = String::from("x€y€");
+    /// escaper.escape_in_place(&mut s);
+    ///
+    /// assert_eq!(s, r"x\ey\e");
+    /// ```
+    pub fn escape_in_place(&self, s: &mut String) {
+        let Some(first) = self.first_to_escape(s) else {
+            return;
+        };
+
+        let mut escaped_len = first;
+        let mut shrinks = false;
+        let mut prev = s[..first].chars().next_back();
+        for c in s[first..].chars() {
+            let len = self.escape_after(prev, c).map_or(c.len_utf8(), |sequence| {
+                self.escape_char.len_utf8() + sequence.len()
+            });
+            shrinks |= len < c.len_utf8();
+            escaped_len += len;
+            prev = Some(c);
+        }
+
+        if shrinks {
+            *s = self.escape(s).into_owned();
+            return;
+        }
+
+        *s = self.escape_bytes_in_place(core::mem::take(s).into_bytes(), first, escaped_len);
+    }
+
+    /// Escapes the UTF-8 encoded string in `bytes` without allocating a new buffer.
+    ///
+    /// No [`char`] before the byte offset `first` needs to be escaped, no escape sequence is
+    /// shorter than the [`char`] it replaces and the escaped string will be `escaped_len` bytes
+    /// long.
+    fn escape_bytes_in_place(
+        &self,
+        mut bytes: Vec<u8>,
+        first: usize,
+        escaped_len: usize,
+    ) -> String {
+        let mut read = bytes.len();
+        let mut write = escaped_len;
+        bytes.resize(escaped_len, 0);
+
+        // Walk backwards so that no byte is overwritten before it has been read.
+        while read > first {
+            let c = last_char(&bytes[..read]);
+            let start = read - c.len_utf8();
+            // the bytes before `start` haven't been overwritten yet
+            let prev = (start != 0).then(|| last_char(&bytes[..start]));
+
+            if let Some(sequence) = self.escape_after(prev, c) {
+                for part in [sequence.as_str(), self.escape_char.encode_utf8(&mut [0; 4])] {
+                    write -= part.len();
+                    bytes[write..write + part.len()].copy_from_slice(part.as_bytes());
+                }
+            } else {
+                write -= c.len_utf8();
+                bytes.copy_within(start..read, write);
+            }
+
+            read = start;
+        }
+
+        debug_assert_eq!(read, write);
+
+        String::from_utf8(bytes).expect("all escape sequences are valid UTF-8")
+    }
+
+    /// Escapes `s` like [`escape()`](Self::escape) does, but writes the result to `writer`.
+    ///
+    /// No intermediate [`String`] is created, runs of [`char`]s that don't need to be escaped are
+    /// written as is.
+    ///
+    /// ```
+    /// # use char_escape::escaper;
+    /// #
+    /// let escaper = escaper! {
+    ///     '\n' => 'n',
+    /// };
+    ///
+    /// let mut log = Vec::new();
+    /// escaper.escape_to_writer("multi\nline", &mut log).expect("writing to a Vec never fails");
+    ///
+    /// assert_eq!(log, b"multi\\nline");
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Fails if writing to `writer` fails.
+    pub fn escape_to_writer<W: io::Write>(&self, s: &str, mut writer: W) -> io::Result<()> {
+        self.escape_pieces(None, s, |piece| writer.write_all(piece.as_bytes()))
+    }
+
+    /// Escapes `s` like [`escape()`](Self::escape) does, but writes the result to `w`.
+    ///
+    /// This makes it possible to escape strings inside of [`Display`] implementations without
+    /// allocating a temporary [`String`].
+    ///
+    /// ```
+    /// # use {char_escape::{escaper, Escaper}, std::fmt::{self, Display}};
+    /// #
+    /// const ESCAPER: Escaper<'static> = escaper! {
+    ///     '"' => 'q',
+    /// };
+    ///
+    /// struct Quoted<'a>(&'a str);
+    ///
+    /// impl Display for Quoted<'_> {
+    ///     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    ///         f.write_str("\"")?;
+    ///         ESCAPER.escape_to_fmt(self.0, f)?;
+    ///         f.write_str("\"")
+    ///     }
+    /// }
+    ///
+    /// assert_eq!(Quoted("say \"hi\"").to_string(), r#""say \qhi\q""#);
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Fails if writing to `w` fails.
+    pub fn escape_to_fmt(&self, s: &str, w: &mut impl fmt::Write) -> fmt::Result {
+        self.escape_pieces(None, s, |piece| w.write_str(piece))
+    }
+
+    /// Returns a wrapper around `s` that escapes it on the fly when it is formatted.
+    ///
+    /// This is useful in combination with [`format!`], [`write!`] and similar macros since no
+    /// memory needs to be allocated.
+    ///
+    /// ```
+    /// # use char_escape::escaper;
+    /// #
+    /// let escaper = escaper! {
+    ///     '\n' => 'n',
+    /// };
+    ///
+    /// let name = "two\nlines";
+    ///
+    /// assert_eq!(format!("name = {}", escaper.display_escaped(name)), r"name = two\nlines");
+    /// ```
+    pub fn display_escaped(&'a self, s: &'a str) -> Escaped<'a> {
+        Escaped { escaper: self, s }
+    }
+
+    /// Returns a wrapper around `s` that unescapes it on the fly when it is formatted.
+    ///
+    /// Formatting never fails because of invalid escape sequences, they are replaced by a marker
+    /// instead. By default, the marker is [`char::REPLACEMENT_CHARACTER`], a different one can be
+    /// chosen using [`DisplayUnescaped::with_replacement()`]. This makes the wrapper useful for
+    /// debug printing data of unknown quality.
+    ///
+    /// ```
+    /// # use char_escape::escaper;
+    /// #
+    /// let escaper = escaper! {
+    ///     '\n' => 'n',
+    /// };
+    ///
+    /// let wire_data = r"valid\ninvalid\x";
+    ///
+    /// assert_eq!(escaper.display_unescaped(wire_data).to_string(), "valid\ninvalid\u{fffd}");
+    /// assert_eq!(
+    ///     escaper.display_unescaped(wire_data).with_replacement("<?>").to_string(),
+    ///     "valid\ninvalid<?>",
+    /// );
+    /// ```
+    pub fn display_unescaped(&'a self, s: &'a str) -> DisplayUnescaped<'a> {
+        DisplayUnescaped {
+            escaper: self,
+            s,
+            replacement: DEFAULT_REPLACEMENT,
+        }
+    }
+
+    /// Returns an iterator over the [`char`]s of the escaped form of `s`.
+    ///
+    /// The string is escaped lazily, similar to what [`char::escape_debug()`] does.
+    ///
+    /// ```
+    /// # use char_escape::escaper;
+    /// #
+    /// let escaper = escaper! {
+    ///     '\n' => 'n',
+    /// };
+    ///
+    /// let escaped: Vec<char> = escaper.escape_chars("a\nb").collect();
+    ///
+    /// assert_eq!(escaped, ['a', '\\', 'n', 'b']);
+    /// ```
+    pub fn escape_chars(&'a self, s: &'a str) -> EscapeChars<'a> {
+        EscapeChars {
+            escaper: self,
+            chars: s.chars(),
+            pending: None,
+            pending_position: 0,
+            prev: None,
+        }
+    }
+
+    /// Returns an iterator over the [`char`]s of the unescaped form of `s`.
+    ///
+    /// The string is unescaped lazily. If an error is encountered, it is returned and the
+    /// iterator ends.
+    ///
+    /// ```
+    /// # use char_escape::{escaper, UnescapeError};
+    /// #
+    /// let escaper = escaper! {
+    ///     '\n' => 'n',
+    /// };
+    ///
+    /// let mut unescaped = escaper.unescape_chars(r"a\nb\c");
+    ///
+    /// assert_eq!(unescaped.next(), Some(Ok('a')));
+    /// assert_eq!(unescaped.next(), Some(Ok('\n')));
+    /// assert_eq!(unescaped.next(), Some(Ok('b')));
+    /// assert_eq!(unescaped.next(), Some(Err(UnescapeError::Invalid {
+    ///     sequence: r"\c".to_string(),
+    ///     position: 4,
+    /// })));
+    /// assert_eq!(unescaped.next(), None);
+    /// ```
+    ///
+    /// Collecting the iterator into a [`Result`] is equivalent to calling
+    /// [`unescape()`](Self::unescape).
+    ///
+    /// ```
+    /// # use char_escape::escaper;
+    /// #
+    /// # let escaper = escaper! {
+    /// #     '\n' => 'n',
+    /// # };
+    /// #
+    /// let escaped = r"one\ntwo";
+    /// let unescaped: Result<String, _> = escaper.unescape_chars(escaped).collect();
+    ///
+    /// assert_eq!(unescaped, escaper.unescape(escaped));
+    /// ```
+    pub fn unescape_chars(&'a self, s: &'a str) -> UnescapeChars<'a> {
+        UnescapeChars {
+            escaper: self,
+            s,
+            position: 0,
+            failed: false,
+        }
+    }
+
+    /// Returns an iterator that splits `s` into runs of literal [`char`]s and escape sequences.
+    ///
+    /// Every [`Token`] carries the byte range it spans in `s`. Invalid escape sequences are
+    /// returned as well, they just don't have an unescaped [`char`]. This is useful for building
+    /// syntax highlighting or validation on top of an [`Escaper`].
+    ///
+    /// ```
+    /// # use char_escape::{escaper, Token};
+    /// #
+    /// let escaper = escaper! {
+    ///     '\n' => 'n',
+    /// };
+    ///
+    /// let tokens: Vec<Token> = escaper.tokens(r"ab\n\xc\").collect();
+    ///
+    /// assert_eq!(tokens, [
+    ///     Token::Literal {
+    ///         text: "ab",
+    ///         span: 0..2,
+    ///     },
+    ///     Token::Escaped {
+    ///         sequence: r"\n",
+    ///         unescaped: Some('\n'),
+    ///         span: 2..4,
+    ///     },
+    ///     Token::Escaped {
+    ///         sequence: r"\x",
+    ///         unescaped: None,
+    ///         span: 4..6,
+    ///     },
+    ///     Token::Literal {
+    ///         text: "c",
+    ///         span: 6..7,
+    ///     },
+    ///     Token::Escaped {
+    ///         sequence: r"\",
+    ///         unescaped: None,
+    ///         span: 7..8,
+    ///     },
+    /// ]);
+    /// ```
+    pub fn tokens(&'a self, s: &'a str) -> Tokens<'a> {
+        Tokens {
+            escaper: self,
+            s,
+            position: 0,
+        }
+    }
+
+    /// Calls `write` with consecutive pieces of the escaped form of `s`, as if `s` directly
+    /// followed `prev`.
+    fn escape_pieces<E>(
+        &self,
+        mut prev: Option<char>,
+        s: &str,
+        mut write: impl FnMut(&str) -> Result<(), E>,
+    ) -> Result<(), E> {
+        let mut escape_char = [0; MAX_UTF8_LEN];
+        let escape_char = self.escape_char.encode_utf8(&mut escape_char);
+
+        let mut start = 0;
+        if let Some(search) = self.byte_search() {
+            while let Some(offset) = search.find(&s.as_bytes()[start..]) {
+                let i = start + offset;
+                let c = char::from(s.as_bytes()[i]);
+                let sequence = self
+                    .escape_of(c)
+                    .expect("every byte searched for is escaped");
+                write(&s[start..i])?;
+                write(escape_char)?;
+                write(&sequence)?;
+                start = i + 1;
+            }
+            return write(&s[start..]);
+        }
+
+        for (i, c) in s.char_indices() {
+            if let Some(sequence) = self.escape_after(prev, c) {
+                write(&s[start..i])?;
+                write(escape_char)?;
+                write(&sequence)?;
+                start = i + c.len_utf8();
+            }
+            prev = Some(c);
+        }
+
+        write(&s[start..])
+    }
+
+    /// Returns the [`char`]s that need to be escaped, if they can be found by a byte search.
+    ///
+    /// This is the case if only the [`Rule`]s and [`SequenceRule`]s escape anything, and they
+    /// only escape ASCII [`char`]s.
+    fn byte_search(&self) -> Option<ByteSearch> {
+        if !self.class_rules.is_empty()
+            || !self.positional_rules.is_empty()
+            || self.fallback.notation().is_some()
+        {
+            return None;
+        }
+
+        let rules = self.rules.iter().map(|rule| rule.unescaped);
+        let sequence_rules = self.sequence_rules.iter().map(|rule| rule.unescaped);
+        ByteSearch::new(rules.chain(sequence_rules))
+    }
+
+    /// Like [`byte_search()`](Self::byte_search), but only if at most three different ASCII
+    /// [`char`]s are escaped.
+    fn needles(&self) -> Option<Needles> {
+        match self.byte_search()? {
+            ByteSearch::Needles(needles) => Some(needles),
+            ByteSearch::Set(_) => None,
+        }
+    }
+
+    /// Returns the escape sequence of `c`, without the leading escape character.
+    ///
+    /// Returns [`None`] if `c` doesn't need to be escaped.
+    ///
+    /// ```
+    /// # use char_escape::{escaper, SequenceRule};
+    /// #
+    /// let sequence_rules = [SequenceRule::new('\0', "x00")];
+    /// let escaper = escaper! {
+    ///     '\n' => 'n',
+    /// }
+    /// .with_sequence_rules(&sequence_rules);
+    ///
+    /// assert_eq!(escaper.escape_of('\n').as_deref(), Some("n"));
+    /// assert_eq!(escaper.escape_of('\\').as_deref(), Some("\\"));
+    /// assert_eq!(escaper.escape_of('\0').as_deref(), Some("x00"));
+    /// assert_eq!(escaper.escape_of('n'), None);
+    /// ```
+    pub fn escape_of(&self, c: char) -> Option<EscapeSequence<'a>> {
+        if let Some(index) = self.rule_index_of(c) {
+            Some(EscapeSequence::from_char(self.rules[index].escaped))
+        } else if let Some(index) = self.sequence_rule_index_of(c) {
+            let rules = self.sequence_rules;
+            Some(EscapeSequence::borrowed(&rules[index].escaped))
+        } else {
+            self.escape_by_class(c)
+        }
+    }
+
+    /// Returns the escape sequence of `c` according to the [`ClassRule`]s and the fallback.
+    ///
+    /// Only applies if neither a [`Rule`] nor a [`SequenceRule`] applies to `c`.
+    fn escape_by_class(&self, c: char) -> Option<EscapeSequence<'a>> {
+        if let Some(sequence) = self
+            .class_rules
+            .iter()
+            .filter(|rule| rule.class.contains(c))
+            .find_map(|rule| rule.notation.encode(c, self.escape_char))
+        {
+            Some(sequence)
+        } else if fallback::is_safe(c) {
+            None
+        } else {
+            self.fallback.notation()?.encode(c, self.escape_char)
+        }
+    }
+
+    /// Returns the [`Rule`] used to escape `c`.
+    ///
+    /// If there are several rules for `c`, the last one takes precedence. Returns [`None`] if no
+    /// [`Rule`] applies to `c`, even if `c` is escaped some other way, e.g. by a
+    /// [`SequenceRule`].
+    ///
+    /// ```
+    /// # use char_escape::{escaper, EscaperBuf, Rule};
+    /// #
+    /// let mut escaper = EscaperBuf::from(escaper! {
+    ///     '\n' => 'n',
+    /// });
+    /// escaper.add_rule(('\n', 'N').into()).expect("rules are valid");
+    /// let escaper = escaper.as_escaper();
+    ///
+    /// assert_eq!(escaper.effective_rule('\n'), Some(Rule {
+    ///     unescaped: '\n',
+    ///     escaped: 'N',
+    /// }));
+    /// assert_eq!(escaper.effective_rule('\\'), Some(Rule::same('\\')));
+    /// assert_eq!(escaper.effective_rule('n'), None);
+    ///
+    /// // the overridden escape sequence can still be unescaped
+    /// assert_eq!(escaper.unescape(r"\n\N").expect("is properly escaped"), "\n\n");
+    /// ```
+    pub fn effective_rule(&self, c: char) -> Option<Rule> {
+        self.rule_index_of(c).map(|index| self.rules[index])
+    }
+
+    /// Returns the index of the rule used to escape `c`.
+    fn rule_index_of(&self, c: char) -> Option<usize> {
+        self.rules.iter().rposition(|rule| rule.unescaped == c)
+    }
+
+    /// Returns the index of the sequence rule used to escape `c`.
+    fn sequence_rule_index_of(&self, c: char) -> Option<usize> {
+        self.sequence_rules
+            .iter()
+            .rposition(|rule| rule.unescaped == c)
+    }
+
+    /// Returns the [`char`] that the escape sequence consisting of the escape character followed
+    /// by `escaped` stands for.
+    ///
+    /// This is the inverse of [`escape_of()`](Self::escape_of). Returns [`None`] if the escape
+    /// sequence is invalid.
+    ///
+    /// ```
+    /// # use char_escape::{escaper, SequenceRule};
+    /// #
+    /// let sequence_rules = [SequenceRule::new('\0', "x00")];
+    /// let escaper = escaper! {
+    ///     '\n' => 'n',
+    /// }
+    /// .with_sequence_rules(&sequence_rules);
+    ///
+    /// assert_eq!(escaper.unescape_of("n"), Some('\n'));
+    /// assert_eq!(escaper.unescape_of("\\"), Some('\\'));
+    /// assert_eq!(escaper.unescape_of("x00"), Some('\0'));
+    /// assert_eq!(escaper.unescape_of("x0"), None);
+    /// assert_eq!(escaper.unescape_of("\n"), None);
+    /// ```
+    pub fn unescape_of(&self, escaped: &str) -> Option<char> {
+        match self.decode(escaped, true) {
+            Decoded::Valid { unescaped, len } if len == escaped.len() => Some(unescaped),
+            _ => None,
+        }
+    }
+
+    /// Returns the [`char`] that the escape character followed by `escaped` stands for according
+    /// to the [`Rule`]s, the [`PositionalRule`]s and the aliases.
+    fn unescape_single(&self, escaped: char) -> Option<char> {
+        let positional_rules = self.positional_rules.iter().map(|rule| Rule {
+            unescaped: rule.unescaped,
+            escaped: rule.escaped,
+        });
+        // later rules take precedence
+        let mut rules = self
+            .rules
+            .iter()
+            .rev()
+            .copied()
+            .chain(positional_rules)
+            .chain(self.aliases.iter().copied());
+        let rule = rules
+            .clone()
+            .find(|rule| rule.escaped == escaped)
+            .or_else(|| {
+                rules.find(|rule| self.case_insensitive && eq_ignore_case(rule.escaped, escaped))
+            })?;
+
+        Some(rule.unescaped)
+    }
+
+    /// Decodes the escape sequence at the start of `rest`, the string following an escape
+    /// character.
+    ///
+    /// `at_end` indicates that `rest` extends to the end of the input. Otherwise, an escape
+    /// sequence that might be continued after `rest` is incomplete.
+    fn decode(&self, rest: &str, at_end: bool) -> Decoded {
+        self.decode_with(rest, at_end, |escaped| self.unescape_single(escaped))
+    }
+
+    /// Like [`decode()`](Self::decode), but looks up the [`char`] that the escape character
+    /// followed by a single [`char`] stands for using `unescape_single`.
+    fn decode_with(
+        &self,
+        rest: &str,
+        at_end: bool,
+        unescape_single: impl FnOnce(char) -> Option<char>,
+    ) -> Decoded {
+        let notations = self.class_rules.iter().map(|rule| rule.notation);
+        if let Some(decoded) = notations
+            .chain(self.fallback.notation())
+            .find_map(|notation| notation.decode(rest, self.escape_char, at_end))
+        {
+            return decoded;
+        }
+
+        let mut longest = rest.chars().next().and_then(|first| {
+            let unescaped = unescape_single(first)?;
+            Some((unescaped, first.len_utf8()))
+        });
+
+        let mut incomplete = false;
+        for rule in self.sequence_rules.iter().rev() {
+            match match_prefix(rest, &rule.escaped, self.case_insensitive) {
+                PrefixMatch::Full(len) if longest.is_none_or(|(_, longest)| len > longest) => {
+                    longest = Some((rule.unescaped, len));
+                }
+                PrefixMatch::Partial => incomplete = true,
+                _ => {}
+            }
+        }
+
+        if incomplete && !at_end {
+            // a longer sequence rule might match once the input is continued
+            Decoded::Incomplete
+        } else if let Some((unescaped, len)) = longest {
+            Decoded::Valid { unescaped, len }
+        } else if rest.is_empty() || incomplete {
+            Decoded::Incomplete
+        } else {
+            Decoded::Invalid {
+                len: rest.chars().next().map_or(0, char::len_utf8),
+            }
+        }
+    }
+
+    /// Check if the given string contains any [`char`] that needs to be escaped.
+    ///
+    /// If this returns `false`, [`escape()`](Self::escape) would return `s` unchanged.
+    ///
+    /// ```
+    /// # use char_escape::escaper;
+    /// #
+    /// let escaper = escaper! {
+    ///     '\n' => 'n',
+    /// };
+    ///
+    /// assert_eq!(escaper.needs_escaping("clean"), false);
+    /// assert_eq!(escaper.needs_escaping("dirty\n"), true);
+    /// assert_eq!(escaper.needs_escaping(r"escape char \"), true);
+    /// ```
+    pub fn needs_escaping(&self, s: &str) -> bool {
+        self.first_to_escape(s).is_some()
+    }
+
+    /// Computes the length in bytes of the escaped form of `s`.
+    ///
+    /// The result is always equal to `self.escape(s).len()`, but the escaped string isn't created.
+    ///
+    /// ```
+    /// # use char_escape::escaper;
+    /// #
+    /// let escaper = escaper! {
+    ///     '\n' => 'n',
+    ///     'ä' => 'a',
+    /// };
+    ///
+    /// let s = "jä\nja";
+    ///
+    /// assert_eq!(escaper.escaped_len(s), 7);
+    /// assert_eq!(escaper.escaped_len(s), escaper.escape(s).len());
+    /// ```
+    pub fn escaped_len(&self, s: &str) -> usize {
+        self.sequences(s)
+            .map(|(_, c, sequence)| match sequence {
+                Some(sequence) => self.escape_char.len_utf8() + sequence.len(),
+                None => c.len_utf8(),
+            })
+            .sum()
+    }
+
+    /// Computes the length in bytes of the unescaped form of `s`.
+    ///
+    /// The result is always equal to the length of the string returned by
+    /// [`unescape()`](Self::unescape), but the unescaped string isn't created.
+    ///
+    /// ```
+    /// # use char_escape::escaper;
+    /// #
+    /// let escaper = escaper! {
+    ///     '\n' => 'n',
+    ///     'ä' => 'a',
+    /// };
+    ///
+    /// let s = r"j\a\nja";
+    ///
+    /// assert_eq!(escaper.unescaped_len(s), Ok(6));
+    /// assert_eq!(escaper.unescaped_len(s), escaper.unescape(s).map(|s| s.len()));
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Fails under the same circumstances as [`unescape()`](Self::unescape).
+    pub fn unescaped_len(&self, s: &str) -> Result<usize, UnescapeError> {
+        self.unescape_chars(s).map(|c| c.map(char::len_utf8)).sum()
+    }
+
+    /// Reverts what [`escape()`](Self::escape) does.
+    ///
+    // TODO proptest
+    /// Escaping a string and then unescaping it is guaranteed to always result in the original
+    /// string if the rules are injective (meaning that no two different `chars` are mapped to the
+    /// same escape sequence).
+    ///
+    /// ```
+    /// # use char_escape::escaper;
+    /// #
+    /// let escaper = escaper! {
+    ///     ' ' => 'w',
+    /// };
+    ///
+    /// let unescaped = escaper.unescape(r"S\wP\wA\wC\wE").expect("is unescapable");
+    ///
+    /// assert_eq!(unescaped, "S P A C E");
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Fails if the string to escape contains an invalid escape sequence ...
+    ///
+    /// ```
+    /// # use char_escape::{escaper, UnescapeError};
+    /// #
+    /// let escaper = escaper! {
+    ///     '\n' => 'n',
+    ///     '\t' => 't',
+    /// };
+    ///
+    /// let error1 = escaper.unescape(r"\nval\d escape sequence");
+    ///
+    /// assert_eq!(error1, Err(UnescapeError::Invalid {
+    ///     sequence: r"\d".to_string(),
+    ///     position: 5,
+    /// }));
+    /// ```
+    ///
+    /// ... or if the string to escape ends in the middle of an escape sequence.
+    ///
+    /// ```
+    /// # use char_escape::{escaper, UnescapeError};
+    /// #
+    /// # let escaper = escaper! {
+    /// #     '\n' => 'n',
+    /// #     '\t' => 't',
+    /// # };
+    /// #
+    /// let error2 = escaper.unescape(r"another failure\");
+    ///
+    /// assert_eq!(error2, Err(UnescapeError::Incomplete { position: 15 }));
+    /// ```
+    ///
+    /// Note that [`unescape()`](Escaper::unescape) will _not_ fail if the provided string is not
+    /// properly escaped.
+    pub fn unescape(&self, s: &str) -> Result<String, UnescapeError> {
+        let mut ret = String::with_capacity(s.len());
+        self.unescape_into(s, &mut ret)?;
+        Ok(ret)
+    }
+
+    /// Unescapes `s` like [`unescape()`](Self::unescape) does, but borrows `s` if possible.
+    ///
+    /// If `s` doesn't contain the escape character, there is nothing to unescape, so `s` is
+    /// returned as is and no memory is allocated.
+    ///
+    /// ```
+    /// # use {char_escape::escaper, std::borrow::Cow};
+    /// #
+    /// let escaper = escaper! {
+    ///     '\n' => 'n',
+    /// };
+    ///
+    /// assert!(matches!(escaper.unescape_cow("nothing to do"), Ok(Cow::Borrowed("nothing to do"))));
+    /// assert_eq!(escaper.unescape_cow(r"line\n").expect("is properly escaped"), "line\n");
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Fails under the same circumstances as [`unescape()`](Self::unescape).
+    pub fn unescape_cow<'s>(&self, s: &'s str) -> Result<Cow<'s, str>, UnescapeError> {
+        if s.contains(self.escape_char) {
+            self.unescape(s).map(Cow::Owned)
+        } else {
+            Ok(Cow::Borrowed(s))
+        }
+    }
+
+    /// Unescapes `s` like [`unescape()`](Self::unescape) does, but appends the result to `out`.
+    ///
+    /// This allows reusing the same buffer for unescaping many strings.
+    ///
+    /// ```
+    /// # use char_escape::escaper;
+    /// #
+    /// let escaper = escaper! {
+    ///     '\t' => 't',
+    /// };
+    ///
+    /// let mut buffer = String::new();
+    /// for record in [r"a\tb", r"c\td"] {
+    ///     buffer.clear();
+    ///     escaper.unescape_into(record, &mut buffer).expect("is properly escaped");
+    ///     assert_eq!(buffer.split('\t').count(), 2);
+    /// }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Fails under the same circumstances as [`unescape()`](Self::unescape). If an error occurs,
+    /// `out` is left as it was before the call.
+    ///
+    /// ```
+    /// # use char_escape::escaper;
+    /// #
+    /// # let escaper = escaper! {
+    /// #     '\t' => 't',
+    /// # };
+    /// #
+    /// let mut buffer = String::from("untouched");
+    ///
+    /// assert!(escaper.unescape_into(r"a\tb\", &mut buffer).is_err());
+    /// assert_eq!(buffer, "untouched");
+    /// ```
+    pub fn unescape_into(&self, s: &str, out: &mut String) -> Result<(), UnescapeError> {
+        let original_len = out.len();
+        let result = self.unescape_into_inner(s, out);
+        if result.is_err() {
+            out.truncate(original_len);
+        }
+        result
+    }
+
+    fn unescape_into_inner(&self, s: &str, out: &mut String) -> Result<(), UnescapeError> {
+        out.reserve(s.len());
+
+        let mut state = UnescaperState::new(*self);
+        state.feed(s, out)?;
+        state.finish(out)
+    }
+
+    /// Unescapes `s` like [`unescape()`](Self::unescape) does, but never fails.
+    ///
+    /// Invalid escape sequences and a trailing escape character are replaced by
+    /// [`char::REPLACEMENT_CHARACTER`].
+    ///
+    /// ```
+    /// # use char_escape::escaper;
+    /// #
+    /// let escaper = escaper! {
+    ///     '\n' => 'n',
+    /// };
+    ///
+    /// assert_eq!(escaper.unescape_lossy(r"a\nb\xc\"), "a\nb\u{fffd}c\u{fffd}");
+    /// ```
+    pub fn unescape_lossy(&self, s: &str) -> String {
+        self.unescape_lossy_with(s, DEFAULT_REPLACEMENT)
+    }
+
+    /// Unescapes `s` like [`unescape_lossy()`](Self::unescape_lossy) does, but replaces invalid
+    /// escape sequences with `replacement`.
+    ///
+    /// ```
+    /// # use char_escape::escaper;
+    /// #
+    /// let escaper = escaper! {
+    ///     '\n' => 'n',
+    /// };
+    ///
+    /// assert_eq!(escaper.unescape_lossy_with(r"a\nb\xc\", "?"), "a\nb?c?");
+    /// ```
+    pub fn unescape_lossy_with(&self, s: &str, replacement: &str) -> String {
+        let mut ret = String::with_capacity(s.len());
+
+        for token in self.tokens(s) {
+            match token {
+                Token::Literal { text, .. } => ret.push_str(text),
+                Token::Escaped {
+                    unescaped: Some(unescaped),
+                    ..
+                } => ret.push(unescaped),
+                Token::Escaped {
+                    sequence,
+                    unescaped: None,
+                    span,
+                } => {
+                    ret.push_str(self.lossy_replacement(
+                        sequence,
+                        span.end == s.len(),
+                        replacement,
+                    ));
+                }
+            }
+        }
+
+        ret
+    }
+
+    /// Returns what the invalid escape sequence `sequence` is replaced with when unescaping lossily.
+    ///
+    /// `at_end` indicates that `sequence` is at the end of the string.
+    fn lossy_replacement<'s>(
+        &self,
+        sequence: &'s str,
+        at_end: bool,
+        replacement: &'s str,
+    ) -> &'s str {
+        let incomplete = at_end
+            && matches!(
+                self.decode(&sequence[self.escape_char.len_utf8()..], true),
+                Decoded::Incomplete
+            );
+
+        match self.trailing_escape {
+            TrailingEscape::Drop if incomplete => "",
+            TrailingEscape::Keep if incomplete => sequence,
+            _ => replacement,
+        }
+    }
+
+    /// Reads an escaped string from `reader`, unescapes it and writes the result to `writer`.
+    ///
+    /// The input is processed incrementally, it is never held in memory as a whole. This makes it
+    /// possible to unescape inputs that are larger than the available memory.
+    ///
+    /// ```
+    /// # use char_escape::escaper;
+    /// #
+    /// let escaper = escaper! {
+    ///     '\n' => 'n',
+    /// };
+    ///
+    /// let file = "first\\nsecond\\nthird".as_bytes();
+    /// let mut unescaped = Vec::new();
+    /// escaper.unescape_from_reader(file, &mut unescaped).expect("is properly escaped");
+    ///
+    /// assert_eq!(unescaped, b"first\nsecond\nthird");
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Fails if reading from `reader` or writing to `writer` fails.
+    ///
+    /// Also fails with an error of kind [`InvalidData`](io::ErrorKind::InvalidData) if the input
+    /// is not valid UTF-8 or if it cannot be [unescaped](Self::unescape). In the latter case, the
+    /// [`UnescapeError`] can be retrieved using [`io::Error::into_inner()`].
+    ///
+    /// ```
+    /// # use char_escape::{escaper, UnescapeError};
+    /// #
+    /// # let escaper = escaper! {
+    /// #     '\n' => 'n',
+    /// # };
+    /// #
+    /// let error = escaper
+    ///     .unescape_from_reader(r"trailing\".as_bytes(), std::io::sink())
+    ///     .unwrap_err();
+    ///
+    /// assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+    /// assert_eq!(
+    ///     error.into_inner().unwrap().downcast_ref::<UnescapeError>(),
+    ///     Some(&UnescapeError::Incomplete { position: 8 }),
+    /// );
+    /// ```
+    ///
+    /// Note that when an error occurs, part of the unescaped output may already have been written
+    /// to `writer`.
+    pub fn unescape_from_reader<R: io::Read, W: io::Write>(
+        &self,
+        reader: R,
+        writer: W,
+    ) -> io::Result<()> {
+        self.copy_unescaped(reader, writer).map(|_| ())
+    }
+
+    /// Escapes everything read from `reader` and writes the result to `writer`.
+    ///
+    /// The input is read in chunks into a fixed-size buffer, so files of any size can be escaped
+    /// using a constant amount of memory. A chunk may end in the middle of a UTF-8 encoded
+    /// [`char`], see [`EscaperState`].
+    ///
+    /// ```
+    /// # use char_escape::{escaper, CopyStats};
+    /// #
+    /// let escaper = escaper! {
+    ///     '\n' => 'n',
+    /// };
+    ///
+    /// let file = "first\nsecond".as_bytes();
+    /// let mut escaped = Vec::new();
+    /// let stats = escaper.copy_escaped(file, &mut escaped).expect("is valid UTF-8");
+    ///
+    /// assert_eq!(escaped, br"first\nsecond");
+    /// assert_eq!(stats, CopyStats { read: 12, written: 13 });
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Fails if reading from `reader` or writing to `writer` fails. Also fails with an error of
+    /// kind [`InvalidData`](io::ErrorKind::InvalidData) if the input is not valid UTF-8.
+    ///
+    /// Note that when an error occurs, part of the escaped output may already have been written
+    /// to `writer`.
+    pub fn copy_escaped<R: io::Read, W: io::Write>(
+        &self,
+        mut reader: R,
+        mut writer: W,
+    ) -> io::Result<CopyStats> {
+        let mut buf = [0; READ_BUF_LEN];
+        let mut state = EscaperState::new(*self);
+        let mut out = String::new();
+        let mut copied = CopyStats::default();
+
+        loop {
+            let read = match reader.read(&mut buf) {
+                Ok(0) => break,
+                Ok(read) => read,
+                Err(error) if error.kind() == io::ErrorKind::Interrupted => continue,
+                Err(error) => return Err(error),
+            };
+
+            out.clear();
+            state
+                .feed(&buf[..read], &mut out)
+                .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+            writer.write_all(out.as_bytes())?;
+            copied.add(read, out.len());
+        }
+
+        state
+            .finish()
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+        Ok(copied)
+    }
+
+    /// Unescapes everything read from `reader` and writes the result to `writer`, like
+    /// [`unescape_from_reader()`](Self::unescape_from_reader) does.
+    ///
+    /// The input is read in chunks into a fixed-size buffer, so files of any size can be
+    /// unescaped using a constant amount of memory. Escape sequences split across chunks are
+    /// handled like [`UnescaperState`] does.
+    ///
+    /// ```
+    /// # use char_escape::{escaper, CopyStats};
+    /// #
+    /// let escaper = escaper! {
+    ///     '\n' => 'n',
+    /// };
+    ///
+    /// let file = r"first\nsecond".as_bytes();
+    /// let mut unescaped = Vec::new();
+    /// let stats = escaper.copy_unescaped(file, &mut unescaped).expect("is properly escaped");
+    ///
+    /// assert_eq!(unescaped, b"first\nsecond");
+    /// assert_eq!(stats, CopyStats { read: 13, written: 12 });
+    /// ```
+    ///
+    /// The result doesn't depend on where the reads end:
+    ///
+    /// ```
+    /// # use char_escape::presets::{C_STRING, JSON_ASCII};
+    /// use std::io::Read;
+    ///
+    /// for (escaper, escaped) in [(C_STRING, r"\101"), (JSON_ASCII, r"\ud83d\ude00")] {
+    ///     for i in 0..=escaped.len() {
+    ///         let (first, second) = escaped.split_at(i);
+    ///         let mut unescaped = Vec::new();
+    ///         escaper
+    ///             .copy_unescaped(first.as_bytes().chain(second.as_bytes()), &mut unescaped)
+    ///             .expect("is properly escaped");
+    ///
+    ///         let expected = escaper.unescape(escaped).expect("is properly escaped");
+    ///         assert_eq!(unescaped, expected.as_bytes());
+    ///     }
+    /// }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Fails under the same circumstances as
+    /// [`unescape_from_reader()`](Self::unescape_from_reader).
+    pub fn copy_unescaped<R: io::Read, W: io::Write>(
+        &self,
+        mut reader: R,
+        mut writer: W,
+    ) -> io::Result<CopyStats> {
+        let mut copied = CopyStats::default();
+        let mut buf = [0; READ_BUF_LEN];
+        // number of bytes of an incomplete UTF-8 sequence at the start of buf
+        let mut pending = 0;
+        let mut state = UnescaperState::new(*self);
+        let mut out = String::new();
+
+        loop {
+            let read = match reader.read(&mut buf[pending..]) {
+                Ok(0) => break,
+                Ok(read) => read,
+                Err(error) if error.kind() == io::ErrorKind::Interrupted => continue,
+                Err(error) => return Err(error),
+            };
+            let filled = pending + read;
+
+            let chunk = complete_utf8_prefix(&buf[..filled])
+                .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+            let valid_len = chunk.len();
+
+            out.clear();
+            state
+                .feed(chunk, &mut out)
+                .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+            writer.write_all(out.as_bytes())?;
+            copied.add(read, out.len());
+
+            buf.copy_within(valid_len..filled, 0);
+            pending = filled - valid_len;
+        }
+
+        if pending != 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "stream did not end with a complete UTF-8 sequence",
+            ));
+        }
+
+        out.clear();
+        state
+            .finish(&mut out)
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+        writer.write_all(out.as_bytes())?;
+        copied.add(0, out.len());
+
+        Ok(copied)
+    }
+
+    /// Unescapes the escape sequence at the start of `rest`, the string following the escape
+    /// character at the byte offset `position`. See [`decode()`](Self::decode) for the meaning of
+    /// `at_end`.
+    ///
+    /// Returns the unescaped [`char`] and the length of the escape sequence without the escape
+    /// character.
+    pub(crate) fn unescape_sequence(
+        &self,
+        rest: &str,
+        position: usize,
+        at_end: bool,
+    ) -> Result<(char, usize), UnescapeError> {
+        match self.decode(rest, at_end) {
+            Decoded::Valid { unescaped, len } => Ok((unescaped, len)),
+            Decoded::Invalid { len } => Err(UnescapeError::Invalid {
+                sequence: format!("{}{}", self.escape_char, &rest[..len]),
+                position,
+            }),
+            Decoded::Incomplete => Err(UnescapeError::Incomplete { position }),
+        }
+    }
+
+    /// Check if the given string is escaped.
+    ///
+    // TODO proptest
+    /// A string is considered escaped if it contains only valid escape sequences, it contains no
+    /// [`char`] that need to be escaped and it doesn't end with the escape character.
+    ///
+    // TODO proptest
+    /// If a string is escaped it is guaranteed that [unescaping](Escaper::unescape) it will never
+    /// generate an error.
+    ///
+    // TODO proptest
+    /// A string returned by [`escape()`](Self::escape) will always return true when tested if it
+    /// [`is_escaped()`](Self::is_escaped).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use char_escape::escaper;
+    /// #
+    /// let escaper = escaper! {
+    ///     escape_char = '\\',
+    ///     rules = [
+    ///         '&' => 'a',
+    ///         '\\' => 'b',
+    ///         '%' => 'm',
+    ///         '|' => 'p',
+    ///         '/' => 's',
+    ///     ],
+    /// };
+    ///
+    /// // contains invalid escape sequence
+    /// assert_eq!(escaper.is_escaped(r"\a  \b  \c  \d  \e"), false);
+    ///
+    /// // ends with the escape character
+    /// assert_eq!(escaper.is_escaped(r"\a  \b  \m  \p  \s  \"), false);
+    ///
+    /// // contains a char that should be escaped
+    /// assert_eq!(escaper.is_escaped(r"\a  \b  \m  \|  \s"), false);
+    ///
+    /// // and finally... the following is escaped
+    /// assert_eq!(escaper.is_escaped(r"\a  \b  \m  \p  \s"), true);
+    /// ```
+    pub fn is_escaped(&self, s: &str) -> bool {
+        self.find_invalid(s).is_none()
+    }
+
+    /// Finds the first position at which the given string is not [escaped](Self::is_escaped).
+    ///
+    /// Returns the byte offset of the first invalid escape sequence, the first [`char`] that
+    /// should have been escaped or the trailing escape character, whichever comes first. Returns
+    /// [`None`] if the string is escaped.
+    ///
+    /// ```
+    /// # use char_escape::escaper;
+    /// #
+    /// let escaper = escaper! {
+    ///     '\n' => 'n',
+    ///     '"' => 'q',
+    /// };
+    ///
+    /// // invalid escape sequence
+    /// assert_eq!(escaper.find_invalid(r"\n\x"), Some(2));
+    ///
+    /// // char that should have been escaped
+    /// assert_eq!(escaper.find_invalid(r#"say "hi""#), Some(4));
+    ///
+    /// // trailing escape character
+    /// assert_eq!(escaper.find_invalid(r"\q\"), Some(2));
+    ///
+    /// assert_eq!(escaper.find_invalid(r"\qhi\q\n"), None);
+    /// ```
+    pub fn find_invalid(&self, s: &str) -> Option<usize> {
+        self.diagnostics(s)
+            .next()
+            .map(|diagnostic| diagnostic.span().start)
+    }
+
+    /// Finds all the reasons why the given string is not [escaped](Self::is_escaped).
+    ///
+    /// The [`Diagnostic`]s are ordered by their position in the string. If the string is escaped,
+    /// the returned [`Vec`] is empty.
+    ///
+    /// ```
+    /// # use char_escape::{escaper, Diagnostic};
+    /// #
+    /// let escaper = escaper! {
+    ///     '\n' => 'n',
+    ///     '"' => 'q',
+    /// };
+    ///
+    /// assert_eq!(escaper.validate(r#"\x "\n\"#), [
+    ///     Diagnostic::InvalidEscapeSequence { span: 0..2 },
+    ///     Diagnostic::UnescapedChar {
+    ///         c: '"',
+    ///         span: 3..4,
+    ///     },
+    ///     Diagnostic::TrailingEscapeChar { span: 6..7 },
+    /// ]);
+    ///
+    /// assert_eq!(escaper.validate(r"\qok\q"), []);
+    /// ```
+    pub fn validate(&self, s: &str) -> Vec<Diagnostic> {
+        self.diagnostics(s).collect()
+    }
+
+    fn diagnostics<'s>(&'s self, s: &'s str) -> impl Iterator<Item = Diagnostic> + 's {
+        let mut i = 0;
+        // the previous unescaped char
+        let mut prev = None;
+
+        core::iter::from_fn(move || {
+            while let Some(c) = s[i..].chars().next() {
+                let start = i;
+                i += c.len_utf8();
+
+                if c == self.escape_char {
+                    match self.decode(&s[i..], true) {
+                        Decoded::Valid { unescaped, len } => {
+                            i += len;
+                            prev = Some(unescaped);
+                        }
+                        Decoded::Invalid { len } => {
+                            i += len;
+                            return Some(Diagnostic::InvalidEscapeSequence { span: start..i });
+                        }
+                        Decoded::Incomplete => {
+                            i = s.len();
+                            if self.trailing_escape == TrailingEscape::Error {
+                                return Some(Diagnostic::TrailingEscapeChar { span: start..i });
+                            }
+                        }
+                    }
+                } else if self.escape_after(prev, c).is_some() {
+                    return Some(Diagnostic::UnescapedChar { c, span: start..i });
+                } else {
+                    prev = Some(c);
+                }
+            }
+
+            None
+        })
+    }
+}
+
+/// Determines what happens when unescaping a string that ends in the middle of an escape
+/// sequence, e.g. with a trailing escape character.
+///
+/// See [`Escaper::with_trailing_escape()`] for more information.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum TrailingEscape {
+    /// Fail with [`UnescapeError::Incomplete`].
+    #[default]
+    Error,
+    /// Drop the incomplete escape sequence.
+    Drop,
+    /// Keep the incomplete escape sequence as it is.
+    Keep,
+}
+
+/// Statistics about escaping a string.
+///
+/// See also [`Escaper::escape_with_stats()`].
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct EscapeStats {
+    /// The number of [`char`]s that were escaped.
+    pub escaped_chars: usize,
+    /// How often each rule was applied, in the same order as the [rules](Escaper::rules).
+    pub rule_hits: Vec<usize>,
+    /// How often each sequence rule was applied, in the same order as the
+    /// [sequence rules](Escaper::sequence_rules).
+    pub sequence_rule_hits: Vec<usize>,
+}
+
+/// The number of bytes read and written by [`Escaper::copy_escaped()`] or
+/// [`Escaper::copy_unescaped()`].
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct CopyStats {
+    /// The number of bytes read from the reader.
+    pub read: u64,
+    /// The number of bytes written to the writer.
+    pub written: u64,
+}
+
+impl CopyStats {
+    /// Counts another `read` bytes read and `written` bytes written.
+    fn add(&mut self, read: usize, written: usize) {
+        self.read += u64::try_from(read).expect("a buffer fits into a u64");
+        self.written += u64::try_from(written).expect("a buffer fits into a u64");
+    }
+}
+
+/// A reason why a string is not [escaped](Escaper::is_escaped).
+///
+/// See also [`Escaper::validate()`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Diagnostic {
+    /// An escape sequence that is not defined by any rule.
+    InvalidEscapeSequence {
+        /// The byte range of the escape sequence.
+        span: Range<usize>,
+    },
+    /// A [`char`] that needs to be escaped but isn't.
+    UnescapedChar {
+        /// The [`char`] that should have been escaped.
+        c: char,
+        /// The byte range of the [`char`].
+        span: Range<usize>,
+    },
+    /// The escape character at the very end of the string, possibly followed by the beginning of
+    /// an escape sequence.
+    TrailingEscapeChar {
+        /// The byte range of the escape character and the incomplete escape sequence.
+        span: Range<usize>,
+    },
+}
+
+impl Diagnostic {
+    /// Returns the byte range of the problematic part of the string.
+    pub fn span(&self) -> Range<usize> {
+        match self {
+            Self::InvalidEscapeSequence { span }
+            | Self::UnescapedChar { span, .. }
+            | Self::TrailingEscapeChar { span } => span.clone(),
+        }
+    }
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::InvalidEscapeSequence { span } => {
+                write!(f, "invalid escape sequence at {span:?}")
+            }
+            Self::UnescapedChar { c, span } => {
+                write!(f, "unescaped character {c:?} at {span:?}")
+            }
+            Self::TrailingEscapeChar { span } => {
+                write!(f, "trailing escape character at {span:?}")
+            }
+        }
+    }
+}
+
+/// Escapes a string when it is formatted.
+///
+/// Created by [`Escaper::display_escaped()`].
+#[derive(Clone, Copy, Debug)]
+pub struct Escaped<'a> {
+    escaper: &'a Escaper<'a>,
+    s: &'a str,
+}
+
+impl Display for Escaped<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.escaper.escape_to_fmt(self.s, f)
+    }
+}
+
+/// Unescapes a string when it is formatted.
+///
+/// Created by [`Escaper::display_unescaped()`].
+#[derive(Clone, Copy, Debug)]
+pub struct DisplayUnescaped<'a> {
+    escaper: &'a Escaper<'a>,
+    s: &'a str,
+    replacement: &'a str,
+}
+
+impl<'a> DisplayUnescaped<'a> {
+    /// Sets the marker that is written in place of invalid escape sequences.
+    ///
+    /// ```
+    /// # use char_escape::escaper;
+    /// #
+    /// let escaper = escaper! {
+    ///     '\t' => 't',
+    /// };
+    ///
+    /// let unescaped = escaper.display_unescaped(r"\t\?\").with_replacement("");
+    ///
+    /// assert_eq!(unescaped.to_string(), "\t");
+    /// ```
+    #[must_use]
+    pub const fn with_replacement(self, replacement: &'a str) -> Self {
+        Self {
+            replacement,
+            ..self
+        }
+    }
+}
+
+impl Display for DisplayUnescaped<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for token in self.escaper.tokens(self.s) {
+            match token {
+                Token::Literal { text, .. } => f.write_str(text)?,
+                Token::Escaped {
+                    unescaped: Some(unescaped),
+                    ..
+                } => f.write_char(unescaped)?,
+                Token::Escaped {
+                    sequence,
+                    unescaped: None,
+                    span,
+                } => f.write_str(self.escaper.lossy_replacement(
+                    sequence,
+                    span.end == self.s.len(),
+                    self.replacement,
+                ))?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// An iterator over the [`char`]s of an escaped string.
+///
+/// Created by [`Escaper::escape_chars()`].
+#[derive(Clone, Debug)]
+pub struct EscapeChars<'a> {
+    escaper: &'a Escaper<'a>,
+    chars: Chars<'a>,
+    // the rest of an escape sequence whose escape char was already returned
+    pending: Option<EscapeSequence<'a>>,
+    pending_position: usize,
+    prev: Option<char>,
+}
+
+impl Iterator for EscapeChars<'_> {
+    type Item = char;
+
+    fn next(&mut self) -> Option<char> {
+        if let Some(sequence) = &self.pending {
+            let mut rest = sequence[self.pending_position..].chars();
+            if let Some(c) = rest.next() {
+                self.pending_position += c.len_utf8();
+                if rest.as_str().is_empty() {
+                    self.pending = None;
+                }
+                return Some(c);
+            }
+            self.pending = None;
+        }
+
+        let c = self.chars.next()?;
+        let prev = self.prev.replace(c);
+        match self.escaper.escape_after(prev, c) {
+            Some(sequence) => {
+                self.pending = Some(sequence);
+                self.pending_position = 0;
+                Some(self.escaper.escape_char)
+            }
+            None => Some(c),
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let pending = self.pending.as_ref().map_or(0, |sequence| {
+            sequence[self.pending_position..].chars().count()
+        });
+        let max_sequence_len = self
+            .escaper
+            .sequence_rules
+            .iter()
+            .map(|rule| rule.escaped.chars().count())
+            .chain(
+                (!self.escaper.class_rules.is_empty()
+                    || self.escaper.fallback.notation().is_some())
+                .then_some(INLINE_SEQUENCE_LEN),
+            )
+            .fold(1, usize::max);
+        let (lower, upper) = self.chars.size_hint();
+
+        (
+            lower.saturating_add(pending),
+            upper
+                .and_then(|upper| upper.checked_mul(1 + max_sequence_len))
+                .and_then(|upper| upper.checked_add(pending)),
+        )
+    }
+}
+
+impl FusedIterator for EscapeChars<'_> {}
+
+/// An iterator over the [`char`]s of an unescaped string.
+///
+/// Created by [`Escaper::unescape_chars()`].
+#[derive(Clone, Debug)]
+pub struct UnescapeChars<'a> {
+    escaper: &'a Escaper<'a>,
+    s: &'a str,
+    position: usize,
+    failed: bool,
+}
+
+impl Iterator for UnescapeChars<'_> {
+    type Item = Result<char, UnescapeError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+
+        let start = self.position;
+        let c = self.s[start..].chars().next()?;
+        self.position += c.len_utf8();
+
+        let result = if c == self.escaper.escape_char {
+            match self
+                .escaper
+                .unescape_sequence(&self.s[self.position..], start, true)
+            {
+                Ok((unescaped, len)) => {
+                    self.position += len;
+                    Ok(unescaped)
+                }
+                Err(error @ UnescapeError::Incomplete { .. }) => match self.escaper.trailing_escape
+                {
+                    TrailingEscape::Error => Err(error),
+                    TrailingEscape::Drop => {
+                        self.position = self.s.len();
+                        return None;
+                    }
+                    // the rest of the incomplete escape sequence is returned as is, too
+                    TrailingEscape::Keep => Ok(c),
+                },
+                Err(error) => Err(error),
+            }
+        } else {
+            Ok(c)
+        };
+
+        self.failed = result.is_err();
+        Some(result)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        if self.failed {
+            (0, Some(0))
+        } else {
+            let rest = self.s.len() - self.position;
+            (usize::from(rest != 0), Some(rest))
+        }
+    }
+}
+
+impl FusedIterator for UnescapeChars<'_> {}
+
+/// A segment of an escaped string.
+///
+/// Returned by [`Tokens`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Token<'a> {
+    /// A run of [`char`]s that are not part of an escape sequence.
+    Literal {
+        /// The [`char`]s of the run.
+        text: &'a str,
+        /// The byte range of the run.
+        span: Range<usize>,
+    },
+    /// An escape sequence.
+    Escaped {
+        /// The escape sequence, including the escape character.
+        ///
+        /// If the string ends in the middle of an escape sequence, this is the incomplete escape
+        /// sequence.
+        sequence: &'a str,
+        /// The [`char`] the escape sequence stands for, [`None`] if the escape sequence is
+        /// invalid.
+        unescaped: Option<char>,
+        /// The byte range of the escape sequence.
+        span: Range<usize>,
+    },
+}
+
+impl<'a> Token<'a> {
+    /// Returns the part of the string this token consists of.
+    pub const fn as_str(&self) -> &'a str {
+        match self {
+            Self::Literal { text, .. } => text,
+            Self::Escaped { sequence, .. } => sequence,
+        }
+    }
+
+    /// Returns the byte range this token spans.
+    pub fn span(&self) -> Range<usize> {
+        match self {
+            Self::Literal { span, .. } | Self::Escaped { span, .. } => span.clone(),
+        }
+    }
+}
+
+/// An iterator over the [`Token`]s of an escaped string.
+///
+/// Created by [`Escaper::tokens()`].
+#[derive(Clone, Debug)]
+pub struct Tokens<'a> {
+    escaper: &'a Escaper<'a>,
+    s: &'a str,
+    position: usize,
+}
+
+impl<'a> Iterator for Tokens<'a> {
+    type Item = Token<'a>;
+
+    fn next(&mut self) -> Option<Token<'a>> {
+        let start = self.position;
+        let rest = &self.s[start..];
+        let mut chars = rest.chars();
+        let first = chars.next()?;
+
+        if first == self.escaper.escape_char {
+            self.position += first.len_utf8();
+            let unescaped = match self.escaper.decode(chars.as_str(), true) {
+                Decoded::Valid { unescaped, len } => {
+                    self.position += len;
+                    Some(unescaped)
+                }
+                Decoded::Invalid { len } => {
+                    self.position += len;
+                    None
+                }
+                Decoded::Incomplete => {
+                    self.position = self.s.len();
+                    None
+                }
+            };
+
+            Some(Token::Escaped {
+                sequence: &self.s[start..self.position],
+                unescaped,
+                span: start..self.position,
+            })
+        } else {
+            self.position += rest.find(self.escaper.escape_char).unwrap_or(rest.len());
+
+            Some(Token::Literal {
+                text: &self.s[start..self.position],
+                span: start..self.position,
+            })
+        }
+    }
+}
+
+impl FusedIterator for Tokens<'_> {}
+
+/// The marker used in place of invalid escape sequences if nothing else is specified.
+const DEFAULT_REPLACEMENT: &str = "\u{fffd}";
+
+/// The size of the buffer used when reading from an [`io::Read`].
+const READ_BUF_LEN: usize = 8 * 1024;
+
+/// The approximate number of bytes [`Escaper::escape_parallel()`] escapes per task.
+#[cfg(feature = "rayon")]
+const PARALLEL_CHUNK_LEN: usize = 1024 * 1024;
+
+/// The maximum number of bytes needed to encode a [`char`] as UTF-8.
+const MAX_UTF8_LEN: usize = 4;
+
+/// The number of bytes an [`EscapeSequence`] can store without borrowing.
+///
+/// Large enough for the longest [`Notation`], [`Notation::Percent`] with four bytes and three
+/// escape characters in between.
+const INLINE_SEQUENCE_LEN: usize = 5 * MAX_UTF8_LEN;
+
+/// The result of decoding the escape sequence following an escape character.
+enum Decoded {
+    /// A valid escape sequence of `len` bytes (without the escape character).
+    Valid { unescaped: char, len: usize },
+    /// An invalid escape sequence of `len` bytes (without the escape character).
+    Invalid { len: usize },
+    /// The string ends before the escape sequence is complete.
+    Incomplete,
+}
+
+/// How the start of a string matches a pattern.
+enum PrefixMatch {
+    /// The string starts with the pattern, which matched the given number of bytes.
+    Full(usize),
+    /// The string is the start of the pattern.
+    Partial,
+    /// The string doesn't match the pattern.
+    None,
+}
+
+/// Checks how the start of `s` matches `pattern`.
+fn match_prefix(s: &str, pattern: &str, ignore_case: bool) -> PrefixMatch {
+    let mut chars = s.chars();
+    for expected in pattern.chars() {
+        match chars.next() {
+            Some(c) if c == expected || (ignore_case && eq_ignore_case(c, expected)) => {}
+            Some(_) => return PrefixMatch::None,
+            None => return PrefixMatch::Partial,
+        }
+    }
+
+    PrefixMatch::Full(s.len() - chars.as_str().len())
+}
+
+/// Returns `true` if `a` and `b` are the same [`char`] when ignoring case.
+fn eq_ignore_case(a: char, b: char) -> bool {
+    a == b || a.to_lowercase().eq(b.to_lowercase())
+}
+
+/// Interprets the bytes stored inline in an [`EscapeSequence`].
+fn inline_str(bytes: &[u8]) -> &str {
+    core::str::from_utf8(bytes).expect("inline escape sequences are valid UTF-8")
+}
+
+/// Decodes the last [`char`] of `bytes`.
+///
+/// `bytes` must be non-empty and end with a complete UTF-8 sequence.
+fn last_char(bytes: &[u8]) -> char {
+    let start = bytes
+        .iter()
+        .rposition(|&byte| !is_utf8_continuation_byte(byte))
+        .expect("bytes ends with a complete UTF-8 sequence");
+
+    core::str::from_utf8(&bytes[start..])
+        .ok()
+        .and_then(|s| s.chars().next())
+        .expect("bytes ends with a complete UTF-8 sequence")
+}
+
+const fn is_utf8_continuation_byte(byte: u8) -> bool {
+    byte & 0b1100_0000 == 0b1000_0000
+}
+
+/// Returns the longest prefix of `bytes` that is valid UTF-8.
+///
+/// Fails if `bytes` contains invalid UTF-8 that isn't just an incomplete sequence at its end.
+fn complete_utf8_prefix(bytes: &[u8]) -> Result<&str, core::str::Utf8Error> {
+    match core::str::from_utf8(bytes) {
+        Ok(s) => Ok(s),
+        Err(error) if error.error_len().is_none() => {
+            Ok(core::str::from_utf8(&bytes[..error.valid_up_to()])
+                .expect("is valid UTF-8 up to this point"))
+        }
+        Err(error) => Err(error),
+    }
+}
+
+/// Checks that `rules` are a valid set of rules for the escape character `escape_char`.
+///
+/// Uses `while` loops, since iterators can't be used in `const` contexts.
+const fn validate_rules(escape_char: char, rules: &[Rule]) -> Result<(), RuleSetError> {
+    if !contains_escape_char_rule(escape_char, rules) {
+        return Err(RuleSetError::MissingEscapeCharRule);
+    }
+
+    let mut i = 0;
+    while i < rules.len() {
+        let first = rules[i];
+        let mut j = i + 1;
+        while j < rules.len() {
+            let second = rules[j];
+            let conflict = ConflictingRules { first, second };
+            let same_unescaped = first.unescaped == second.unescaped;
+            let same_escaped = first.escaped == second.escaped;
+            if same_unescaped && same_escaped {
+                return Err(RuleSetError::DuplicateRule(first));
+            } else if same_unescaped {
+                return Err(RuleSetError::DuplicateUnescaped(conflict));
+            } else if same_escaped {
+                return Err(RuleSetError::DuplicateEscaped(conflict));
+            }
+            j += 1;
+        }
+        i += 1;
     }
+
+    Ok(())
 }
 
-fn contains_escape_char_rule(escape_char: char, rules: &[Rule]) -> bool {
-    rules.iter().any(|rule| rule.unescaped == escape_char)
+const fn contains_escape_char_rule(escape_char: char, rules: &[Rule]) -> bool {
+    let mut i = 0;
+    while i < rules.len() {
+        if rules[i].unescaped == escape_char {
+            return true;
+        }
+        i += 1;
+    }
+
+    false
 }
 
 /// The error that occurs if unescaping a string fails.
@@ -520,18 +3737,65 @@ fn contains_escape_char_rule(escape_char: char, rules: &[Rule]) -> bool {
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum UnescapeError {
     /// Indicates that an invalid escape sequence was encountered.
+    Invalid {
+        /// The invalid escape sequence.
+        sequence: String,
+        /// The byte offset of the invalid escape sequence.
+        position: usize,
+    },
+    /// Indicates that the string that was to be escaped ended in the middle of an escape sequence.
+    Incomplete {
+        /// The byte offset of the escape character of the incomplete escape sequence.
+        position: usize,
+    },
+}
+
+impl UnescapeError {
+    /// Returns the byte offset at which the error occurred.
+    pub const fn position(&self) -> usize {
+        match self {
+            Self::Invalid { position, .. } | Self::Incomplete { position } => *position,
+        }
+    }
+
+    /// Computes the line and column at which the error occurred in `input`.
     ///
-    /// The associated [`String`] value is the invalid escape sequence.
-    Invalid(String),
-    /// Indicates that the string that was to be escaped ended with the escape character.
-    Incomplete,
+    /// `input` must be the string that failed to be unescaped. Both line and column start at 1,
+    /// the column is counted in [`char`]s.
+    ///
+    /// ```
+    /// # use char_escape::escaper;
+    /// #
+    /// let escaper = escaper! {
+    ///     '\n' => 'n',
+    /// };
+    ///
+    /// let input = "first line\nsecond \\line";
+    /// let error = escaper.unescape(input).unwrap_err();
+    ///
+    /// assert_eq!(error.position(), 18);
+    /// assert_eq!(error.line_column(input), (2, 8));
+    /// ```
+    pub fn line_column(&self, input: &str) -> (usize, usize) {
+        let before = &input[..self.position().min(input.len())];
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+
+        (
+            before.matches('\n').count() + 1,
+            before[line_start..].chars().count() + 1,
+        )
+    }
 }
 
 impl Display for UnescapeError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
-            Self::Invalid(sequence) => write!(f, "invalid escape sequence: {sequence}"),
-            Self::Incomplete => write!(f, "incomplete escape sequence"),
+            Self::Invalid { sequence, position } => {
+                write!(f, "invalid escape sequence {sequence} at byte {position}")
+            }
+            Self::Incomplete { position } => {
+                write!(f, "incomplete escape sequence at byte {position}")
+            }
         }
     }
 }
@@ -546,6 +3810,7 @@ pub struct MissingEscapeCharRule {}
 
 impl MissingEscapeCharRule {
     /// Create a new [`MissingEscapeCharRule`].
+    #[allow(clippy::new_without_default)]
     pub const fn new() -> Self {
         Self {}
     }
@@ -559,6 +3824,72 @@ impl Display for MissingEscapeCharRule {
 
 impl Error for MissingEscapeCharRule {}
 
+/// Results from attempting to create an [`Escaper`] from an invalid set of rules.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum RuleSetError {
+    /// No rule escapes the escape character.
+    MissingEscapeCharRule,
+    /// The same rule appears more than once.
+    DuplicateRule(Rule),
+    /// Two rules escape the same [`char`] differently.
+    DuplicateUnescaped(ConflictingRules),
+    /// Two rules escape different [`char`]s the same way, so unescaping is ambiguous.
+    DuplicateEscaped(ConflictingRules),
+}
+
+impl Display for RuleSetError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::MissingEscapeCharRule => MissingEscapeCharRule::new().fmt(f),
+            Self::DuplicateRule(rule) => {
+                write!(
+                    f,
+                    "rule {:?} => {:?} appears more than once",
+                    rule.unescaped, rule.escaped
+                )
+            }
+            Self::DuplicateUnescaped(conflict) | Self::DuplicateEscaped(conflict) => {
+                conflict.fmt(f)
+            }
+        }
+    }
+}
+
+impl Error for RuleSetError {}
+
+impl From<MissingEscapeCharRule> for RuleSetError {
+    fn from(_: MissingEscapeCharRule) -> Self {
+        Self::MissingEscapeCharRule
+    }
+}
+
+/// Results from attempting to [invert](Escaper::inverse) an [`Escaper`] whose rules are not
+/// invertible.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum InverseError {
+    /// No rule has the escape character as its escaped [`char`], so the inverse can't escape the
+    /// escape character.
+    MissingEscapeCharRule,
+    /// Two rules have the same escaped [`char`].
+    ConflictingRules(ConflictingRules),
+}
+
+impl Display for InverseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::MissingEscapeCharRule => {
+                write!(
+                    f,
+                    "no rule has the escape character as its escaped character"
+                )
+            }
+            Self::ConflictingRules(conflict) => conflict.fmt(f),
+        }
+    }
+}
+
+impl Error for InverseError {}
+
 // TODO test how it handles non-ascii chars
 
 #[cfg(doctest)]
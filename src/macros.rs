@@ -0,0 +1,534 @@
+//! Support code for the [`escaper!`](crate::escaper) macro.
+//!
+//! Everything in here is an implementation detail and not part of the public API.
+
+use {
+    crate::{ClassRule, Escaper, Fallback, Notation, Rule, SequenceRule, TrailingEscape},
+    core::ops::RangeInclusive,
+    std::borrow::Cow,
+};
+
+/// Shorthands for the [`Notation`]s that can be used in the [`escaper!`](crate::escaper) macro.
+#[allow(non_upper_case_globals)]
+pub mod notations {
+    use crate::Notation;
+
+    /// See [`Notation::Hex`].
+    pub const hex: Notation = Notation::Hex;
+    /// See [`Notation::AsciiHex`].
+    pub const ascii_hex: Notation = Notation::AsciiHex;
+    /// See [`Notation::Unicode`].
+    pub const unicode: Notation = Notation::Unicode;
+    /// See [`Notation::Utf16`].
+    pub const utf16: Notation = Notation::Utf16;
+    /// See [`Notation::Percent`].
+    pub const percent: Notation = Notation::Percent;
+}
+
+/// Shorthands for the values of the `trailing_escape` option of the
+/// [`escaper!`](crate::escaper) macro.
+#[allow(non_upper_case_globals)]
+pub mod trailing_escape {
+    use crate::TrailingEscape;
+
+    /// See [`TrailingEscape::Error`].
+    pub const error: TrailingEscape = TrailingEscape::Error;
+    /// See [`TrailingEscape::Drop`].
+    pub const drop: TrailingEscape = TrailingEscape::Drop;
+    /// See [`TrailingEscape::Keep`].
+    pub const keep: TrailingEscape = TrailingEscape::Keep;
+}
+
+/// Shorthands for the values of the `fallback` option of the [`escaper!`](crate::escaper) macro.
+#[allow(non_upper_case_globals)]
+pub mod fallback {
+    use crate::Fallback;
+
+    /// See [`Fallback::Keep`].
+    pub const keep: Fallback = Fallback::Keep;
+    /// See [`Fallback::Reject`].
+    pub const reject: Fallback = Fallback::Reject;
+    /// See [`Fallback::Hex`].
+    pub const hex: Fallback = Fallback::Hex;
+    /// See [`Fallback::Unicode`].
+    pub const unicode: Fallback = Fallback::Unicode;
+}
+
+/// One `unescaped => escaped` entry of an [`escaper!`](crate::escaper) invocation.
+#[derive(Clone, Copy)]
+pub enum Entry {
+    /// A single [`char`] escaped as a single [`char`].
+    Rule(Rule),
+    /// A single [`char`] escaped as a string.
+    Sequence {
+        unescaped: char,
+        escaped: &'static str,
+    },
+    /// A range of [`char`]s escaped using a [`Notation`].
+    Class(ClassRule),
+}
+
+/// The two sides of an entry, before its kind is known.
+///
+/// Each supported combination of types has its own `entry()` method, which lets the macro accept
+/// arbitrary `const` expressions on both sides.
+pub struct Pair<U, E>(pub U, pub E);
+
+impl Pair<char, char> {
+    pub const fn entry(self) -> Entry {
+        Entry::Rule(Rule {
+            unescaped: self.0,
+            escaped: self.1,
+        })
+    }
+}
+
+impl Pair<char, &'static str> {
+    pub const fn entry(self) -> Entry {
+        Entry::Sequence {
+            unescaped: self.0,
+            escaped: self.1,
+        }
+    }
+}
+
+impl Pair<char, Notation> {
+    pub const fn entry(self) -> Entry {
+        Entry::Class(ClassRule::range(self.0..=self.0, self.1))
+    }
+}
+
+impl Pair<RangeInclusive<char>, Notation> {
+    pub const fn entry(self) -> Entry {
+        Entry::Class(ClassRule::range(self.0, self.1))
+    }
+}
+
+/// Returns the [`Escaper`] that the macro starts from.
+///
+/// That is the extended [`Escaper`], if any, with the escape character replaced if one is
+/// specified.
+pub const fn base(escape_char: Option<char>, extend: Option<Escaper<'static>>) -> Escaper<'static> {
+    match (extend, escape_char) {
+        (Some(base), Some(escape_char)) => Escaper {
+            escape_char,
+            ..base
+        },
+        (Some(base), None) => base,
+        (None, Some(escape_char)) => Escaper::new_unchecked(escape_char, &[]),
+        (None, None) => Escaper::new_unchecked('\\', &[]),
+    }
+}
+
+/// Returns `escaper` with the options that are specified applied to it.
+pub const fn configure(
+    escaper: Escaper<'static>,
+    trailing_escape: Option<TrailingEscape>,
+    fallback: Option<Fallback>,
+    case_insensitive: Option<bool>,
+) -> Escaper<'static> {
+    let escaper = match trailing_escape {
+        Some(trailing_escape) => escaper.with_trailing_escape(trailing_escape),
+        None => escaper,
+    };
+    let escaper = match fallback {
+        Some(fallback) => escaper.with_fallback(fallback),
+        None => escaper,
+    };
+    match case_insensitive {
+        Some(case_insensitive) => escaper.with_case_insensitive(case_insensitive),
+        None => escaper,
+    }
+}
+
+/// Returns `base` with its rules replaced.
+pub const fn extend<'a>(
+    base: Escaper<'a>,
+    rules: &'a [Rule],
+    sequence_rules: &'a [SequenceRule<'a>],
+    class_rules: &'a [ClassRule],
+) -> Escaper<'a> {
+    Escaper {
+        rules,
+        sequence_rules,
+        class_rules,
+        ..base
+    }
+}
+
+/// Returns the number of [`Rule`]s among the `entries`.
+pub const fn count_rules(entries: &[Entry]) -> usize {
+    let mut count = 0;
+    let mut i = 0;
+    while i < entries.len() {
+        if let Entry::Rule(_) = entries[i] {
+            count += 1;
+        }
+        i += 1;
+    }
+
+    count
+}
+
+/// Returns the number of [`SequenceRule`]s among the `entries`.
+pub const fn count_sequence_rules(entries: &[Entry]) -> usize {
+    let mut count = 0;
+    let mut i = 0;
+    while i < entries.len() {
+        if let Entry::Sequence { .. } = entries[i] {
+            count += 1;
+        }
+        i += 1;
+    }
+
+    count
+}
+
+/// Returns the number of [`ClassRule`]s among the `entries`.
+pub const fn count_class_rules(entries: &[Entry]) -> usize {
+    entries.len() - count_rules(entries) - count_sequence_rules(entries)
+}
+
+/// Returns a [`Rule`] escaping each of the `same` [`char`]s to itself, followed by the `entries`.
+pub const fn with_same<const N: usize>(same: &[char], entries: &[Entry]) -> [Entry; N] {
+    let mut all = [Entry::Rule(Rule::same('\0')); N];
+    let mut len = 0;
+    while len < same.len() {
+        all[len] = Entry::Rule(Rule::same(same[len]));
+        len += 1;
+    }
+
+    let mut i = 0;
+    while i < entries.len() {
+        all[len] = entries[i];
+        len += 1;
+        i += 1;
+    }
+
+    all
+}
+
+/// Returns the number of rules that the macro starts from, i.e. the rules of `base` and the
+/// implicit rule for the escape character if it is `implicit` and `base` doesn't have a rule for
+/// it.
+pub const fn count_base_rules(base: &Escaper, implicit: bool) -> usize {
+    if !implicit || crate::contains_escape_char_rule(base.escape_char, base.rules) {
+        base.rules.len()
+    } else {
+        base.rules.len() + 1
+    }
+}
+
+/// Returns the rules that the macro starts from, followed by the [`Rule`]s among the `entries`.
+///
+/// The implicit rule for the escape character comes first, if it is `implicit`. Since later rules
+/// take precedence, the `entries` can override both it and the rules of `base`.
+pub const fn rules<const N: usize>(base: &Escaper, implicit: bool, entries: &[Entry]) -> [Rule; N] {
+    let mut rules = [Rule::same(base.escape_char); N];
+    let mut len = count_base_rules(base, implicit) - base.rules.len();
+    let base = base.rules;
+    let mut i = 0;
+    while i < base.len() {
+        rules[len] = base[i];
+        len += 1;
+        i += 1;
+    }
+
+    let mut i = 0;
+    while i < entries.len() {
+        if let Entry::Rule(rule) = entries[i] {
+            rules[len] = rule;
+            len += 1;
+        }
+        i += 1;
+    }
+
+    rules
+}
+
+/// Returns the sequence rules of the base followed by the [`SequenceRule`]s among the `entries`.
+///
+/// Since [`SequenceRule`] has a destructor, the array is filled using [`core::mem::replace()`]
+/// and [`core::mem::forget()`], which, unlike assignments, can be evaluated at compile time.
+pub const fn sequence_rules<const N: usize>(
+    base: &[SequenceRule<'static>],
+    entries: &[Entry],
+) -> [SequenceRule<'static>; N] {
+    let mut sequence_rules = [const { SequenceRule::new('\0', "") }; N];
+    let mut len = 0;
+    while len < base.len() {
+        let escaped = match &base[len].escaped {
+            Cow::Borrowed(escaped) => *escaped,
+            Cow::Owned(_) => panic!("escaper!: can't extend an escaper with owned sequence rules"),
+        };
+        let rule = SequenceRule::new(base[len].unescaped, escaped);
+        core::mem::forget(core::mem::replace(&mut sequence_rules[len], rule));
+        len += 1;
+    }
+
+    let mut i = 0;
+    while i < entries.len() {
+        if let Entry::Sequence { unescaped, escaped } = entries[i] {
+            let rule = SequenceRule::new(unescaped, escaped);
+            core::mem::forget(core::mem::replace(&mut sequence_rules[len], rule));
+            len += 1;
+        }
+        i += 1;
+    }
+
+    sequence_rules
+}
+
+/// Returns the class rules of the base followed by the [`ClassRule`]s among the `entries`.
+pub const fn class_rules<const N: usize>(base: &[ClassRule], entries: &[Entry]) -> [ClassRule; N] {
+    let mut class_rules = [ClassRule::range('\0'..='\0', Notation::Hex); N];
+    let mut len = 0;
+    while len < base.len() {
+        class_rules[len] = base[len];
+        len += 1;
+    }
+
+    let mut i = 0;
+    while i < entries.len() {
+        if let Entry::Class(class_rule) = entries[i] {
+            class_rules[len] = class_rule;
+            len += 1;
+        }
+        i += 1;
+    }
+
+    class_rules
+}
+
+/// Panics if the rules of an [`escaper!`](crate::escaper) invocation are ambiguous.
+///
+/// The rules from `explicit_start` on are the ones listed in the invocation, they must escape
+/// distinct [`char`]s. The rules before may be overridden. The rules that are in effect must all
+/// have distinct escaped [`char`]s.
+pub const fn check_rules(rules: &[Rule], explicit_start: usize) {
+    let mut i = explicit_start;
+    while i < rules.len() {
+        let mut j = i + 1;
+        while j < rules.len() {
+            if rules[i].unescaped == rules[j].unescaped {
+                fail("escaper!: more than one rule escapes ", rules[i].unescaped);
+            }
+            j += 1;
+        }
+        i += 1;
+    }
+
+    let mut i = 0;
+    while i < rules.len() {
+        let mut j = i + 1;
+        while j < rules.len() {
+            if rules[i].escaped == rules[j].escaped
+                && is_effective(rules, i)
+                && is_effective(rules, j)
+            {
+                fail(
+                    "escaper!: more than one rule is escaped as ",
+                    rules[i].escaped,
+                );
+            }
+            j += 1;
+        }
+        i += 1;
+    }
+}
+
+/// Panics if neither the `rules` nor the sequence rules contain a rule for the `escape_char`.
+///
+/// Only needed with `no_implicit_escape`, since the implicit rule is added otherwise.
+pub const fn check_escape_char_rule(
+    escape_char: char,
+    rules: &[Rule],
+    base_sequences: &[SequenceRule<'static>],
+    entries: &[Entry],
+) {
+    let mut found = crate::contains_escape_char_rule(escape_char, rules);
+    let mut i = 0;
+    while i < base_sequences.len() {
+        found |= base_sequences[i].unescaped == escape_char;
+        i += 1;
+    }
+    let mut i = 0;
+    while i < entries.len() {
+        if let Entry::Sequence { unescaped, .. } = entries[i] {
+            found |= unescaped == escape_char;
+        }
+        i += 1;
+    }
+
+    if !found {
+        fail(
+            "escaper!: no_implicit_escape requires a rule for the escape character ",
+            escape_char,
+        );
+    }
+}
+
+/// Panics if two rules of a [`decode_only!`](crate::decode_only) invocation use the same escaped
+/// [`char`].
+pub const fn check_decode_rules(rules: &[Rule]) {
+    let mut i = 0;
+    while i < rules.len() {
+        let mut j = i + 1;
+        while j < rules.len() {
+            if rules[i].escaped == rules[j].escaped {
+                fail(
+                    "decode_only!: more than one rule is escaped as ",
+                    rules[i].escaped,
+                );
+            }
+            j += 1;
+        }
+        i += 1;
+    }
+}
+
+/// Panics if the string escape sequences of an [`escaper!`](crate::escaper) invocation are
+/// ambiguous.
+///
+/// No [`char`] may have both a [`Rule`] and a [`SequenceRule`] among the `entries`, and no two
+/// [`SequenceRule`]s may have the same escaped string. Escaped strings must not be empty and
+/// must be prefix-free: neither the escaped [`char`] of one of the effective `rules` nor the
+/// escaped string of another [`SequenceRule`] may be their start.
+pub const fn check_sequences(rules: &[Rule], entries: &[Entry]) {
+    let mut i = 0;
+    while i < entries.len() {
+        if let Entry::Sequence { unescaped, escaped } = entries[i] {
+            if escaped.is_empty() {
+                fail("escaper!: empty escape sequence for ", unescaped);
+            }
+
+            let mut j = 0;
+            while j < rules.len() {
+                if is_effective(rules, j) && starts_with_char(escaped, rules[j].escaped) {
+                    fail(
+                        "escaper!: the escape sequence of a rule starts the one of ",
+                        unescaped,
+                    );
+                }
+                j += 1;
+            }
+
+            let mut j = 0;
+            while j < entries.len() {
+                match entries[j] {
+                    Entry::Rule(rule) if rule.unescaped == unescaped => {
+                        fail("escaper!: more than one rule escapes ", unescaped);
+                    }
+                    Entry::Sequence {
+                        unescaped: other,
+                        escaped: other_escaped,
+                    } if j > i => {
+                        if other == unescaped {
+                            fail("escaper!: more than one rule escapes ", unescaped);
+                        } else if str_eq(escaped, other_escaped) {
+                            fail("escaper!: more than one rule is escaped like ", unescaped);
+                        } else if starts_with(escaped, other_escaped) {
+                            fail(
+                                "escaper!: the escape sequence of a rule starts the one of ",
+                                unescaped,
+                            );
+                        } else if starts_with(other_escaped, escaped) {
+                            fail(
+                                "escaper!: the escape sequence of a rule starts the one of ",
+                                other,
+                            );
+                        }
+                    }
+                    _ => {}
+                }
+                j += 1;
+            }
+        }
+        i += 1;
+    }
+}
+
+/// `const` version of `a == b`.
+const fn str_eq(a: &str, b: &str) -> bool {
+    a.len() == b.len() && starts_with(a, b)
+}
+
+/// `const` version of `s.starts_with(prefix)`.
+const fn starts_with(s: &str, prefix: &str) -> bool {
+    let (s, prefix) = (s.as_bytes(), prefix.as_bytes());
+    if s.len() < prefix.len() {
+        return false;
+    }
+
+    let mut i = 0;
+    while i < prefix.len() {
+        if s[i] != prefix[i] {
+            return false;
+        }
+        i += 1;
+    }
+
+    true
+}
+
+/// `const` version of `s.starts_with(c)`.
+const fn starts_with_char(s: &str, c: char) -> bool {
+    let mut buf = [0; 4];
+    starts_with(s, c.encode_utf8(&mut buf))
+}
+
+/// Returns `true` if `rules[index]` is not overridden by a later rule.
+const fn is_effective(rules: &[Rule], index: usize) -> bool {
+    let mut i = index + 1;
+    while i < rules.len() {
+        if rules[i].unescaped == rules[index].unescaped {
+            return false;
+        }
+        i += 1;
+    }
+
+    true
+}
+
+/// Panics with `message` followed by `c`.
+///
+/// `panic!()` can't format arguments in `const` contexts, so the message is assembled by hand.
+const fn fail(message: &str, c: char) -> ! {
+    const LEN: usize = 128;
+
+    let mut buf = [0; LEN];
+    let mut len = 0;
+    let message = message.as_bytes();
+    while len < message.len() && len < LEN - 8 {
+        buf[len] = message[len];
+        len += 1;
+    }
+
+    if c.is_ascii_graphic() || c == ' ' {
+        buf[len] = b'\'';
+        buf[len + 1] = c as u8;
+        buf[len + 2] = b'\'';
+        len += 3;
+    } else {
+        // e.g. U+000A
+        const DIGITS: &[u8; 16] = b"0123456789ABCDEF";
+        buf[len] = b'U';
+        buf[len + 1] = b'+';
+        len += 2;
+        let code_point = c as u32;
+        let mut shift = if code_point > 0xffff { 20 } else { 12 };
+        loop {
+            buf[len] = DIGITS[((code_point >> shift) & 0xf) as usize];
+            len += 1;
+            if shift == 0 {
+                break;
+            }
+            shift -= 4;
+        }
+    }
+
+    match core::str::from_utf8(buf.split_at(len).0) {
+        Ok(message) => panic!("{}", message),
+        Err(_) => panic!("escaper!: invalid rules"),
+    }
+}
@@ -0,0 +1,78 @@
+use {
+    crate::{ConflictingRules, Rule},
+    core::fmt::{self, Display},
+    std::error::Error,
+};
+
+/// Results from attempting to [merge](crate::Escaper::merged_with) two [`Escaper`](crate::Escaper)s
+/// whose rules contradict each other.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum MergeError {
+    /// The [`Escaper`](crate::Escaper)s use different escape characters.
+    DifferentEscapeChars {
+        /// The escape character of the first [`Escaper`](crate::Escaper).
+        first: char,
+        /// The escape character of the second [`Escaper`](crate::Escaper).
+        second: char,
+    },
+    /// A rule of the first [`Escaper`](crate::Escaper) and a rule of the second one have the same
+    /// unescaped or escaped [`char`], but are not the same.
+    ConflictingRules(ConflictingRules),
+}
+
+impl Display for MergeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::DifferentEscapeChars { first, second } => {
+                write!(f, "escape characters {first:?} and {second:?} differ")
+            }
+            Self::ConflictingRules(conflict) => conflict.fmt(f),
+        }
+    }
+}
+
+impl Error for MergeError {}
+
+/// The differences between the rules of two [`Escaper`](crate::Escaper)s.
+///
+/// Created by [`Escaper::diff()`](crate::Escaper::diff).
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct RuleDiff {
+    /// The rules that only the second [`Escaper`](crate::Escaper) has.
+    pub added: Vec<Rule>,
+    /// The rules that only the first [`Escaper`](crate::Escaper) has.
+    pub removed: Vec<Rule>,
+}
+
+impl RuleDiff {
+    /// Returns `true` if both [`Escaper`](crate::Escaper)s have the same rules.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty()
+    }
+}
+
+/// Returns the rules of `rules` that are not part of `other`, without duplicates.
+pub(crate) fn missing_from(rules: &[Rule], other: &[Rule]) -> Vec<Rule> {
+    let mut missing: Vec<Rule> = Vec::new();
+    for rule in rules {
+        if !other.contains(rule) && !missing.contains(rule) {
+            missing.push(*rule);
+        }
+    }
+
+    missing
+}
+
+/// Returns the first rule of `rules` that contradicts `rule`.
+pub(crate) fn find_contradiction(rules: &[Rule], rule: Rule) -> Option<ConflictingRules> {
+    rules
+        .iter()
+        .find(|existing| {
+            **existing != rule
+                && (existing.unescaped == rule.unescaped || existing.escaped == rule.escaped)
+        })
+        .map(|existing| ConflictingRules {
+            first: *existing,
+            second: rule,
+        })
+}
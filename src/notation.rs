@@ -0,0 +1,535 @@
+use {
+    crate::{Decoded, EscapeSequence, INLINE_SEQUENCE_LEN},
+    std::io::Write as _,
+};
+
+/// A generic form of escape sequences that represents [`char`]s by their code points.
+///
+/// Used by [class rules](crate::ClassRule) and the [fallback](crate::Fallback).
+///
+/// Unescaping accepts the notations in use for every [`char`], not only for those that would be
+/// escaped that way. No rule should produce an escape sequence that starts like a notation in use.
+///
+/// ```
+/// # use char_escape::{escaper, CharClass, ClassRule, Escaper, Notation};
+/// #
+/// const ESCAPER: Escaper<'static> = escaper! {
+///     '\n' => 'n',
+/// }
+/// .with_class_rules(&[ClassRule {
+///     class: CharClass::Control,
+///     notation: Notation::AsciiHex,
+/// }]);
+///
+/// assert_eq!(ESCAPER.escape("\0\x7f\n"), r"\x00\x7f\n");
+/// assert_eq!(ESCAPER.unescape(r"\x41\x1b").expect("is properly escaped"), "A\x1b");
+/// assert!(ESCAPER.unescape(r"\xff").is_err());
+///
+/// // '\u{85}' is a control character, but not ASCII
+/// assert_eq!(ESCAPER.escape("\u{85}"), "\u{85}");
+/// ```
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Notation {
+    /// The hexadecimal code point in braces, prefixed by `'x'`.
+    ///
+    /// E.g. `'\0'` becomes `\x{00}` and `'ß'` becomes `\x{df}` if the escape character is
+    /// `'\\'`.
+    Hex,
+    /// The hexadecimal code point using exactly two digits, prefixed by `'x'`.
+    ///
+    /// E.g. `'\0'` becomes `\x00` if the escape character is `'\\'`. Only ASCII [`char`]s can be
+    /// represented this way, other [`char`]s are left to the next rule that applies to them.
+    /// Unescaping rejects escape sequences greater than `\x7f`, since they don't stand for a
+    /// [`char`] on their own.
+    AsciiHex,
+    /// The hexadecimal code point in braces, prefixed by `'u'`, like in Rust.
+    ///
+    /// E.g. `'\0'` becomes `\u{0}` and `'😀'` becomes `\u{1f600}` if the escape character is
+    /// `'\\'`. Unescaping accepts up to 6 digits and rejects code points that are not a valid
+    /// [`char`], e.g. surrogates.
+    ///
+    /// ```
+    /// # use char_escape::{escaper, Escaper, Fallback};
+    /// #
+    /// const ESCAPER: Escaper<'static> = escaper! {
+    ///     '\n' => 'n',
+    /// }
+    /// .with_fallback(Fallback::Unicode);
+    ///
+    /// assert_eq!(ESCAPER.escape("😀\n\0"), r"\u{1f600}\n\u{0}");
+    /// assert_eq!(ESCAPER.unescape(r"\u{00e4}").expect("is properly escaped"), "ä");
+    /// assert!(ESCAPER.unescape(r"\u{d800}").is_err());
+    /// assert!(ESCAPER.unescape(r"\u{110000}").is_err());
+    /// ```
+    Unicode,
+    /// The octal code point, like in C.
+    ///
+    /// Escaping pads the code point with zeros to `max_digits` digits, e.g. `'A'` becomes `\101`
+    /// if `max_digits` is 3 and the escape character is `'\\'`. [`char`]s that need more digits
+    /// are left to the next rule that applies to them. Values of `max_digits` greater than 7 (the
+    /// number of digits needed for [`char::MAX`]) behave like 7.
+    ///
+    /// If `strict` is `true`, unescaping requires exactly `max_digits` digits. Otherwise, it
+    /// accepts 1 to `max_digits` digits.
+    ///
+    /// ```
+    /// # use char_escape::{escaper, CharClass, ClassRule, Escaper, Notation};
+    /// #
+    /// const C_LIKE: Escaper<'static> = escaper! {
+    ///     '\n' => 'n',
+    /// }
+    /// .with_class_rules(&[ClassRule {
+    ///     class: CharClass::AsciiControl,
+    ///     notation: Notation::Octal {
+    ///         max_digits: 3,
+    ///         strict: false,
+    ///     },
+    /// }]);
+    ///
+    /// assert_eq!(C_LIKE.escape("\0\x1b[1m"), r"\000\033[1m");
+    /// assert_eq!(C_LIKE.unescape(r"\0\1019\1").expect("is properly escaped"), "\0A9\x01");
+    /// ```
+    Octal {
+        /// The maximum number of octal digits.
+        max_digits: u8,
+        /// Whether unescaping requires exactly `max_digits` digits.
+        strict: bool,
+    },
+    /// The UTF-16 code units using exactly four hexadecimal digits each, prefixed by `'u'`, like
+    /// in JSON.
+    ///
+    /// E.g. `'\0'` becomes `\u0000` if the escape character is `'\\'`. [`char`]s outside the Basic
+    /// Multilingual Plane become a surrogate pair, i.e. two escape sequences. Unescaping rejects
+    /// surrogates that are not part of a pair.
+    ///
+    /// ```
+    /// # use char_escape::{escaper, CharClass, ClassRule, Escaper, Notation};
+    /// #
+    /// const ESCAPER: Escaper<'static> = escaper! {
+    ///     '\n' => 'n',
+    /// }
+    /// .with_class_rules(&[ClassRule {
+    ///     class: CharClass::NonAscii,
+    ///     notation: Notation::Utf16,
+    /// }]);
+    ///
+    /// assert_eq!(ESCAPER.escape("ä😀"), r"\u00e4\ud83d\ude00");
+    /// assert_eq!(ESCAPER.unescape(r"\ud83d\ude00\u00E4").expect("is properly escaped"), "😀ä");
+    /// assert!(ESCAPER.unescape(r"\ud83d").is_err());
+    /// assert!(ESCAPER.unescape(r"\ude00").is_err());
+    /// ```
+    Utf16,
+    /// The UTF-8 bytes using exactly two uppercase hexadecimal digits each, like in URLs.
+    ///
+    /// E.g. `' '` becomes `%20` and `'ä'` becomes `%C3%A4` if the escape character is `'%'`.
+    /// [`char`]s that are not ASCII become several escape sequences. Unescaping accepts lowercase
+    /// digits as well and rejects byte sequences that are not valid UTF-8.
+    ///
+    /// ```
+    /// # use char_escape::{CharClass, ClassRule, Escaper, Notation};
+    /// #
+    /// const ESCAPER: Escaper<'static> = Escaper::new_unchecked('%', &[]).with_class_rules(&[
+    ///     ClassRule {
+    ///         class: CharClass::Predicate(|c| !c.is_ascii_alphanumeric()),
+    ///         notation: Notation::Percent,
+    ///     },
+    /// ]);
+    ///
+    /// assert_eq!(ESCAPER.escape("a b/ä"), "a%20b%2F%C3%A4");
+    /// assert_eq!(ESCAPER.unescape("%f0%9f%98%80%25").expect("is properly escaped"), "😀%");
+    /// assert!(ESCAPER.unescape("%C3").is_err());
+    /// assert!(ESCAPER.unescape("%C3%28").is_err());
+    /// ```
+    Percent,
+    /// The hexadecimal code point using exactly `digits` digits, prefixed by `prefix`.
+    ///
+    /// E.g. `'é'` becomes `\u00e9` if `prefix` is `'u'`, `digits` is 4 and the escape character
+    /// is `'\\'`. [`char`]s that need more digits are left to the next rule that applies to them.
+    /// Values of `digits` greater than 8 behave like 8.
+    ///
+    /// ```
+    /// # use char_escape::{escaper, Escaper, Notation};
+    /// #
+    /// const ESCAPER: Escaper<'static> = escaper! {
+    ///     '\0'..='\u{ff}' => Notation::FixedHex { prefix: 'x', digits: 2 },
+    ///     '\u{100}'..='\u{10ffff}' => Notation::FixedHex { prefix: 'U', digits: 8 },
+    /// };
+    ///
+    /// assert_eq!(ESCAPER.escape("ä😀"), r"\xe4\U0001f600");
+    /// assert_eq!(ESCAPER.unescape(r"\x41\U000000e4").expect("is properly escaped"), "Aä");
+    /// assert!(ESCAPER.unescape(r"\U0000d800").is_err());
+    /// ```
+    FixedHex {
+        /// The [`char`] following the escape character.
+        prefix: char,
+        /// The number of hexadecimal digits.
+        digits: u8,
+    },
+    /// The Unicode name in braces, prefixed by `'N'`, like in Python.
+    ///
+    /// E.g. `\N{EM DASH}` stands for `'—'` if the escape character is `'\\'`. Names are matched
+    /// case-insensitively. Escaping never uses this notation, so it is only useful for unescaping,
+    /// e.g. with [`CharClass::Predicate`](crate::CharClass::Predicate) matching no [`char`].
+    /// Unescaping requires the `unicode-names` feature, without it escape sequences in this
+    /// notation are invalid.
+    ///
+    /// ```
+    /// # use char_escape::{escaper, CharClass, ClassRule, Escaper, Notation};
+    /// #
+    /// const ESCAPER: Escaper<'static> = escaper! {
+    ///     '\n' => 'n',
+    /// }
+    /// .with_class_rules(&[ClassRule {
+    ///     class: CharClass::Predicate(|_| false),
+    ///     notation: Notation::Named,
+    /// }]);
+    ///
+    /// assert_eq!(ESCAPER.escape("—"), "—");
+    /// # #[cfg(feature = "unicode-names")]
+    /// assert_eq!(ESCAPER.unescape(r"\N{em dash}\n").expect("is properly escaped"), "—\n");
+    /// assert!(ESCAPER.unescape(r"\N{NO SUCH CHARACTER}").is_err());
+    /// ```
+    Named,
+}
+
+impl Notation {
+    /// Returns the escape sequence of `c` in this notation, without the leading escape character.
+    ///
+    /// Returns [`None`] if `c` can't be represented in this notation.
+    pub(crate) fn encode(self, c: char, escape_char: char) -> Option<EscapeSequence<'static>> {
+        let mut bytes = [0; INLINE_SEQUENCE_LEN];
+        let mut cursor = &mut bytes[..];
+        match self {
+            Self::Hex => write!(cursor, "x{{{:02x}}}", u32::from(c)),
+            Self::AsciiHex if c.is_ascii() => write!(cursor, "x{:02x}", u32::from(c)),
+            // escaping never uses `Named`
+            Self::AsciiHex | Self::Named => return None,
+            Self::Unicode => write!(cursor, "u{{{:x}}}", u32::from(c)),
+            Self::Octal { max_digits, .. } => {
+                let max_digits = clamp_octal_digits(max_digits);
+                let fits = 8_u32
+                    .checked_pow(u32::try_from(max_digits).expect("at most 7 digits"))
+                    .is_none_or(|limit| u32::from(c) < limit);
+                if max_digits == 0 || !fits {
+                    return None;
+                }
+                write!(cursor, "{:0max_digits$o}", u32::from(c))
+            }
+            Self::Utf16 => match *c.encode_utf16(&mut [0; 2]) {
+                [unit] => write!(cursor, "u{unit:04x}"),
+                [high, low] => write!(cursor, "u{high:04x}{escape_char}u{low:04x}"),
+                _ => unreachable!("a char consists of one or two UTF-16 code units"),
+            },
+            Self::FixedHex { prefix, digits } => {
+                let digits = clamp_hex_digits(digits);
+                let fits = 16_u32
+                    .checked_pow(u32::try_from(digits).expect("at most 8 digits"))
+                    .is_none_or(|limit| u32::from(c) < limit);
+                if digits == 0 || !fits {
+                    return None;
+                }
+                write!(cursor, "{prefix}{:0digits$x}", u32::from(c))
+            }
+            Self::Percent => {
+                let mut utf8 = [0; 4];
+                let mut result = Ok(());
+                for (i, byte) in c.encode_utf8(&mut utf8).bytes().enumerate() {
+                    if i > 0 {
+                        result = result.and_then(|()| write!(cursor, "{escape_char}"));
+                    }
+                    result = result.and_then(|()| write!(cursor, "{byte:02X}"));
+                }
+                result
+            }
+        }
+        .expect("fits into the inline buffer");
+        let len = INLINE_SEQUENCE_LEN - cursor.len();
+
+        Some(EscapeSequence::inline(bytes, len))
+    }
+
+    /// Decodes an escape sequence in this notation at the start of `rest`, the string following
+    /// an escape character.
+    ///
+    /// `at_end` indicates that `rest` extends to the end of the input, so the escape sequence
+    /// can't be continued.
+    ///
+    /// Returns [`None`] if `rest` doesn't start with an escape sequence in this notation.
+    pub(crate) fn decode(self, rest: &str, escape_char: char, at_end: bool) -> Option<Decoded> {
+        match self {
+            Self::Utf16 => decode_utf16(rest, escape_char),
+            Self::Percent => decode_percent(rest, escape_char),
+            Self::FixedHex { prefix, digits } => {
+                let digits = clamp_hex_digits(digits);
+                if digits == 0 {
+                    return None;
+                }
+                decode_fixed(rest, prefix.encode_utf8(&mut [0; 4]), 16, digits)
+            }
+            Self::Hex => decode_braced(rest, "x{", 16, 6),
+            Self::Unicode => decode_braced(rest, "u{", 16, 6),
+            Self::Octal { max_digits, strict } => {
+                decode_octal(rest, clamp_octal_digits(max_digits), strict, at_end)
+            }
+            #[cfg(feature = "unicode-names")]
+            Self::Named => decode_named(rest),
+            #[cfg(not(feature = "unicode-names"))]
+            Self::Named => None,
+            Self::AsciiHex => decode_fixed(rest, "x", 16, 2).map(|decoded| match decoded {
+                Decoded::Valid { unescaped, len } if !unescaped.is_ascii() => {
+                    Decoded::Invalid { len }
+                }
+                decoded => decoded,
+            }),
+        }
+    }
+}
+
+/// Limits the number of octal digits to the number needed for [`char::MAX`].
+fn clamp_octal_digits(max_digits: u8) -> usize {
+    usize::from(max_digits).min(7)
+}
+
+/// Limits the number of hexadecimal digits to the number that fits into a [`u32`].
+fn clamp_hex_digits(digits: u8) -> usize {
+    usize::from(digits).min(8)
+}
+
+/// Decodes an octal escape sequence of up to `max_digits` digits.
+///
+/// Fewer digits at the end of `rest` are incomplete unless `at_end` is `true` and `strict` is
+/// `false`, since more digits might follow.
+fn decode_octal(rest: &str, max_digits: usize, strict: bool, at_end: bool) -> Option<Decoded> {
+    let digits = rest
+        .bytes()
+        .take_while(|byte| (b'0'..=b'7').contains(byte))
+        .take(max_digits)
+        .count();
+    if digits == 0 {
+        return None;
+    }
+
+    let decoded = if digits < max_digits && digits == rest.len() && (strict || !at_end) {
+        Decoded::Incomplete
+    } else if strict && digits < max_digits {
+        Decoded::Invalid { len: digits }
+    } else {
+        match u32::from_str_radix(&rest[..digits], 8)
+            .ok()
+            .and_then(char::from_u32)
+        {
+            Some(unescaped) => Decoded::Valid {
+                unescaped,
+                len: digits,
+            },
+            None => Decoded::Invalid { len: digits },
+        }
+    };
+
+    Some(decoded)
+}
+
+/// Decodes an escape sequence consisting of `prefix` followed by exactly `digits` digits in the
+/// given `radix`.
+fn decode_fixed(rest: &str, prefix: &str, radix: u32, digits: usize) -> Option<Decoded> {
+    let decoded = match decode_fixed_value(rest, prefix, radix, digits)? {
+        Ok((value, len)) => match char::from_u32(value) {
+            Some(unescaped) => Decoded::Valid { unescaped, len },
+            None => Decoded::Invalid { len },
+        },
+        Err(decoded) => decoded,
+    };
+
+    Some(decoded)
+}
+
+/// Like [`decode_fixed()`], but returns the value and the length of the escape sequence instead
+/// of a [`char`].
+///
+/// Returns [`Err`] if the escape sequence is incomplete or invalid.
+fn decode_fixed_value(
+    rest: &str,
+    prefix: &str,
+    radix: u32,
+    digits: usize,
+) -> Option<Result<(u32, usize), Decoded>> {
+    if rest.is_empty() || !(rest.starts_with(prefix) || prefix.starts_with(rest)) {
+        return None;
+    }
+
+    let after_prefix = rest.get(prefix.len()..).unwrap_or_default();
+    let end = after_prefix
+        .find(|c: char| !c.is_digit(radix))
+        .unwrap_or(after_prefix.len())
+        .min(digits);
+    let decoded = if end == digits {
+        let value = u32::from_str_radix(&after_prefix[..digits], radix)
+            .expect("consists of at most 8 valid digits");
+        Ok((value, prefix.len() + digits))
+    } else if end == after_prefix.len() {
+        Err(Decoded::Incomplete)
+    } else {
+        Err(Decoded::Invalid {
+            len: prefix.len() + end,
+        })
+    };
+
+    Some(decoded)
+}
+
+/// Decodes one or, for surrogate pairs, two UTF-16 code units in [`Notation::Utf16`].
+fn decode_utf16(rest: &str, escape_char: char) -> Option<Decoded> {
+    let (high, len) = match decode_fixed_value(rest, "u", 16, 4)? {
+        Ok(unit) => unit,
+        Err(decoded) => return Some(decoded),
+    };
+
+    let decoded = match high {
+        0xd800..=0xdbff => {
+            let after = &rest[len..];
+            match after.strip_prefix(escape_char) {
+                Some(low) => match decode_fixed_value(low, "u", 16, 4) {
+                    Some(Ok((low @ 0xdc00..=0xdfff, low_len))) => Decoded::Valid {
+                        unescaped: char::from_u32(0x10000 + ((high - 0xd800) << 10) + low - 0xdc00)
+                            .expect("a surrogate pair encodes a valid char"),
+                        len: len + escape_char.len_utf8() + low_len,
+                    },
+                    Some(Err(Decoded::Incomplete)) => Decoded::Incomplete,
+                    None if low.is_empty() => Decoded::Incomplete,
+                    _ => Decoded::Invalid { len },
+                },
+                None if after.is_empty() => Decoded::Incomplete,
+                None => Decoded::Invalid { len },
+            }
+        }
+        0xdc00..=0xdfff => Decoded::Invalid { len },
+        _ => Decoded::Valid {
+            unescaped: char::from_u32(high).expect("is not a surrogate"),
+            len,
+        },
+    };
+
+    Some(decoded)
+}
+
+/// Decodes the UTF-8 bytes of one [`char`] in [`Notation::Percent`].
+fn decode_percent(rest: &str, escape_char: char) -> Option<Decoded> {
+    if !rest.starts_with(|c: char| c.is_ascii_hexdigit()) {
+        return None;
+    }
+
+    let (first, mut len) = match decode_fixed_value(rest, "", 16, 2)? {
+        Ok(byte) => byte,
+        Err(decoded) => return Some(decoded),
+    };
+
+    let width = match first {
+        0x00..=0x7f => 1,
+        0xc2..=0xdf => 2,
+        0xe0..=0xef => 3,
+        0xf0..=0xf4 => 4,
+        _ => return Some(Decoded::Invalid { len }),
+    };
+    let mut utf8 = [
+        u8::try_from(first).expect("consists of two hex digits"),
+        0,
+        0,
+        0,
+    ];
+    for byte in &mut utf8[1..width] {
+        let after = &rest[len..];
+        let Some(next) = after.strip_prefix(escape_char) else {
+            return Some(if after.is_empty() {
+                Decoded::Incomplete
+            } else {
+                Decoded::Invalid { len }
+            });
+        };
+        match decode_fixed_value(next, "", 16, 2) {
+            Some(Ok((value @ 0x80..=0xbf, value_len))) => {
+                *byte = u8::try_from(value).expect("is a continuation byte");
+                len += escape_char.len_utf8() + value_len;
+            }
+            Some(Err(Decoded::Incomplete)) => return Some(Decoded::Incomplete),
+            None if next.is_empty() => return Some(Decoded::Incomplete),
+            _ => return Some(Decoded::Invalid { len }),
+        }
+    }
+
+    let decoded = match core::str::from_utf8(&utf8[..width]) {
+        Ok(s) => Decoded::Valid {
+            unescaped: s.chars().next().expect("consists of one char"),
+            len,
+        },
+        // overlong encodings and surrogates
+        Err(_) => Decoded::Invalid { len },
+    };
+
+    Some(decoded)
+}
+
+/// Decodes a Unicode name in [`Notation::Named`].
+#[cfg(feature = "unicode-names")]
+fn decode_named(rest: &str) -> Option<Decoded> {
+    const PREFIX: &str = "N{";
+
+    if rest.is_empty() || !(rest.starts_with(PREFIX) || PREFIX.starts_with(rest)) {
+        return None;
+    }
+
+    let name = rest.get(PREFIX.len()..).unwrap_or_default();
+    let end = name
+        .find(|c: char| !(c.is_ascii_alphanumeric() || c == ' ' || c == '-'))
+        .unwrap_or(name.len());
+    let decoded = if end == name.len() {
+        Decoded::Incomplete
+    } else if name[end..].starts_with('}') {
+        let len = PREFIX.len() + end + 1;
+        match unicode_names2::character(&name[..end]) {
+            Some(unescaped) => Decoded::Valid { unescaped, len },
+            None => Decoded::Invalid { len },
+        }
+    } else {
+        Decoded::Invalid {
+            len: PREFIX.len() + end,
+        }
+    };
+
+    Some(decoded)
+}
+
+/// Decodes an escape sequence consisting of `prefix`, up to `max_digits` digits in the given
+/// `radix` and a closing brace.
+fn decode_braced(rest: &str, prefix: &str, radix: u32, max_digits: usize) -> Option<Decoded> {
+    if rest.is_empty() || !(rest.starts_with(prefix) || prefix.starts_with(rest)) {
+        return None;
+    }
+
+    let digits = rest.get(prefix.len()..).unwrap_or_default();
+    let end = digits
+        .find(|c: char| !c.is_digit(radix))
+        .unwrap_or(digits.len());
+    let decoded = if end > max_digits {
+        Decoded::Invalid {
+            len: prefix.len() + max_digits,
+        }
+    } else if end == digits.len() {
+        Decoded::Incomplete
+    } else if digits[end..].starts_with('}') {
+        // fails for an empty string of digits
+        let unescaped = u32::from_str_radix(&digits[..end], radix)
+            .ok()
+            .and_then(char::from_u32);
+        let len = prefix.len() + end + 1;
+        match unescaped {
+            Some(unescaped) => Decoded::Valid { unescaped, len },
+            None => Decoded::Invalid { len },
+        }
+    } else {
+        Decoded::Invalid {
+            len: prefix.len() + end,
+        }
+    };
+
+    Some(decoded)
+}
@@ -0,0 +1,250 @@
+use {
+    crate::{Escaper, Rule, Tokens, UnescapeChars, UnescapeError},
+    core::fmt::{self, Display},
+    std::{borrow::Cow, error::Error, io},
+};
+
+/// An [`Escaper`] that can only escape.
+///
+/// Since the result is never unescaped, the rules don't need to contain a [`Rule`] for escaping
+/// the escape character and several [`char`]s may be escaped the same way. The only requirement
+/// is that there is at most one [`Rule`] for every [`char`].
+///
+/// ```
+/// # use char_escape::{EncodeOnly, Rule};
+/// #
+/// // both '\r' and '\n' become "\n", and '\\' is not escaped
+/// let encoder = EncodeOnly::new('\\', &[
+///     Rule {
+///         unescaped: '\r',
+///         escaped: 'n',
+///     },
+///     Rule {
+///         unescaped: '\n',
+///         escaped: 'n',
+///     },
+/// ])
+/// .expect("rules are valid");
+///
+/// assert_eq!(encoder.escape("C:\\dir\r\n"), r"C:\dir\n\n");
+/// ```
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct EncodeOnly<'a> {
+    escaper: Escaper<'a>,
+}
+
+impl<'a> EncodeOnly<'a> {
+    /// Create a new [`EncodeOnly`].
+    ///
+    /// # Errors
+    ///
+    /// If two of the provided `rules` escape the same [`char`].
+    pub fn new(escape_char: char, rules: &'a [Rule]) -> Result<Self, ConflictingRules> {
+        match find_conflict(rules, |rule| rule.unescaped) {
+            Some(error) => Err(error),
+            None => Ok(Self {
+                escaper: Escaper::new_unchecked(escape_char, rules),
+            }),
+        }
+    }
+
+    /// Returns the underlying [`Escaper`].
+    ///
+    /// The [`Escaper`] may not be able to unescape what it escaped.
+    pub const fn escaper(&self) -> Escaper<'a> {
+        self.escaper
+    }
+
+    /// Escapes a string.
+    ///
+    /// See [`Escaper::escape()`].
+    pub fn escape<'s>(&self, s: &'s str) -> Cow<'s, str> {
+        self.escaper.escape_cow(s)
+    }
+
+    /// Escapes a string and appends the result to `out`.
+    ///
+    /// See [`Escaper::escape_into()`].
+    pub fn escape_into(&self, s: &str, out: &mut String) {
+        self.escaper.escape_into(s, out);
+    }
+
+    /// Escapes a string and writes the result to `writer`.
+    ///
+    /// See [`Escaper::escape_to_writer()`].
+    ///
+    /// # Errors
+    ///
+    /// If writing to `writer` fails.
+    pub fn escape_to_writer<W: io::Write>(&self, s: &str, writer: W) -> io::Result<()> {
+        self.escaper.escape_to_writer(s, writer)
+    }
+
+    /// Check if the given string contains any [`char`] that needs to be escaped.
+    ///
+    /// See [`Escaper::needs_escaping()`].
+    pub fn needs_escaping(&self, s: &str) -> bool {
+        self.escaper.needs_escaping(s)
+    }
+
+    /// Returns the length in bytes of the escaped form of `s`.
+    ///
+    /// See [`Escaper::escaped_len()`].
+    pub fn escaped_len(&self, s: &str) -> usize {
+        self.escaper.escaped_len(s)
+    }
+}
+
+/// An [`Escaper`] that can only unescape.
+///
+/// Since nothing is ever escaped, the rules don't need to contain a [`Rule`] for escaping the
+/// escape character and several escape sequences may stand for the same [`char`]. The only
+/// requirement is that there is at most one [`Rule`] for every escape sequence.
+///
+/// ```
+/// # use char_escape::{DecodeOnly, Rule};
+/// #
+/// let decoder = DecodeOnly::new('%', &[
+///     Rule {
+///         unescaped: ' ',
+///         escaped: '_',
+///     },
+///     Rule {
+///         unescaped: ' ',
+///         escaped: 's',
+///     },
+/// ])
+/// .expect("rules are valid");
+///
+/// assert_eq!(decoder.unescape("a%_b%sc").expect("is properly escaped"), "a b c");
+/// ```
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct DecodeOnly<'a> {
+    escaper: Escaper<'a>,
+}
+
+impl<'a> DecodeOnly<'a> {
+    /// Create a new [`DecodeOnly`].
+    ///
+    /// # Errors
+    ///
+    /// If two of the provided `rules` use the same escaped [`char`].
+    pub fn new(escape_char: char, rules: &'a [Rule]) -> Result<Self, ConflictingRules> {
+        match find_conflict(rules, |rule| rule.escaped) {
+            Some(error) => Err(error),
+            None => Ok(Self::new_unchecked(escape_char, rules)),
+        }
+    }
+
+    /// Create a new [`DecodeOnly`] without verifying that the `rules` are valid.
+    ///
+    /// Use the [`decode_only!`](crate::decode_only) macro or [`DecodeOnly::new()`] instead, if
+    /// possible. If several of the `rules` use the same escaped [`char`], only the last of them
+    /// is used.
+    pub const fn new_unchecked(escape_char: char, rules: &'a [Rule]) -> Self {
+        Self {
+            escaper: Escaper::new_unchecked(escape_char, rules),
+        }
+    }
+
+    /// Returns the underlying [`Escaper`].
+    ///
+    /// The [`Escaper`] may not be able to escape what it unescapes.
+    pub const fn escaper(&self) -> Escaper<'a> {
+        self.escaper
+    }
+
+    /// Unescapes a string.
+    ///
+    /// See [`Escaper::unescape()`].
+    ///
+    /// # Errors
+    ///
+    /// If `s` is not properly escaped.
+    pub fn unescape(&self, s: &str) -> Result<String, UnescapeError> {
+        self.escaper.unescape(s)
+    }
+
+    /// Unescapes a string, borrowing it if it contains no escape sequence.
+    ///
+    /// See [`Escaper::unescape_cow()`].
+    ///
+    /// # Errors
+    ///
+    /// If `s` is not properly escaped.
+    pub fn unescape_cow<'s>(&self, s: &'s str) -> Result<Cow<'s, str>, UnescapeError> {
+        self.escaper.unescape_cow(s)
+    }
+
+    /// Unescapes a string and appends the result to `out`.
+    ///
+    /// See [`Escaper::unescape_into()`].
+    ///
+    /// # Errors
+    ///
+    /// If `s` is not properly escaped.
+    pub fn unescape_into(&self, s: &str, out: &mut String) -> Result<(), UnescapeError> {
+        self.escaper.unescape_into(s, out)
+    }
+
+    /// Unescapes a string, replacing invalid escape sequences with `'\u{fffd}'`.
+    ///
+    /// See [`Escaper::unescape_lossy()`].
+    pub fn unescape_lossy(&self, s: &str) -> String {
+        self.escaper.unescape_lossy(s)
+    }
+
+    /// Returns an iterator over the [`char`]s of the unescaped form of `s`.
+    ///
+    /// See [`Escaper::unescape_chars()`].
+    pub fn unescape_chars(&'a self, s: &'a str) -> UnescapeChars<'a> {
+        self.escaper.unescape_chars(s)
+    }
+
+    /// Returns an iterator over the literal runs and escape sequences of `s`.
+    ///
+    /// See [`Escaper::tokens()`].
+    pub fn tokens(&'a self, s: &'a str) -> Tokens<'a> {
+        self.escaper.tokens(s)
+    }
+}
+
+/// Returns the first two rules that have the same `key`.
+pub(crate) fn find_conflict(
+    rules: &[Rule],
+    key: impl Fn(&Rule) -> char,
+) -> Option<ConflictingRules> {
+    rules.iter().enumerate().find_map(|(i, first)| {
+        rules[i + 1..]
+            .iter()
+            .find(|second| key(first) == key(second))
+            .map(|second| ConflictingRules {
+                first: *first,
+                second: *second,
+            })
+    })
+}
+
+/// Results from creating an [`EncodeOnly`] or [`DecodeOnly`] from rules that contradict each
+/// other.
+///
+/// Also used by [`MergeError`](crate::MergeError) and [`InverseError`](crate::InverseError).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ConflictingRules {
+    /// The first of the conflicting rules.
+    pub first: Rule,
+    /// The second of the conflicting rules.
+    pub second: Rule,
+}
+
+impl Display for ConflictingRules {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "rules {:?} => {:?} and {:?} => {:?} conflict",
+            self.first.unescaped, self.first.escaped, self.second.unescaped, self.second.escaped
+        )
+    }
+}
+
+impl Error for ConflictingRules {}
@@ -0,0 +1,511 @@
+use {
+    crate::{
+        ClassRule, Escaper, Fallback, MissingEscapeCharRule, PositionalRule, Rule, RuleSetError,
+        SequenceRule, TrailingEscape,
+    },
+    std::collections::{BTreeMap, HashMap, HashSet},
+};
+
+/// An owned version of [`Escaper`].
+///
+/// [`Escaper`] borrows its rules, [`EscaperBuf`] stores them in a [`Vec`]. This makes it easy to
+/// create escapers at runtime, e.g. from a configuration file, and to store them in structs.
+///
+/// Use [`as_escaper()`](Self::as_escaper) to escape and unescape strings.
+///
+/// ```
+/// # use char_escape::{EscaperBuf, Rule};
+/// #
+/// // e.g. read from a configuration file
+/// let config = [('\n', 'n'), ('\t', 't'), ('\\', '\\')];
+///
+/// let rules = config
+///     .into_iter()
+///     .map(|(unescaped, escaped)| Rule { unescaped, escaped })
+///     .collect();
+/// let escaper = EscaperBuf::new('\\', rules).expect("rules are valid");
+///
+/// assert_eq!(escaper.as_escaper().escape("a\tb"), r"a\tb");
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EscaperBuf {
+    escape_char: char,
+    rules: Vec<Rule>,
+    sequence_rules: Vec<SequenceRule<'static>>,
+    class_rules: Vec<ClassRule>,
+    positional_rules: Vec<PositionalRule>,
+    aliases: Vec<Rule>,
+    fallback: Fallback,
+    case_insensitive: bool,
+    trailing_escape: TrailingEscape,
+}
+
+impl EscaperBuf {
+    /// Create a new [`EscaperBuf`].
+    ///
+    /// # Errors
+    ///
+    /// If the provided `rules` are not valid, see [`Escaper::new()`].
+    pub fn new(escape_char: char, rules: Vec<Rule>) -> Result<Self, RuleSetError> {
+        Escaper::new(escape_char, &rules)?;
+        Ok(Self {
+            escape_char,
+            rules,
+            sequence_rules: Vec::new(),
+            class_rules: Vec::new(),
+            positional_rules: Vec::new(),
+            aliases: Vec::new(),
+            fallback: Fallback::Keep,
+            case_insensitive: false,
+            trailing_escape: TrailingEscape::Error,
+        })
+    }
+
+    /// Adds a [`Rule`] escaping each of the given [`char`]s to itself.
+    ///
+    /// [`char`]s that already have a [`Rule`] are skipped, so this never overrides the escape
+    /// character or any other existing [`Rule`]. No other [`Rule`] may use one of the [`char`]s as
+    /// its `escaped` [`char`], otherwise unescaping is ambiguous.
+    ///
+    /// ```
+    /// # use char_escape::{escaper, EscaperBuf};
+    /// #
+    /// let escaper = EscaperBuf::new('\\', vec![('\n', 'n').into(), ('\\', '\\').into()])
+    ///     .expect("rules are valid")
+    ///     .escape_same(['%', '&', '|', '\\']);
+    ///
+    /// assert_eq!(escaper.as_escaper().escape("a|b&c\n"), r"a\|b\&c\n");
+    /// assert_eq!(escaper.rules().len(), 5);
+    /// ```
+    #[must_use]
+    pub fn escape_same(mut self, chars: impl IntoIterator<Item = char>) -> Self {
+        for c in chars {
+            if !self.rules.iter().any(|rule| rule.unescaped == c) {
+                self.rules.push(Rule::same(c));
+            }
+        }
+
+        self
+    }
+
+    /// Adds a [`Rule`].
+    ///
+    /// Since later rules take precedence, this overrides any existing [`Rule`] for the same
+    /// [`char`] when escaping.
+    ///
+    /// ```
+    /// # use char_escape::{escaper, EscaperBuf, Rule};
+    /// #
+    /// let mut escaper = EscaperBuf::from(escaper! { '\n' => 'n' });
+    ///
+    /// escaper
+    ///     .add_rule(Rule {
+    ///         unescaped: '\t',
+    ///         escaped: 't',
+    ///     })
+    ///     .expect("rules are valid");
+    ///
+    /// assert_eq!(escaper.as_escaper().escape("\t\n"), r"\t\n");
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// If the rules in effect afterwards are not valid, see [`Escaper::new()`]. The rules are
+    /// left unchanged in that case.
+    ///
+    /// ```
+    /// # use char_escape::{escaper, EscaperBuf, Rule, RuleSetError};
+    /// #
+    /// let mut escaper = EscaperBuf::from(escaper! { '\n' => 'n' });
+    ///
+    /// let result = escaper.add_rule(Rule {
+    ///     unescaped: 'x',
+    ///     escaped: 'n',
+    /// });
+    ///
+    /// assert!(matches!(result, Err(RuleSetError::DuplicateEscaped(_))));
+    /// assert_eq!(escaper.as_escaper(), escaper! { '\n' => 'n' });
+    /// ```
+    pub fn add_rule(&mut self, rule: Rule) -> Result<(), RuleSetError> {
+        self.rules.push(rule);
+        if let Err(error) = check_effective_rules(self.escape_char, &self.rules) {
+            self.rules.pop();
+            return Err(error);
+        }
+
+        Ok(())
+    }
+
+    /// Adds a [`Rule`] that is known not to conflict with the rules in effect.
+    pub(crate) fn push_rule(&mut self, rule: Rule) {
+        self.rules.push(rule);
+    }
+
+    /// Removes all rules for escaping `unescaped` and returns the one that was in effect.
+    ///
+    /// # Errors
+    ///
+    /// If `unescaped` is the escape character. The rules are left unchanged in that case.
+    ///
+    /// ```
+    /// # use char_escape::{escaper, EscaperBuf, MissingEscapeCharRule, Rule};
+    /// #
+    /// let mut escaper = EscaperBuf::from(escaper! { '\n' => 'n', '\t' => 't' });
+    ///
+    /// assert_eq!(escaper.remove_rule('\t'), Ok(Some(Rule {
+    ///     unescaped: '\t',
+    ///     escaped: 't',
+    /// })));
+    /// assert_eq!(escaper.remove_rule('\t'), Ok(None));
+    /// assert_eq!(escaper.remove_rule('\\'), Err(MissingEscapeCharRule::new()));
+    ///
+    /// assert_eq!(escaper.as_escaper().escape("\t\n"), "\t\\n");
+    /// ```
+    pub fn remove_rule(&mut self, unescaped: char) -> Result<Option<Rule>, MissingEscapeCharRule> {
+        if unescaped == self.escape_char {
+            return Err(MissingEscapeCharRule::new());
+        }
+
+        Ok(self.take_rules(unescaped))
+    }
+
+    /// Replaces all rules for escaping `rule.unescaped` with `rule` and returns the one that was in
+    /// effect before.
+    ///
+    /// ```
+    /// # use char_escape::{escaper, EscaperBuf, Rule};
+    /// #
+    /// let mut escaper = EscaperBuf::from(escaper! { '\n' => 'n' });
+    ///
+    /// let previous = escaper
+    ///     .replace_rule(Rule {
+    ///         unescaped: '\n',
+    ///         escaped: 'N',
+    ///     })
+    ///     .expect("rules are valid");
+    ///
+    /// assert_eq!(previous.map(|rule| rule.escaped), Some('n'));
+    /// assert_eq!(escaper.as_escaper().escape("\n"), r"\N");
+    /// assert!(escaper.as_escaper().unescape(r"\n").is_err());
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// If the rules in effect afterwards are not valid, see [`Escaper::new()`]. The rules are
+    /// left unchanged in that case.
+    ///
+    /// ```
+    /// # use char_escape::{escaper, EscaperBuf, Rule, RuleSetError};
+    /// #
+    /// let mut escaper = EscaperBuf::from(escaper! { '\t' => 't' });
+    ///
+    /// let result = escaper.replace_rule(Rule {
+    ///     unescaped: '\t',
+    ///     escaped: '\\',
+    /// });
+    ///
+    /// assert!(matches!(result, Err(RuleSetError::DuplicateEscaped(_))));
+    /// assert_eq!(escaper.as_escaper(), escaper! { '\t' => 't' });
+    /// ```
+    pub fn replace_rule(&mut self, rule: Rule) -> Result<Option<Rule>, RuleSetError> {
+        let mut candidate: Vec<Rule> = self
+            .rules
+            .iter()
+            .copied()
+            .filter(|other| other.unescaped != rule.unescaped)
+            .collect();
+        candidate.push(rule);
+        check_effective_rules(self.escape_char, &candidate)?;
+
+        let previous = self.take_rules(rule.unescaped);
+        self.rules.push(rule);
+
+        Ok(previous)
+    }
+
+    /// Removes all rules except the one for escaping the escape character.
+    ///
+    /// ```
+    /// # use char_escape::{escaper, EscaperBuf, Rule};
+    /// #
+    /// let mut escaper = EscaperBuf::from(escaper! { '\n' => 'n', '\t' => 't' });
+    ///
+    /// escaper.clear();
+    ///
+    /// assert_eq!(escaper.rules(), [Rule::same('\\')]);
+    /// ```
+    pub fn clear(&mut self) {
+        let escape_char_rule = self
+            .as_escaper()
+            .effective_rule(self.escape_char)
+            .unwrap_or(Rule::same(self.escape_char));
+        self.rules.clear();
+        self.rules.push(escape_char_rule);
+    }
+
+    /// Removes all rules for escaping `unescaped` and returns the one that was in effect.
+    fn take_rules(&mut self, unescaped: char) -> Option<Rule> {
+        let effective = self.as_escaper().effective_rule(unescaped);
+        self.rules.retain(|rule| rule.unescaped != unescaped);
+
+        effective
+    }
+
+    /// Replaces the rules whose escape sequences consist of more than one [`char`].
+    ///
+    /// See also [`Escaper::with_sequence_rules()`].
+    #[must_use]
+    pub fn with_sequence_rules(self, sequence_rules: Vec<SequenceRule<'static>>) -> Self {
+        Self {
+            sequence_rules,
+            ..self
+        }
+    }
+
+    /// Returns an [`Escaper`] that borrows the rules of this [`EscaperBuf`].
+    pub fn as_escaper(&self) -> Escaper<'_> {
+        Escaper::new_unchecked(self.escape_char, &self.rules)
+            .with_sequence_rules(&self.sequence_rules)
+            .with_class_rules(&self.class_rules)
+            .with_positional_rules(&self.positional_rules)
+            .with_aliases(&self.aliases)
+            .with_fallback(self.fallback)
+            .with_case_insensitive(self.case_insensitive)
+            .with_trailing_escape(self.trailing_escape)
+    }
+
+    /// Replaces the rules that escape whole classes of [`char`]s.
+    ///
+    /// See also [`Escaper::with_class_rules()`].
+    #[must_use]
+    pub fn with_class_rules(self, class_rules: Vec<ClassRule>) -> Self {
+        Self {
+            class_rules,
+            ..self
+        }
+    }
+
+    /// Replaces the rules that only apply at specific positions.
+    ///
+    /// See also [`Escaper::with_positional_rules()`].
+    #[must_use]
+    pub fn with_positional_rules(self, positional_rules: Vec<PositionalRule>) -> Self {
+        Self {
+            positional_rules,
+            ..self
+        }
+    }
+
+    /// Replaces the rules that are only used for unescaping.
+    ///
+    /// See also [`Escaper::with_aliases()`].
+    #[must_use]
+    pub fn with_aliases(self, aliases: Vec<Rule>) -> Self {
+        Self { aliases, ..self }
+    }
+
+    /// Sets what happens to [`char`]s outside of the safe alphabet that no rule applies to.
+    ///
+    /// See also [`Escaper::with_fallback()`].
+    #[must_use]
+    pub fn with_fallback(self, fallback: Fallback) -> Self {
+        Self { fallback, ..self }
+    }
+
+    /// Sets whether unescaping ignores the case of escape sequences.
+    ///
+    /// See also [`Escaper::with_case_insensitive()`].
+    #[must_use]
+    pub fn with_case_insensitive(self, case_insensitive: bool) -> Self {
+        Self {
+            case_insensitive,
+            ..self
+        }
+    }
+
+    /// Sets what happens when unescaping a string that ends in the middle of an escape sequence.
+    ///
+    /// See also [`Escaper::with_trailing_escape()`].
+    #[must_use]
+    pub fn with_trailing_escape(self, trailing_escape: TrailingEscape) -> Self {
+        Self {
+            trailing_escape,
+            ..self
+        }
+    }
+
+    /// Returns the escape character.
+    pub const fn escape_char(&self) -> char {
+        self.escape_char
+    }
+
+    /// Returns the rules used for escaping and unescaping.
+    pub fn rules(&self) -> &[Rule] {
+        &self.rules
+    }
+
+    /// Returns the rules whose escape sequences consist of more than one [`char`].
+    pub fn sequence_rules(&self) -> &[SequenceRule<'static>] {
+        &self.sequence_rules
+    }
+
+    /// Returns the rules that escape whole classes of [`char`]s.
+    pub fn class_rules(&self) -> &[ClassRule] {
+        &self.class_rules
+    }
+
+    /// Returns the rules that only apply at specific positions.
+    pub fn positional_rules(&self) -> &[PositionalRule] {
+        &self.positional_rules
+    }
+
+    /// Returns the rules that are only used for unescaping.
+    pub fn aliases(&self) -> &[Rule] {
+        &self.aliases
+    }
+}
+
+/// Checks the rules in effect, i.e. the last [`Rule`] for every [`char`], using
+/// [`Escaper::new()`].
+///
+/// Earlier rules for the same [`char`] are overridden on purpose, so they are not reported as
+/// [`RuleSetError::DuplicateUnescaped`].
+fn check_effective_rules(escape_char: char, rules: &[Rule]) -> Result<(), RuleSetError> {
+    let mut seen = HashSet::new();
+    let mut effective: Vec<Rule> = rules
+        .iter()
+        .rev()
+        .copied()
+        .filter(|rule| seen.insert(rule.unescaped))
+        .collect();
+    effective.reverse();
+
+    Escaper::new(escape_char, &effective).map(|_| ())
+}
+
+impl From<Escaper<'_>> for EscaperBuf {
+    fn from(escaper: Escaper<'_>) -> Self {
+        Self {
+            escape_char: escaper.escape_char(),
+            rules: escaper.rules().to_vec(),
+            sequence_rules: escaper
+                .sequence_rules()
+                .iter()
+                .cloned()
+                .map(SequenceRule::into_owned)
+                .collect(),
+            class_rules: escaper.class_rules().to_vec(),
+            positional_rules: escaper.positional_rules().to_vec(),
+            aliases: escaper.aliases().to_vec(),
+            fallback: escaper.fallback(),
+            case_insensitive: escaper.is_case_insensitive(),
+            trailing_escape: escaper.trailing_escape(),
+        }
+    }
+}
+
+impl<'a> From<&'a EscaperBuf> for Escaper<'a> {
+    fn from(escaper: &'a EscaperBuf) -> Self {
+        escaper.as_escaper()
+    }
+}
+
+/// Collects rules into an [`EscaperBuf`] the same way the [`escaper!`](crate::escaper) macro
+/// does.
+///
+/// The escape character is `'\\'`. If there is no rule for escaping the escape character, the
+/// rule `'\\' => '\\'` is inserted before the others.
+///
+/// ```
+/// # use char_escape::{escaper, EscaperBuf};
+/// #
+/// let escaper: EscaperBuf = [('\n', 'n'), ('\t', 't')].into_iter().collect();
+///
+/// assert_eq!(escaper.as_escaper(), escaper! { '\n' => 'n', '\t' => 't' });
+/// ```
+impl FromIterator<Rule> for EscaperBuf {
+    fn from_iter<I: IntoIterator<Item = Rule>>(iter: I) -> Self {
+        const ESCAPE_CHAR: char = '\\';
+
+        let mut rules: Vec<Rule> = iter.into_iter().collect();
+        if !crate::contains_escape_char_rule(ESCAPE_CHAR, &rules) {
+            rules.insert(0, Rule::same(ESCAPE_CHAR));
+        }
+
+        Self {
+            escape_char: ESCAPE_CHAR,
+            rules,
+            sequence_rules: Vec::new(),
+            class_rules: Vec::new(),
+            positional_rules: Vec::new(),
+            aliases: Vec::new(),
+            fallback: Fallback::Keep,
+            case_insensitive: false,
+            trailing_escape: TrailingEscape::Error,
+        }
+    }
+}
+
+/// Same as the implementation of `FromIterator<Rule>`, with the tuples being of the form
+/// `(unescaped, escaped)`.
+impl FromIterator<(char, char)> for EscaperBuf {
+    fn from_iter<I: IntoIterator<Item = (char, char)>>(iter: I) -> Self {
+        iter.into_iter().map(Rule::from).collect()
+    }
+}
+
+/// Creates an [`EscaperBuf`] with the escape character `'\\'` from a map of unescaped to escaped
+/// [`char`]s.
+///
+/// # Errors
+///
+/// If the map doesn't contain a rule for escaping `'\\'` or if two [`char`]s are escaped the
+/// same way.
+///
+/// ```
+/// # use {char_escape::EscaperBuf, std::collections::HashMap};
+/// #
+/// let mut map = HashMap::new();
+/// map.insert('\n', 'n');
+/// map.insert('\\', '\\');
+///
+/// let escaper = EscaperBuf::try_from(&map).expect("rules are valid");
+///
+/// assert_eq!(escaper.as_escaper().escape("a\nb"), r"a\nb");
+/// ```
+impl<S> TryFrom<&HashMap<char, char, S>> for EscaperBuf {
+    type Error = RuleSetError;
+
+    fn try_from(map: &HashMap<char, char, S>) -> Result<Self, Self::Error> {
+        Self::new(
+            '\\',
+            map.iter().map(|(&k, &v)| Rule::from((k, v))).collect(),
+        )
+    }
+}
+
+/// Creates an [`EscaperBuf`] with the escape character `'\\'` from a map of unescaped to escaped
+/// [`char`]s.
+///
+/// # Errors
+///
+/// If the map doesn't contain a rule for escaping `'\\'` or if two [`char`]s are escaped the
+/// same way.
+///
+/// ```
+/// # use {char_escape::{EscaperBuf, RuleSetError}, std::collections::BTreeMap};
+/// #
+/// let map = BTreeMap::from([('\n', 'n'), ('\t', 't')]);
+///
+/// assert_eq!(EscaperBuf::try_from(&map), Err(RuleSetError::MissingEscapeCharRule));
+/// ```
+impl TryFrom<&BTreeMap<char, char>> for EscaperBuf {
+    type Error = RuleSetError;
+
+    fn try_from(map: &BTreeMap<char, char>) -> Result<Self, Self::Error> {
+        Self::new(
+            '\\',
+            map.iter().map(|(&k, &v)| Rule::from((k, v))).collect(),
+        )
+    }
+}
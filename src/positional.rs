@@ -0,0 +1,58 @@
+/// Where in a string a [`PositionalRule`] applies.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Position {
+    /// At the very start of the string.
+    StartOfString,
+    /// At the start of the string and directly after every `'\n'`.
+    StartOfLine,
+}
+
+impl Position {
+    /// Returns `true` if a [`char`] directly following `prev` is at this position.
+    ///
+    /// `prev` is [`None`] at the start of the string.
+    pub(crate) const fn matches(self, prev: Option<char>) -> bool {
+        match self {
+            Self::StartOfString => prev.is_none(),
+            Self::StartOfLine => matches!(prev, None | Some('\n')),
+        }
+    }
+}
+
+/// Defines how one specific [`char`] should be escaped when it is at a specific position.
+///
+/// Elsewhere, the [`char`] is left as it is, unless another rule applies to it. Unescaping
+/// accepts the escape sequence anywhere in the string.
+///
+/// The position is determined using the unescaped string, e.g. a [`char`] following an escaped
+/// `'\n'` is at the start of a line.
+///
+/// ```
+/// # use char_escape::{escaper, Escaper, Position, PositionalRule};
+/// #
+/// // comments start with '#' at the start of a line
+/// const ESCAPER: Escaper<'static> = escaper! {
+///     '\n' => 'n',
+/// }
+/// .with_positional_rules(&[PositionalRule {
+///     unescaped: '#',
+///     escaped: '#',
+///     position: Position::StartOfLine,
+/// }]);
+///
+/// let unescaped = "#1 and #2\n#3";
+/// let escaped = r"\#1 and #2\n\#3";
+///
+/// assert_eq!(ESCAPER.escape(unescaped), escaped);
+/// assert_eq!(ESCAPER.unescape(escaped).expect("is properly escaped"), unescaped);
+/// assert_eq!(ESCAPER.unescape(r"\#1 and \#2").expect("is properly escaped"), "#1 and #2");
+/// ```
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct PositionalRule {
+    #[allow(missing_docs)]
+    pub unescaped: char,
+    #[allow(missing_docs)]
+    pub escaped: char,
+    #[allow(missing_docs)]
+    pub position: Position,
+}
@@ -0,0 +1,1978 @@
+//! Ready-made [`Escaper`]s for common formats.
+//!
+//! Every preset is a `const`, so it can be used as is or extended using the `extend` option of
+//! the [`escaper!`] macro.
+//!
+//! ```
+//! use char_escape::presets;
+//!
+//! assert_eq!(presets::RUST.escape("tab\tquote\""), r#"tab\tquote\""#);
+//! ```
+
+use {
+    crate::{
+        escaper, Chain, CharClass, ClassRule, Escaper, EscaperBuf, Fallback, Notation, Position,
+        PositionalRule, Quoter, RejectedChar, Replacement, Replacer, Rule, UnescapeError,
+    },
+    core::{fmt::Write as _, iter},
+    std::borrow::Cow,
+};
+
+/// Escapes strings like Rust string literals.
+///
+/// Escaping produces the same result as [`str::escape_default()`]: `'\t'`, `'\r'`, `'\n'`,
+/// `'\''`, `'"'` and `'\\'` are escaped using a backslash, all other [`char`]s outside of the
+/// printable ASCII range are escaped as `\u{…}`.
+///
+/// Unescaping additionally accepts the other escape sequences of Rust string literals, i.e. `\0`
+/// and `\x00` to `\x7f`. Line continuations are not supported.
+///
+/// ```
+/// use char_escape::presets::RUST;
+///
+/// let s = "\"Grüße\"\n\0";
+///
+/// assert_eq!(RUST.escape(s), s.escape_default().to_string());
+/// let unescaped = RUST.unescape(r#"\"Gr\u{fc}\x42e\"\0"#).expect("is properly escaped");
+/// assert_eq!(unescaped, "\"GrüBe\"\0");
+/// ```
+pub const RUST: Escaper<'static> = escaper! {
+    '\t' => 't',
+    '\r' => 'r',
+    '\n' => 'n',
+    '\'' => '\'',
+    '"' => '"',
+}
+.with_fallback(Fallback::Unicode)
+.with_aliases(&[Rule {
+    unescaped: '\0',
+    escaped: '0',
+}])
+.with_class_rules(&[DECODE_ONLY_ASCII_HEX]);
+
+/// Escapes strings like JSON string literals, as specified by RFC 8259.
+///
+/// `'"'` and `'\\'` are escaped using a backslash, and so are the control characters that have
+/// a short escape sequence. All other control characters from `'\0'` to `'\x1f'` are escaped as
+/// `\uXXXX`. Non-ASCII [`char`]s are kept as they are, see [`JSON_ASCII`] for an alternative.
+///
+/// Unescaping accepts every escape sequence allowed by JSON, including `\/` and surrogate pairs.
+///
+/// ```
+/// use char_escape::presets::JSON;
+///
+/// assert_eq!(JSON.escape("\"Grüße\"\n\u{1}"), r#"\"Grüße\"\n\u0001"#);
+/// assert_eq!(JSON.unescape(r"\/\ud83d\ude00\u00e4").expect("is properly escaped"), "/😀ä");
+/// assert!(JSON.unescape(r"\x41").is_err());
+/// ```
+pub const JSON: Escaper<'static> = escaper! {
+    '"' => '"',
+    '\u{8}' => 'b',
+    '\u{c}' => 'f',
+    '\n' => 'n',
+    '\r' => 'r',
+    '\t' => 't',
+    '\0'..='\x1f' => utf16,
+}
+.with_aliases(&[Rule {
+    unescaped: '/',
+    escaped: '/',
+}]);
+
+/// Like [`JSON`], but also escapes all non-ASCII [`char`]s, so the result is pure ASCII.
+///
+/// ```
+/// use char_escape::presets::JSON_ASCII;
+///
+/// assert_eq!(JSON_ASCII.escape("ä😀"), r"\u00e4\ud83d\ude00");
+/// ```
+pub const JSON_ASCII: Escaper<'static> = escaper! {
+    extend = JSON,
+    rules = ['\u{80}'..='\u{10ffff}' => utf16],
+};
+
+/// Escapes strings like C string literals.
+///
+/// `'"'` and `'\\'` are escaped using a backslash, and so are the control characters that have
+/// a short escape sequence, e.g. `\n`. All other ASCII control characters are escaped using three
+/// octal digits, e.g. `\033`. Unlike `\0` or `\x1b`, this can't run into the digits that follow.
+/// Non-ASCII [`char`]s are kept as they are, so the result is meant for UTF-8 source files.
+///
+/// Unescaping additionally accepts `\'`, `\?`, octal escape sequences of one to three digits and
+/// `\x00` to `\x7f`. Octal escape sequences are decoded as code points, not as bytes.
+///
+/// ```
+/// use char_escape::presets::C_STRING;
+///
+/// assert_eq!(C_STRING.escape("\"a\"\t\0\x1b[0m"), r#"\"a\"\t\000\033[0m"#);
+/// let unescaped = C_STRING.unescape(r"\0\x41\101\?\'").expect("is properly escaped");
+/// assert_eq!(unescaped, "\0AA?'");
+/// ```
+pub const C_STRING: Escaper<'static> = escaper! {
+    '"' => '"',
+    '\x07' => 'a',
+    '\x08' => 'b',
+    '\x0c' => 'f',
+    '\n' => 'n',
+    '\r' => 'r',
+    '\t' => 't',
+    '\x0b' => 'v',
+}
+.with_class_rules(&[
+    DECODE_ONLY_ASCII_HEX,
+    ClassRule {
+        class: CharClass::AsciiControl,
+        notation: C_OCTAL,
+    },
+])
+.with_aliases(&[Rule::same('\''), Rule::same('?')]);
+
+/// Escapes strings like Python `str` literals.
+///
+/// `'\''`, `'"'`, `'\\'`, `'\t'`, `'\n'` and `'\r'` are escaped using a backslash. Other
+/// control characters, `'\u{a0}'` and `'\u{ad}'` are escaped as `\xHH`, and `'\u{2028}'` and
+/// `'\u{2029}'` as `\uXXXX`, like `repr()` does. All other [`char`]s are kept as they are, so the
+/// result is meant for UTF-8 source files.
+///
+/// Unescaping additionally accepts `\a`, `\b`, `\f`, `\v`, octal escape sequences of one to
+/// three digits, `\xHH`, `\uXXXX` and `\UXXXXXXXX`. With the `unicode-names` feature, it also
+/// accepts `\N{NAME}` using Unicode names. Line continuations are not supported.
+///
+/// ```
+/// use char_escape::presets::PYTHON;
+///
+/// assert_eq!(PYTHON.escape("'a'\t\0\u{a0}\u{2028}é"), r"\'a\'\t\x00\xa0\u2028é");
+/// let unescaped = PYTHON.unescape(r"\101\x42\u00e9\U0001f600\v").expect("is properly escaped");
+/// assert_eq!(unescaped, "ABé😀\x0b");
+/// ```
+pub const PYTHON: Escaper<'static> = escaper! {
+    '\'' => '\'',
+    '"' => '"',
+    '\t' => 't',
+    '\n' => 'n',
+    '\r' => 'r',
+}
+.with_class_rules(PYTHON_CLASS_RULES)
+.with_aliases(&[
+    Rule {
+        unescaped: '\x07',
+        escaped: 'a',
+    },
+    Rule {
+        unescaped: '\x08',
+        escaped: 'b',
+    },
+    Rule {
+        unescaped: '\x0c',
+        escaped: 'f',
+    },
+    Rule {
+        unescaped: '\x0b',
+        escaped: 'v',
+    },
+]);
+
+/// Escapes strings like JavaScript string literals, in single or double quotes.
+///
+/// `'\''`, `'"'` and `'\\'` are escaped using a backslash, and so are the control characters
+/// that have a short escape sequence, e.g. `\n`. All other ASCII control characters and `'<'` are
+/// escaped as `\xHH`, so the result can't end an inline `<script>` element. The line terminators
+/// `'\u{2028}'` and `'\u{2029}'` are escaped as `\uXXXX`.
+///
+/// Unescaping additionally accepts `\0`, `\xHH`, `\uXXXX` including surrogate pairs and `\u{…}`.
+///
+/// ```
+/// use char_escape::presets::JS_STRING;
+///
+/// let escaped = JS_STRING.escape("'</script>'\n\u{2028}");
+/// assert_eq!(escaped, r"\'\x3c/script>\'\n\u2028");
+/// let unescaped = JS_STRING.unescape(r"\x41é😀\u{1f600}").expect("is properly escaped");
+/// assert_eq!(unescaped, "Aé😀😀");
+/// ```
+pub const JS_STRING: Escaper<'static> = escaper! {
+    '\'' => '\'',
+    '"' => '"',
+    '\x08' => 'b',
+    '\x0c' => 'f',
+    '\n' => 'n',
+    '\r' => 'r',
+    '\t' => 't',
+    '\x0b' => 'v',
+}
+.with_class_rules(JS_CLASS_RULES)
+.with_aliases(&[Rule {
+    unescaped: '\0',
+    escaped: '0',
+}]);
+
+/// Escapes strings like JavaScript template literals.
+///
+/// Like [`JS_STRING`], but `` '`' `` and `'$'` are escaped using a backslash instead of the
+/// quotes, so the result can neither end the template literal nor start a `${…}` substitution.
+/// Line terminators are escaped as well, since template literals would turn `"\r\n"` into
+/// `"\n"`.
+///
+/// ```
+/// use char_escape::presets::JS_TEMPLATE;
+///
+/// assert_eq!(JS_TEMPLATE.escape("`${x}`\r\n'"), r"\`\${x}\`\r\n'");
+/// assert_eq!(JS_TEMPLATE.unescape(r#"\$\'\"\x3c"#).expect("is properly escaped"), "$'\"<");
+/// ```
+pub const JS_TEMPLATE: Escaper<'static> = escaper! {
+    self = ['`', '$'],
+    rules = [
+        '\x08' => 'b',
+        '\x0c' => 'f',
+        '\n' => 'n',
+        '\r' => 'r',
+        '\t' => 't',
+        '\x0b' => 'v',
+    ],
+}
+.with_class_rules(JS_CLASS_RULES)
+.with_aliases(&[
+    Rule {
+        unescaped: '\0',
+        escaped: '0',
+    },
+    Rule::same('\''),
+    Rule::same('"'),
+]);
+
+/// Escapes strings like the protobuf text format, e.g. in `string` fields.
+///
+/// `'"'`, `'\''`, `'\\'`, `'\n'`, `'\r'` and `'\t'` are escaped using a backslash, all other
+/// ASCII control characters using three octal digits, e.g. `\033`. Non-ASCII [`char`]s are kept
+/// as they are, like protobuf does for `string` fields.
+///
+/// Unescaping additionally accepts `\a`, `\b`, `\f`, `\v`, `\?`, octal escape sequences of one
+/// to three digits, `\xHH`, `\uXXXX` and `\UXXXXXXXX`. Octal and hexadecimal escape sequences
+/// are decoded as code points, not as bytes, so `bytes` fields containing invalid UTF-8 are not
+/// supported.
+///
+/// ```
+/// use char_escape::presets::PROTOBUF_TEXT;
+///
+/// assert_eq!(PROTOBUF_TEXT.escape("\"ä\"\n\0\x7f"), r#"\"ä\"\n\000\177"#);
+/// let unescaped = PROTOBUF_TEXT.unescape(r"\101\x42ä\?").expect("is properly escaped");
+/// assert_eq!(unescaped, "ABä?");
+/// ```
+pub const PROTOBUF_TEXT: Escaper<'static> = escaper! {
+    '"' => '"',
+    '\'' => '\'',
+    '\n' => 'n',
+    '\r' => 'r',
+    '\t' => 't',
+}
+.with_class_rules(&[
+    ClassRule {
+        class: CharClass::AsciiControl,
+        notation: C_OCTAL,
+    },
+    decode_only(YAML_HEX),
+    decode_only(UNICODE_4),
+    decode_only(UNICODE_8),
+])
+.with_aliases(&[
+    Rule {
+        unescaped: '\x07',
+        escaped: 'a',
+    },
+    Rule {
+        unescaped: '\x08',
+        escaped: 'b',
+    },
+    Rule {
+        unescaped: '\x0c',
+        escaped: 'f',
+    },
+    Rule {
+        unescaped: '\x0b',
+        escaped: 'v',
+    },
+    Rule::same('?'),
+]);
+
+/// Escapes values in Java `.properties` files, like `java.util.Properties::store()`.
+///
+/// `'\t'`, `'\n'`, `'\r'` and `'\x0c'` are escaped as `\t`, `\n`, `\r` and `\f`, and `':'`,
+/// `'='`, `'#'` and `'!'` using a backslash. A `' '` at the start of the value is escaped as
+/// `\ `, so it isn't stripped when loading. All other [`char`]s outside of the printable ASCII
+/// range are escaped as `\uXXXX`, using surrogate pairs if necessary. Unlike `store()`, the hex
+/// digits are lowercase, which `load()` accepts as well.
+///
+/// Escape keys using [`JAVA_PROPERTIES_KEY`]. Line continuations are not part of escaping, use
+/// [`join_properties_lines()`] before unescaping.
+///
+/// ```
+/// use char_escape::presets::JAVA_PROPERTIES;
+///
+/// let escaped = JAVA_PROPERTIES.escape(" a=b: Grüße\n");
+/// assert_eq!(escaped, r"\ a\=b\: Gr\u00fc\u00dfe\n");
+/// assert_eq!(JAVA_PROPERTIES.unescape(&escaped).expect("is properly escaped"), " a=b: Grüße\n");
+/// ```
+pub const JAVA_PROPERTIES: Escaper<'static> = escaper! {
+    self = [':', '=', '#', '!'],
+    rules = [
+        '\t' => 't',
+        '\n' => 'n',
+        '\r' => 'r',
+        '\x0c' => 'f',
+        '\0'..='\x1f' => utf16,
+        '\x7f'..='\u{10ffff}' => utf16,
+    ],
+}
+.with_positional_rules(&[PositionalRule {
+    unescaped: ' ',
+    escaped: ' ',
+    position: Position::StartOfString,
+}]);
+
+/// Escapes keys in Java `.properties` files.
+///
+/// Like [`JAVA_PROPERTIES`], but every `' '` is escaped, since an unescaped one would end the key.
+///
+/// ```
+/// use char_escape::presets::JAVA_PROPERTIES_KEY;
+///
+/// assert_eq!(JAVA_PROPERTIES_KEY.escape("my key"), r"my\ key");
+/// ```
+pub const JAVA_PROPERTIES_KEY: Escaper<'static> = escaper! {
+    extend = JAVA_PROPERTIES,
+    self = [' '],
+};
+
+/// Joins the lines of a Java `.properties` file that are continued using a backslash.
+///
+/// A line ending in an odd number of backslashes is continued on the next line. The last
+/// backslash, the line terminator and the whitespace at the start of the next line are removed.
+/// Apply this before splitting the file into keys and values and unescaping them.
+///
+/// ```
+/// use char_escape::presets::{join_properties_lines, JAVA_PROPERTIES};
+///
+/// let file = "fruits = apple, \\\n         banana\npath = C:\\\\\n";
+/// let joined = join_properties_lines(file);
+/// assert_eq!(joined, "fruits = apple, banana\npath = C:\\\\\n");
+///
+/// let (_, value) = joined.lines().next().and_then(|line| line.split_once(" = ")).unwrap();
+/// assert_eq!(JAVA_PROPERTIES.unescape(value).expect("is properly escaped"), "apple, banana");
+/// ```
+pub fn join_properties_lines(s: &str) -> Cow<'_, str> {
+    let mut ret = String::new();
+    let mut start = 0;
+    let mut backslashes = 0;
+    let mut chars = s.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '\\' => backslashes += 1,
+            '\r' | '\n' if backslashes % 2 == 1 => {
+                ret.push_str(&s[start..i - 1]);
+                if c == '\r' {
+                    chars.next_if(|&(_, c)| c == '\n');
+                }
+                while chars
+                    .next_if(|&(_, c)| matches!(c, ' ' | '\t' | '\x0c'))
+                    .is_some()
+                {}
+                start = chars.peek().map_or(s.len(), |&(i, _)| i);
+                backslashes = 0;
+            }
+            _ => backslashes = 0,
+        }
+    }
+
+    if start == 0 {
+        Cow::Borrowed(s)
+    } else {
+        ret.push_str(&s[start..]);
+        Cow::Owned(ret)
+    }
+}
+
+/// Escapes the content of YAML double-quoted scalars, as specified by YAML 1.2.
+///
+/// `'"'` and `'\\'` are escaped using a backslash, and so are the [`char`]s that have a short
+/// escape sequence, e.g. `\n`, `\e`, `\N` for `'\u{85}'` or `\_` for `'\u{a0}'`. All other
+/// [`char`]s that YAML doesn't allow in a stream, e.g. most control characters, are escaped as
+/// `\xHH` or `\uXXXX`. So is the byte order mark `'\u{feff}'`.
+///
+/// Unescaping additionally accepts `\/`, `\ `, a backslash followed by a tab, and `\xHH`,
+/// `\uXXXX` and `\UXXXXXXXX` for every [`char`]. Line folding is not supported.
+///
+/// ```
+/// use char_escape::presets::YAML;
+///
+/// assert_eq!(YAML.escape("\"a\"\t\x1b\u{85}\u{7f}\u{ffff}"), r#"\"a\"\t\e\N\x7f\uffff"#);
+/// let unescaped = YAML.unescape(r"\x41\u00e4\U0001F600\/\ ").expect("is properly escaped");
+/// assert_eq!(unescaped, "Aä😀/ ");
+/// ```
+pub const YAML: Escaper<'static> = escaper! {
+    '"' => '"',
+    '\0' => '0',
+    '\x07' => 'a',
+    '\x08' => 'b',
+    '\t' => 't',
+    '\n' => 'n',
+    '\x0b' => 'v',
+    '\x0c' => 'f',
+    '\r' => 'r',
+    '\x1b' => 'e',
+    '\u{85}' => 'N',
+    '\u{a0}' => '_',
+    '\u{2028}' => 'L',
+    '\u{2029}' => 'P',
+}
+.with_aliases(&[Rule::same('/'), Rule::same(' '), Rule::same('\t')])
+.with_class_rules(&[
+    ClassRule::range('\0'..='\x1f', YAML_HEX),
+    ClassRule::range('\x7f'..='\u{9f}', YAML_HEX),
+    ClassRule::range('\u{feff}'..='\u{feff}', UNICODE_4),
+    ClassRule::range('\u{fffe}'..='\u{ffff}', UNICODE_4),
+    decode_only(UNICODE_8),
+]);
+
+/// Escapes the content of TOML basic strings, as specified by TOML 1.0.
+///
+/// `'"'` and `'\\'` are escaped using a backslash, and so are the control characters that have
+/// a short escape sequence. All other control characters TOML forbids in basic strings are escaped
+/// as `\uXXXX`.
+///
+/// Unescaping additionally accepts `\uXXXX` and `\UXXXXXXXX` for every Unicode scalar value. It
+/// rejects everything else TOML forbids, e.g. `\e`, `\xHH`, `\'` or surrogates.
+///
+/// ```
+/// use char_escape::presets::TOML;
+///
+/// assert_eq!(TOML.escape("\"a\"\t\0\u{7f}"), r#"\"a\"\t\u0000\u007f"#);
+/// let unescaped = TOML.unescape(r"\u00e4\U0001F600").expect("is properly escaped");
+/// assert_eq!(unescaped, "ä😀");
+/// assert!(TOML.unescape(r"\x41").is_err());
+/// assert!(TOML.unescape(r"\ud800").is_err());
+/// ```
+pub const TOML: Escaper<'static> = escaper! {
+    '"' => '"',
+    '\x08' => 'b',
+    '\t' => 't',
+    '\n' => 'n',
+    '\x0c' => 'f',
+    '\r' => 'r',
+}
+.with_class_rules(&[
+    ClassRule {
+        class: CharClass::AsciiControl,
+        notation: UNICODE_4,
+    },
+    decode_only(UNICODE_8),
+]);
+
+/// Escapes untrusted text for log files, so it can't forge log entries.
+///
+/// `'\n'`, `'\r'` and `'\t'` are escaped as `\n`, `\r` and `\t`. All other control characters,
+/// the Unicode line and paragraph separators `'\u{2028}'` and `'\u{2029}'`, and the bidirectional
+/// formatting characters `'\u{202a}'` to `'\u{202e}'` and `'\u{2066}'` to `'\u{2069}'` are
+/// escaped as `\u{…}`.
+///
+/// The result never contains a control character or line separator, so it always fits on a single
+/// line, and a backslash in the result always starts an escape sequence, so escaped text can't
+/// imitate an escape sequence either.
+///
+/// ```
+/// use char_escape::presets::LOG;
+///
+/// let input = "admin\r\n[INFO] login ok\u{1b}[2J";
+/// let escaped = LOG.escape(input);
+///
+/// assert_eq!(escaped, r"admin\r\n[INFO] login ok\u{1b}[2J");
+/// assert!(!escaped.contains(char::is_control));
+/// assert_eq!(LOG.unescape(&escaped).expect("is properly escaped"), input);
+/// ```
+pub const LOG: Escaper<'static> = escaper! {
+    '\n' => 'n',
+    '\r' => 'r',
+    '\t' => 't',
+}
+.with_class_rules(&[
+    ClassRule {
+        class: CharClass::Control,
+        notation: Notation::Unicode,
+    },
+    ClassRule::range('\u{2028}'..='\u{2029}', Notation::Unicode),
+    ClassRule::range('\u{202a}'..='\u{202e}', Notation::Unicode),
+    ClassRule::range('\u{2066}'..='\u{2069}', Notation::Unicode),
+]);
+
+/// Escapes labels for quoted strings in Graphviz DOT files.
+///
+/// `'"'` and `'\\'` are escaped using a backslash, and `'\n'` becomes `\n`, a centered line
+/// break. Unescaping also accepts the left and right justified line breaks `\l` and `\r`, which
+/// become `'\n'` as well. Other escape sequences of labels, e.g. `\N` for the node name, are
+/// rejected.
+///
+/// ```
+/// use char_escape::presets::DOT;
+///
+/// assert_eq!(DOT.escape("say \"hi\"\nC:\\"), r#"say \"hi\"\nC:\\"#);
+/// assert_eq!(DOT.unescape(r"left\lright\r").expect("is properly escaped"), "left\nright\n");
+/// ```
+pub const DOT: Escaper<'static> = escaper! {
+    '"' => '"',
+    '\n' => 'n',
+}
+.with_aliases(&[
+    Rule {
+        unescaped: '\n',
+        escaped: 'l',
+    },
+    Rule {
+        unescaped: '\n',
+        escaped: 'r',
+    },
+]);
+
+/// Like [`DOT`], but `'\n'` becomes `\l`, so the lines of the label are left justified.
+///
+/// ```
+/// use char_escape::presets::DOT_LEFT;
+///
+/// assert_eq!(DOT_LEFT.escape("a\nb\n"), r"a\lb\l");
+/// ```
+pub const DOT_LEFT: Escaper<'static> = escaper! {
+    '"' => '"',
+    '\n' => 'l',
+}
+.with_aliases(&[
+    Rule {
+        unescaped: '\n',
+        escaped: 'n',
+    },
+    Rule {
+        unescaped: '\n',
+        escaped: 'r',
+    },
+]);
+
+/// Like [`DOT`], but `'\n'` becomes `\r`, so the lines of the label are right justified.
+///
+/// ```
+/// use char_escape::presets::DOT_RIGHT;
+///
+/// assert_eq!(DOT_RIGHT.escape("a\nb\n"), r"a\rb\r");
+/// ```
+pub const DOT_RIGHT: Escaper<'static> = escaper! {
+    '"' => '"',
+    '\n' => 'r',
+}
+.with_aliases(&[
+    Rule {
+        unescaped: '\n',
+        escaped: 'n',
+    },
+    Rule {
+        unescaped: '\n',
+        escaped: 'l',
+    },
+]);
+
+/// Escapes untrusted text before it is printed to a terminal.
+///
+/// `'\x1b'` is escaped as `\e` and `'\r'` as `\r`. All other control characters except `'\n'`
+/// and `'\t'`, including the single-character CSI `'\u{9b}'`, are escaped as `\u{…}`. This keeps
+/// the text from moving the cursor, changing colors or setting the window title. Use
+/// [`strip_terminal_controls()`] to remove escape sequences instead.
+///
+/// ```
+/// use char_escape::presets::TERMINAL;
+///
+/// let input = "\x1b]0;pwned\x07\x1b[2Jdone\r\n";
+/// assert_eq!(TERMINAL.escape(input), "\\e]0;pwned\\u{7}\\e[2Jdone\\r\n");
+/// ```
+pub const TERMINAL: Escaper<'static> = escaper! {
+    '\x1b' => 'e',
+    '\r' => 'r',
+}
+.with_class_rules(&[ClassRule {
+    class: CharClass::Predicate(is_terminal_control),
+    notation: Notation::Unicode,
+}]);
+
+/// Removes terminal escape sequences and control characters except `'\n'` and `'\t'` from `s`.
+///
+/// Removes CSI sequences like `"\x1b[1;31m"`, OSC sequences like `"\x1b]0;title\x07"`, the
+/// string sequences DCS, SOS, PM and APC, and two-character escape sequences, in both their 7-bit
+/// and 8-bit forms. An escape sequence that isn't terminated is removed up to the end of `s`.
+///
+/// ```
+/// use char_escape::presets::strip_terminal_controls;
+///
+/// let input = "\x1b[1;31merror\x1b[0m: \x1b]8;;https://example.com\x1b\\link\x1b]8;;\x07\r\n";
+/// assert_eq!(strip_terminal_controls(input), "error: link\n");
+/// assert_eq!(strip_terminal_controls("plain\ttext"), "plain\ttext");
+/// ```
+pub fn strip_terminal_controls(s: &str) -> Cow<'_, str> {
+    if !s.contains(is_terminal_control) {
+        return Cow::Borrowed(s);
+    }
+
+    let mut ret = String::with_capacity(s.len());
+    let mut chars = s.chars().peekable();
+    while let Some(c) = chars.next() {
+        let introducer = match c {
+            '\x1b' => match chars.next_if(|c| (' '..='~').contains(c)) {
+                Some('[') => '\u{9b}',
+                Some(']') => '\u{9d}',
+                Some('P') => '\u{90}',
+                Some('X') => '\u{98}',
+                Some('^') => '\u{9e}',
+                Some('_') => '\u{9f}',
+                _ => continue,
+            },
+            c if is_terminal_control(c) => c,
+            c => {
+                ret.push(c);
+                continue;
+            }
+        };
+
+        match introducer {
+            // CSI: parameter and intermediate bytes, followed by a final byte
+            '\u{9b}' => while chars.next_if(|c| (' '..='?').contains(c)).is_some() {},
+            // OSC, DCS, SOS, PM and APC: a string terminated by ST, OSC also by BEL
+            '\u{9d}' | '\u{90}' | '\u{98}' | '\u{9e}' | '\u{9f}' => {
+                while let Some(c) = chars.next() {
+                    let is_st = c == '\u{9c}' || (c == '\x1b' && chars.next_if_eq(&'\\').is_some());
+                    if is_st || (c == '\x07' && introducer == '\u{9d}') {
+                        break;
+                    }
+                }
+                continue;
+            }
+            _ => continue,
+        }
+        chars.next_if(|c| ('@'..='~').contains(c));
+    }
+
+    Cow::Owned(ret)
+}
+
+/// Quotes CSV fields as specified by RFC 4180.
+///
+/// Fields containing `'"'`, `','`, `'\n'` or `'\r'` are enclosed in double quotes and the double
+/// quotes inside are doubled. Other fields are left as they are. This handles single fields,
+/// splitting records into fields is up to the caller.
+///
+/// ```
+/// use char_escape::presets::CSV;
+///
+/// let fields = ["id", "name, first", "say \"hi\""];
+/// let record: Vec<_> = fields.iter().map(|field| CSV.escape(field)).collect();
+///
+/// assert_eq!(record.join(","), r#"id,"name, first","say ""hi""""#);
+/// assert_eq!(CSV.unescape(r#""say ""hi""""#).expect("is properly quoted"), "say \"hi\"");
+/// ```
+pub const CSV: Quoter<'static> = Quoter::new('"').with_special(&[',', '\n', '\r']);
+
+/// Escapes the special characters of LaTeX text.
+///
+/// `'#'`, `'$'`, `'%'`, `'&'`, `'_'`, `'{'` and `'}'` are preceded by a backslash. `'~'`, `'^'`
+/// and `'\\'` have no such escape sequence, so they are replaced by `\textasciitilde{}`,
+/// `\textasciicircum{}` and `\textbackslash{}`. Unescaping only accepts these escape sequences,
+/// other commands are rejected.
+///
+/// ```
+/// use char_escape::presets::LATEX;
+///
+/// let escaped = LATEX.escape(r"50% of $x_1 & ~\n");
+/// assert_eq!(escaped, r"50\% of \$x\_1 \& \textasciitilde{}\textbackslash{}n");
+/// assert_eq!(LATEX.unescape(&escaped).expect("is properly escaped"), r"50% of $x_1 & ~\n");
+/// assert!(LATEX.unescape(r"\\").is_err());
+/// ```
+pub const LATEX: Escaper<'static> = escaper! {
+    no_implicit_escape,
+    self = ['#', '$', '%', '&', '_', '{', '}'],
+    rules = [
+        '~' => "textasciitilde{}",
+        '^' => "textasciicircum{}",
+        '\\' => "textbackslash{}",
+    ],
+};
+
+/// Escapes text for Markdown, so that it is rendered literally.
+///
+/// The ASCII punctuation characters that can start or end Markdown syntax, e.g. `'*'`, `'_'`,
+/// `'['` or `'#'`, are preceded by a backslash. This includes `'|'` and `'~'` for tables and
+/// strikethrough of GitHub Flavored Markdown, and `'&'` for entity references. Unescaping accepts
+/// a backslash before any ASCII punctuation character, as the Markdown specification allows.
+///
+/// Only inline text is handled. Leading spaces and line breaks still affect the rendering.
+///
+/// ```
+/// use char_escape::presets::MARKDOWN;
+///
+/// assert_eq!(MARKDOWN.escape("# [*x*](y) 1. a_b"), r"\# \[\*x\*\]\(y\) 1\. a\_b");
+/// assert_eq!(MARKDOWN.unescape(r"\@\: \*").expect("is properly escaped"), "@: *");
+/// ```
+pub const MARKDOWN: Escaper<'static> = escaper! {
+    self = [
+        '!', '#', '&', '(', ')', '*', '+', '-', '.', '<', '>', '[', ']', '_', '`', '{', '|', '}',
+        '~',
+    ],
+}
+.with_aliases(&[
+    Rule::same('"'),
+    Rule::same('$'),
+    Rule::same('%'),
+    Rule::same('\''),
+    Rule::same(','),
+    Rule::same('/'),
+    Rule::same(':'),
+    Rule::same(';'),
+    Rule::same('='),
+    Rule::same('?'),
+    Rule::same('@'),
+    Rule::same('^'),
+]);
+
+/// Quotes SQL string literals.
+///
+/// The string is enclosed in single quotes and the single quotes inside are doubled, as the SQL
+/// standard specifies. Backslashes are kept as they are, so databases that treat them as escape
+/// characters inside string literals, e.g. `MySQL` by default, need them to be escaped first.
+/// Prefer bound parameters where possible.
+///
+/// ```
+/// use char_escape::presets::SQL_STRING;
+///
+/// let query = format!("SELECT * FROM users WHERE name = {}", SQL_STRING.escape("O'Brien"));
+/// assert_eq!(query, "SELECT * FROM users WHERE name = 'O''Brien'");
+/// assert_eq!(SQL_STRING.unescape("'O''Brien'").expect("is properly quoted"), "O'Brien");
+/// ```
+pub const SQL_STRING: Quoter<'static> = Quoter::new('\'');
+
+/// Quotes SQL identifiers, e.g. table or column names.
+///
+/// The identifier is enclosed in double quotes and the double quotes inside are doubled, as the
+/// SQL standard specifies. Quoted identifiers are case-sensitive in most databases, so every
+/// identifier is quoted to keep its case consistent.
+///
+/// ```
+/// use char_escape::presets::SQL_IDENTIFIER;
+///
+/// assert_eq!(SQL_IDENTIFIER.escape("my \"table\""), r#""my ""table""""#);
+/// assert_eq!(SQL_IDENTIFIER.escape("users"), r#""users""#);
+/// ```
+pub const SQL_IDENTIFIER: Quoter<'static> = Quoter::new('"');
+
+/// Escapes the wildcards of SQL `LIKE` patterns using a backslash.
+///
+/// `'%'`, `'_'` and `'\\'` are escaped, so the pattern matches the string literally. A backslash
+/// is the default escape character of many databases. For other dialects, use [`sql_like()`] and
+/// `LIKE ... ESCAPE ...`.
+///
+/// The pattern still needs to be passed as a bound parameter or quoted using [`SQL_STRING`].
+///
+/// ```
+/// use char_escape::presets::SQL_LIKE;
+///
+/// let pattern = format!("{}%", SQL_LIKE.escape("100%_sure"));
+/// assert_eq!(pattern, r"100\%\_sure%");
+/// ```
+pub const SQL_LIKE: Escaper<'static> = escaper! {
+    self = ['%', '_'],
+};
+
+/// Escapes the wildcards of SQL `LIKE` patterns using the given escape character.
+///
+/// The escape character must be declared using `ESCAPE`, e.g. `LIKE ? ESCAPE '!'`. SQL Server
+/// also treats `'['` as a wildcard, so add a [`Rule`] for it there.
+///
+/// ```
+/// use char_escape::presets::sql_like;
+///
+/// let escaper = sql_like('!');
+/// assert_eq!(escaper.as_escaper().escape("50% off!"), "50!% off!!");
+///
+/// let sql_server = sql_like('!').escape_same(['[']);
+/// assert_eq!(sql_server.as_escaper().escape("[a]_"), "![a]!_");
+/// ```
+pub fn sql_like(escape_char: char) -> EscaperBuf {
+    EscaperBuf::from(Escaper::new_unchecked(escape_char, &[])).escape_same([escape_char, '%', '_'])
+}
+
+/// Escapes text content of HTML elements.
+///
+/// `'&'`, `'<'` and `'>'` are replaced by their entities. Unescaping additionally decodes
+/// `&quot;`, `&apos;`, `&#39;`, `&nbsp;` and all numeric character references. Other named
+/// entities are kept as they are.
+///
+/// ```
+/// use char_escape::presets::HTML_TEXT;
+///
+/// let escaped = "&lt;b&gt;\"Tom\" &amp; Jerry&lt;/b&gt;";
+/// assert_eq!(HTML_TEXT.escape("<b>\"Tom\" & Jerry</b>"), escaped);
+/// assert_eq!(HTML_TEXT.unescape("&quot;caf&#233;&quot; &#x2603; &copy;"), "\"café\" ☃ &copy;");
+/// ```
+pub const HTML_TEXT: Replacer<'static> = Replacer::new(&[AMP, LT, GT])
+    .with_aliases(HTML_ALIASES)
+    .with_numeric_references(true);
+
+/// Escapes attribute values in HTML, both single and double quoted.
+///
+/// Like [`HTML_TEXT`], but `'"'` and `'\''` are replaced by `&quot;` and `&#39;` as well.
+///
+/// ```
+/// use char_escape::presets::HTML_ATTR;
+///
+/// assert_eq!(HTML_ATTR.escape(r#"say "it's""#), "say &quot;it&#39;s&quot;");
+/// assert_eq!(HTML_ATTR.unescape("it&apos;s"), "it's");
+/// ```
+pub const HTML_ATTR: Replacer<'static> = Replacer::new(&[AMP, LT, GT, QUOT, APOS_NUMERIC])
+    .with_aliases(HTML_ALIASES)
+    .with_numeric_references(true);
+
+const AMP: Replacement<'static> = Replacement {
+    unescaped: "&",
+    escaped: "&amp;",
+};
+const LT: Replacement<'static> = Replacement {
+    unescaped: "<",
+    escaped: "&lt;",
+};
+const GT: Replacement<'static> = Replacement {
+    unescaped: ">",
+    escaped: "&gt;",
+};
+const QUOT: Replacement<'static> = Replacement {
+    unescaped: "\"",
+    escaped: "&quot;",
+};
+const APOS: Replacement<'static> = Replacement {
+    unescaped: "'",
+    escaped: "&apos;",
+};
+const APOS_NUMERIC: Replacement<'static> = Replacement {
+    unescaped: "'",
+    escaped: "&#39;",
+};
+
+/// The named entities that the HTML presets decode, but don't produce.
+const HTML_ALIASES: &[Replacement<'static>] = &[
+    QUOT,
+    APOS,
+    Replacement {
+        unescaped: "\u{a0}",
+        escaped: "&nbsp;",
+    },
+];
+
+/// Escapes text content of XML elements.
+///
+/// `'&'`, `'<'` and `'>'` are replaced by their entities and `'\r'` by `&#13;`, so it survives
+/// line ending normalization. Unescaping additionally decodes `&quot;`, `&apos;` and all numeric
+/// character references.
+///
+/// XML 1.0 doesn't allow some [`char`]s at all, not even as character references. Use
+/// [`check_xml_chars()`] to reject them before escaping.
+///
+/// ```
+/// use char_escape::presets::XML_TEXT;
+///
+/// assert_eq!(XML_TEXT.escape("a < b && c\r\n"), "a &lt; b &amp;&amp; c&#13;\n");
+/// assert_eq!(XML_TEXT.unescape("&apos;&#x41;&apos;"), "'A'");
+/// ```
+pub const XML_TEXT: Replacer<'static> = Replacer::new(&[AMP, LT, GT, CR])
+    .with_aliases(&[QUOT, APOS])
+    .with_numeric_references(true);
+
+/// Escapes attribute values in XML, both single and double quoted.
+///
+/// Like [`XML_TEXT`], but `'"'` and `'\''` are replaced by their entities, and `'\t'` and `'\n'`
+/// by character references, so they survive attribute value normalization.
+///
+/// ```
+/// use char_escape::presets::XML_ATTR;
+///
+/// assert_eq!(XML_ATTR.escape("\"a\"\t'b'\n"), "&quot;a&quot;&#9;&apos;b&apos;&#10;");
+/// ```
+pub const XML_ATTR: Replacer<'static> = Replacer::new(&[
+    AMP,
+    LT,
+    GT,
+    QUOT,
+    APOS,
+    CR,
+    Replacement {
+        unescaped: "\t",
+        escaped: "&#9;",
+    },
+    Replacement {
+        unescaped: "\n",
+        escaped: "&#10;",
+    },
+])
+.with_numeric_references(true);
+
+const CR: Replacement<'static> = Replacement {
+    unescaped: "\r",
+    escaped: "&#13;",
+};
+
+/// Returns `true` if `c` may appear in an XML 1.0 document.
+///
+/// ```
+/// use char_escape::presets::is_xml_char;
+///
+/// assert!(is_xml_char('\n'));
+/// assert!(!is_xml_char('\0'));
+/// assert!(!is_xml_char('\u{fffe}'));
+/// ```
+pub const fn is_xml_char(c: char) -> bool {
+    matches!(
+        c,
+        '\t' | '\n' | '\r' | ' '..='\u{d7ff}' | '\u{e000}'..='\u{fffd}' | '\u{10000}'..='\u{10ffff}'
+    )
+}
+
+/// Checks that every [`char`] of `s` may appear in an XML 1.0 document.
+///
+/// # Errors
+///
+/// Returns the first [`char`] that is not allowed.
+///
+/// ```
+/// use char_escape::{presets::check_xml_chars, RejectedChar};
+///
+/// assert_eq!(check_xml_chars("a\tb"), Ok(()));
+/// assert_eq!(check_xml_chars("a\u{1b}b"), Err(RejectedChar { c: '\u{1b}', position: 1 }));
+/// ```
+pub fn check_xml_chars(s: &str) -> Result<(), RejectedChar> {
+    match s.char_indices().find(|&(_, c)| !is_xml_char(c)) {
+        Some((position, c)) => Err(RejectedChar { c, position }),
+        None => Ok(()),
+    }
+}
+
+/// Percent-encodes URL paths, using the path percent-encode set of the WHATWG URL standard.
+///
+/// Control characters, non-ASCII [`char`]s, `' '`, `'"'`, `'#'`, `'%'`, `'<'`, `'>'`, `'?'`,
+/// `` '`' ``, `'^'`, `'{'` and `'}'` are percent-encoded, non-ASCII [`char`]s as their UTF-8
+/// bytes. `'/'` is kept, so whole paths can be escaped. Escape single segments using
+/// [`URL_FORM`] instead.
+///
+/// Unlike in the standard, `'%'` is always encoded, so that unescaping restores the original
+/// string. Percent-encoding can be customized using [`Notation::Percent`] with a different
+/// [`CharClass`].
+///
+/// ```
+/// use char_escape::presets::URL_PATH;
+///
+/// assert_eq!(URL_PATH.escape("/docs/Grüße 100%?"), "/docs/Gr%C3%BC%C3%9Fe%20100%25%3F");
+/// assert_eq!(URL_PATH.unescape("/a%2fb").expect("is properly escaped"), "/a/b");
+/// ```
+pub const URL_PATH: Escaper<'static> =
+    Escaper::new_unchecked('%', &[]).with_class_rules(&[percent(is_path_reserved)]);
+
+/// Percent-encodes URL queries, using the query percent-encode set of the WHATWG URL standard.
+///
+/// Like [`URL_PATH`], but `'?'`, `` '`' ``, `'^'`, `'{'` and `'}'` are kept. So are `'&'` and
+/// `'='`, escape keys and values using [`URL_FORM`] instead.
+///
+/// ```
+/// use char_escape::presets::URL_QUERY;
+///
+/// assert_eq!(URL_QUERY.escape("q=a b&page=#2"), "q=a%20b&page=%232");
+/// ```
+pub const URL_QUERY: Escaper<'static> =
+    Escaper::new_unchecked('%', &[]).with_class_rules(&[percent(is_query_reserved)]);
+
+/// Percent-encodes URL fragments, using the fragment percent-encode set of the WHATWG URL
+/// standard.
+///
+/// Control characters, non-ASCII [`char`]s, `' '`, `'"'`, `'%'`, `'<'`, `'>'` and `` '`' `` are
+/// percent-encoded.
+///
+/// ```
+/// use char_escape::presets::URL_FRAGMENT;
+///
+/// assert_eq!(URL_FRAGMENT.escape("section <2>#a"), "section%20%3C2%3E#a");
+/// ```
+pub const URL_FRAGMENT: Escaper<'static> =
+    Escaper::new_unchecked('%', &[]).with_class_rules(&[percent(is_fragment_reserved)]);
+
+/// Percent-encodes form data, i.e. keys and values of `application/x-www-form-urlencoded`, using
+/// the form percent-encode set of the WHATWG URL standard.
+///
+/// Every [`char`] except ASCII letters and digits, `'*'`, `'-'`, `'.'` and `'_'` is
+/// percent-encoded. This is also a safe choice for single path segments and query parameters.
+///
+/// `' '` is encoded as `%20` rather than `'+'`, which all decoders accept. Unescaping doesn't turn
+/// `'+'` into `' '` though, so form data produced elsewhere must have its `'+'`s replaced by
+/// `' '` before unescaping.
+///
+/// ```
+/// use char_escape::presets::URL_FORM;
+///
+/// assert_eq!(URL_FORM.escape("a+b = c/d"), "a%2Bb%20%3D%20c%2Fd");
+/// assert_eq!(URL_FORM.unescape("caf%C3%A9").expect("is properly escaped"), "café");
+/// assert!(URL_FORM.unescape("100%").is_err());
+/// ```
+pub const URL_FORM: Escaper<'static> =
+    Escaper::new_unchecked('%', &[]).with_class_rules(&[percent(is_form_reserved)]);
+
+/// Escapes text using the `=XX` escape sequences of quoted-printable, as specified by RFC 2045.
+///
+/// Every [`char`] except `' '`, `'\t'` and the printable ASCII characters other than `'='` is
+/// encoded as the `=XX` escape sequences of its UTF-8 bytes. This includes line breaks, so the
+/// result is a single line. For email bodies, use [`encode_quoted_printable()`], which keeps line
+/// breaks and limits the length of the lines.
+///
+/// ```
+/// use char_escape::presets::QUOTED_PRINTABLE;
+///
+/// assert_eq!(QUOTED_PRINTABLE.escape("a=b\r\nÄ"), "a=3Db=0D=0A=C3=84");
+/// assert_eq!(QUOTED_PRINTABLE.unescape("=c3=a4").expect("is properly escaped"), "ä");
+/// ```
+pub const QUOTED_PRINTABLE: Escaper<'static> =
+    Escaper::new_unchecked('=', &[]).with_class_rules(&[percent(is_quoted_printable_reserved)]);
+
+/// The maximum length of a line in quoted-printable, excluding the line break.
+const QUOTED_PRINTABLE_LINE_LEN: usize = 76;
+
+/// Encodes text as quoted-printable, as specified by RFC 2045.
+///
+/// The line breaks of `s`, either `"\r\n"` or `"\n"`, are kept as they are. Everything else is
+/// escaped using [`QUOTED_PRINTABLE`], and so is whitespace at the end of a line. Lines longer
+/// than 76 [`char`]s are split using soft line breaks, i.e. `"=\r\n"`, without splitting escape
+/// sequences.
+///
+/// ```
+/// use char_escape::presets::encode_quoted_printable;
+///
+/// assert_eq!(encode_quoted_printable("Grüße \r\nx=1 "), "Gr=C3=BC=C3=9Fe=20\r\nx=3D1=20");
+///
+/// let encoded = encode_quoted_printable(&"x".repeat(80));
+/// assert_eq!(encoded, format!("{}=\r\n{}", "x".repeat(75), "x".repeat(5)));
+/// ```
+pub fn encode_quoted_printable(s: &str) -> String {
+    let mut ret = String::with_capacity(s.len());
+    for line in s.split_inclusive('\n') {
+        let (content, line_break) = split_line_break(line);
+
+        let trailing = content.len() - content.trim_end_matches([' ', '\t']).len();
+        let mut units = Vec::new();
+        for (i, c) in content.char_indices() {
+            match QUOTED_PRINTABLE.escape_of(c) {
+                Some(sequence) => {
+                    units.extend(sequence.split('=').map(|byte| ('=', byte.to_owned())));
+                }
+                None if i >= content.len() - trailing => {
+                    units.push(('=', format!("{:02X}", u32::from(c))));
+                }
+                None => units.push((c, String::new())),
+            }
+        }
+
+        let mut len = 0;
+        for (i, (first, rest)) in units.iter().enumerate() {
+            let unit_len = 1 + rest.len();
+            let limit = if i + 1 == units.len() {
+                QUOTED_PRINTABLE_LINE_LEN
+            } else {
+                QUOTED_PRINTABLE_LINE_LEN - 1
+            };
+            if len + unit_len > limit {
+                ret.push_str("=\r\n");
+                len = 0;
+            }
+            ret.push(*first);
+            ret.push_str(rest);
+            len += unit_len;
+        }
+        ret.push_str(line_break);
+    }
+
+    ret
+}
+
+/// Decodes quoted-printable text, as specified by RFC 2045.
+///
+/// Soft line breaks, i.e. `'='` at the end of a line, and whitespace at the end of lines are
+/// removed. Then, the `=XX` escape sequences are decoded using [`QUOTED_PRINTABLE`]. Lowercase hex
+/// digits are accepted as well.
+///
+/// # Errors
+///
+/// If an escape sequence is invalid or the decoded bytes are not valid UTF-8. The position of the
+/// error refers to the text with soft line breaks and trailing whitespace removed.
+///
+/// ```
+/// use char_escape::presets::decode_quoted_printable;
+///
+/// let decoded = decode_quoted_printable("Gr=C3=\r\n=BC=C3=9Fe  \r\nx=3D1").expect("is valid");
+/// assert_eq!(decoded, "Grüße\r\nx=1");
+/// assert!(decode_quoted_printable("=C3").is_err());
+/// ```
+pub fn decode_quoted_printable(s: &str) -> Result<String, UnescapeError> {
+    let mut joined = String::with_capacity(s.len());
+    for line in s.split_inclusive('\n') {
+        let (content, line_break) = split_line_break(line);
+
+        let content = content.trim_end_matches([' ', '\t']);
+        match content.strip_suffix('=') {
+            Some(content) if !line_break.is_empty() => joined.push_str(content),
+            _ => {
+                joined.push_str(content);
+                joined.push_str(line_break);
+            }
+        }
+    }
+
+    QUOTED_PRINTABLE.unescape(&joined)
+}
+
+/// Splits a line into its content and its line break, which is `"\r\n"`, `"\n"` or empty.
+fn split_line_break(line: &str) -> (&str, &str) {
+    match line.strip_suffix('\n') {
+        Some(content) => match content.strip_suffix('\r') {
+            Some(content) => (content, "\r\n"),
+            None => (content, "\n"),
+        },
+        None => (line, ""),
+    }
+}
+
+/// Escapes values in INI files.
+///
+/// The control characters that have a short escape sequence, e.g. `\n`, are escaped, and so are
+/// `'"'`, `'\''`, `'#'` and `';'`, since many parsers support inline comments and strip quotes.
+/// Unescaping additionally accepts `\xXXXX` for every [`char`].
+///
+/// Parsers strip whitespace around values, so use [`escape_ini_value()`] if the value may start or
+/// end with a space.
+///
+/// ```
+/// use char_escape::presets::INI_VALUE;
+///
+/// assert_eq!(INI_VALUE.escape("a;b # \"c\"\n"), r#"a\;b \# \"c\"\n"#);
+/// assert_eq!(INI_VALUE.unescape(r"\x0041\;").expect("is properly escaped"), "A;");
+/// ```
+pub const INI_VALUE: Escaper<'static> = escaper! {
+    self = ['"', '\'', ';', '#'],
+    rules = [
+        '\0' => '0',
+        '\x07' => 'a',
+        '\x08' => 'b',
+        '\t' => 't',
+        '\r' => 'r',
+        '\n' => 'n',
+    ],
+}
+.with_class_rules(&[decode_only(INI_HEX)]);
+
+/// Escapes keys in INI files.
+///
+/// Like [`INI_VALUE`], but `'='` and `':'` are escaped as well, since they separate the key from
+/// the value. `'#'` and `';'` are only escaped at the start of the key, where they would turn the
+/// line into a comment, and so is `'['`, which would start a section.
+///
+/// ```
+/// use char_escape::presets::INI_KEY;
+///
+/// assert_eq!(INI_KEY.escape("#a=b;c"), r"\#a\=b;c");
+/// assert_eq!(INI_KEY.escape("[x]"), r"\[x]");
+/// ```
+pub const INI_KEY: Escaper<'static> = escaper! {
+    self = ['"', '\'', '=', ':'],
+    rules = [
+        '\0' => '0',
+        '\x07' => 'a',
+        '\x08' => 'b',
+        '\t' => 't',
+        '\r' => 'r',
+        '\n' => 'n',
+    ],
+}
+.with_class_rules(&[decode_only(INI_HEX)])
+.with_positional_rules(&[
+    PositionalRule {
+        unescaped: ';',
+        escaped: ';',
+        position: Position::StartOfString,
+    },
+    PositionalRule {
+        unescaped: '#',
+        escaped: '#',
+        position: Position::StartOfString,
+    },
+    PositionalRule {
+        unescaped: '[',
+        escaped: '[',
+        position: Position::StartOfString,
+    },
+]);
+
+/// Escapes a value for an INI file, like [`INI_VALUE`], but also protects spaces at its start and
+/// end.
+///
+/// Parsers strip whitespace around values, even if it is preceded by a backslash. Therefore, these
+/// spaces are escaped as `\x0020`.
+///
+/// ```
+/// use char_escape::presets::{escape_ini_value, INI_VALUE};
+///
+/// let escaped = escape_ini_value("  a b ");
+/// assert_eq!(escaped, r"\x0020\x0020a b\x0020");
+/// assert_eq!(INI_VALUE.unescape(&escaped).expect("is properly escaped"), "  a b ");
+/// ```
+pub fn escape_ini_value(s: &str) -> String {
+    escape_ini(INI_VALUE, s)
+}
+
+/// Escapes a key for an INI file, like [`INI_KEY`], but also protects spaces at its start and end.
+///
+/// See [`escape_ini_value()`].
+pub fn escape_ini_key(s: &str) -> String {
+    escape_ini(INI_KEY, s)
+}
+
+/// Escapes `s` using `ini` and replaces the spaces at its start and end by `\x0020`.
+fn escape_ini(ini: Escaper<'_>, s: &str) -> String {
+    const SPACE: &str = r"\x0020";
+
+    let escaped = ini.escape(s);
+    let content = escaped.trim_matches(' ');
+    let leading = escaped.len() - escaped.trim_start_matches(' ').len();
+    let trailing = escaped.len() - escaped.trim_end_matches(' ').len();
+    if content.is_empty() {
+        return SPACE.repeat(escaped.len());
+    }
+
+    let mut ret = String::with_capacity(content.len() + (leading + trailing) * SPACE.len());
+    ret.push_str(&SPACE.repeat(leading));
+    ret.push_str(content);
+    ret.push_str(&SPACE.repeat(trailing));
+
+    ret
+}
+
+/// Escapes values in git config files, like `git config` does.
+///
+/// `'\n'` and `'\t'` are escaped as `\n` and `\t`, and `'"'` using a backslash. Unescaping
+/// additionally accepts `\b`. Values with whitespace at the start or end or with `';'` or `'#'`
+/// must be quoted as well, use [`quote_for_git_config()`] for that.
+///
+/// ```
+/// use char_escape::presets::GIT_CONFIG;
+///
+/// assert_eq!(GIT_CONFIG.escape("say \"hi\"\n"), r#"say \"hi\"\n"#);
+/// ```
+pub const GIT_CONFIG: Escaper<'static> = escaper! {
+    '\n' => 'n',
+    '\t' => 't',
+    '"' => '"',
+}
+.with_aliases(&[Rule {
+    unescaped: '\x08',
+    escaped: 'b',
+}]);
+
+/// Escapes `s` as a value in a git config file, enclosing it in double quotes if necessary.
+///
+/// Like `git config`, the value is quoted if it starts or ends with whitespace or contains `';'`
+/// or `'#'`, which would start a comment.
+///
+/// ```
+/// use char_escape::presets::quote_for_git_config;
+///
+/// assert_eq!(quote_for_git_config("vim -f"), "vim -f");
+/// assert_eq!(quote_for_git_config(" a;b\n"), r#"" a;b\n""#);
+/// ```
+pub fn quote_for_git_config(s: &str) -> Cow<'_, str> {
+    let needs_quotes = s.starts_with(char::is_whitespace)
+        || s.ends_with(char::is_whitespace)
+        || s.contains([';', '#']);
+    if !needs_quotes {
+        return GIT_CONFIG.escape(s);
+    }
+
+    let mut ret = String::with_capacity(s.len() + 2);
+    ret.push('"');
+    GIT_CONFIG.escape_into(s, &mut ret);
+    ret.push('"');
+
+    Cow::Owned(ret)
+}
+
+/// Parses a value of a git config file, i.e. everything after the `'='`, like git does.
+///
+/// Double quotes can enclose any part of the value and are removed. Outside of them, `';'` and
+/// `'#'` start a comment, whitespace at the start and end of the value is removed and other
+/// whitespace is replaced by spaces. Escape sequences are decoded using [`GIT_CONFIG`], and a
+/// backslash at the end of a line continues the value on the next line.
+///
+/// # Errors
+///
+/// If a double quote is not closed, the value ends in a backslash or contains an escape sequence
+/// that git doesn't accept. A line break that doesn't follow a backslash is rejected as well.
+///
+/// ```
+/// use char_escape::presets::unquote_git_config;
+///
+/// let value = unquote_git_config(r#" "a;b"  c\n\"d\" ; comment"#).expect("is valid");
+/// assert_eq!(value, "a;b  c\n\"d\"");
+/// assert!(unquote_git_config(r#""open"#).is_err());
+/// assert!(unquote_git_config(r"\x").is_err());
+/// ```
+pub fn unquote_git_config(s: &str) -> Result<String, UnescapeError> {
+    let mut ret = String::with_capacity(s.len());
+    let mut spaces = 0;
+    let mut quote = None;
+    let mut chars = s.char_indices();
+    while let Some((position, c)) = chars.next() {
+        match c {
+            '"' => {
+                quote = match quote {
+                    Some(_) => None,
+                    None => Some(position),
+                };
+                continue;
+            }
+            '\\' => match chars.next() {
+                Some((_, '\n')) => continue,
+                Some((_, escaped)) => {
+                    match GIT_CONFIG.unescape_of(escaped.encode_utf8(&mut [0; 4])) {
+                        Some(unescaped) => {
+                            ret.extend(iter::repeat_n(' ', spaces));
+                            spaces = 0;
+                            ret.push(unescaped);
+                            continue;
+                        }
+                        None => {
+                            return Err(UnescapeError::Invalid {
+                                sequence: format!("{c}{escaped}"),
+                                position,
+                            })
+                        }
+                    }
+                }
+                None => return Err(UnescapeError::Incomplete { position }),
+            },
+            '\n' => return Err(invalid(c, position)),
+            ';' | '#' if quote.is_none() => break,
+            c if c.is_whitespace() && quote.is_none() => {
+                if !ret.is_empty() {
+                    spaces += 1;
+                }
+                continue;
+            }
+            _ => {}
+        }
+
+        ret.extend(iter::repeat_n(' ', spaces));
+        spaces = 0;
+        ret.push(c);
+    }
+
+    match quote {
+        Some(position) => Err(UnescapeError::Incomplete { position }),
+        None => Ok(ret),
+    }
+}
+
+/// Escapes a string for use in a systemd unit name, like `systemd-escape` does.
+///
+/// `'/'` is replaced by `'-'`. ASCII letters, digits, `':'`, `'_'` and `'.'` are kept, except for a
+/// `'.'` at the start. Everything else is escaped as `\xHH` for each of its UTF-8 bytes, using
+/// lowercase hex digits.
+///
+/// This uses a different mechanism than [`Escaper`], since `'-'` doesn't start an escape sequence.
+///
+/// ```
+/// use char_escape::presets::systemd_escape;
+///
+/// assert_eq!(systemd_escape("foo/bar-baz ä"), r"foo-bar\x2dbaz\x20\xc3\xa4");
+/// assert_eq!(systemd_escape(".hidden"), r"\x2ehidden");
+/// ```
+pub fn systemd_escape(s: &str) -> String {
+    let mut ret = String::with_capacity(s.len());
+    for (i, c) in s.char_indices() {
+        match c {
+            '/' => ret.push('-'),
+            '.' if i == 0 => ret.push_str(r"\x2e"),
+            'a'..='z' | 'A'..='Z' | '0'..='9' | ':' | '_' | '.' => ret.push(c),
+            _ => {
+                for byte in c.encode_utf8(&mut [0; 4]).bytes() {
+                    write!(ret, r"\x{byte:02x}").expect("writing to a string doesn't fail");
+                }
+            }
+        }
+    }
+
+    ret
+}
+
+/// Reverses [`systemd_escape()`], like `systemd-escape --unescape` does.
+///
+/// `'-'` is replaced by `'/'` and `\xHH` escape sequences are decoded. Uppercase hex digits are
+/// accepted as well.
+///
+/// # Errors
+///
+/// If a backslash isn't followed by `'x'` and two hex digits, or if the escaped bytes are not
+/// valid UTF-8.
+///
+/// ```
+/// use char_escape::presets::systemd_unescape;
+///
+/// let unescaped = systemd_unescape(r"foo-bar\x2dbaz\x20\xc3\xa4").expect("is properly escaped");
+/// assert_eq!(unescaped, "foo/bar-baz ä");
+/// assert!(systemd_unescape(r"\xc3").is_err());
+/// ```
+pub fn systemd_unescape(s: &str) -> Result<String, UnescapeError> {
+    let mut ret = String::with_capacity(s.len());
+    let mut position = 0;
+    while let Some(c) = s[position..].chars().next() {
+        match c {
+            '-' => ret.push('/'),
+            '\\' => {
+                let mut utf8 = Vec::with_capacity(4);
+                let mut end = position;
+                loop {
+                    let remaining = &s[end..];
+                    let is_cut_off = r"\x".starts_with(remaining)
+                        || remaining.strip_prefix(r"\x").is_some_and(|digits| {
+                            digits.len() < 2 && digits.bytes().all(|byte| byte.is_ascii_hexdigit())
+                        });
+                    let byte = match systemd_byte(remaining) {
+                        Some(byte) => byte,
+                        None if is_cut_off => return Err(UnescapeError::Incomplete { position }),
+                        None => {
+                            let sequence = s[position..].chars().take(end - position + 4);
+                            return Err(UnescapeError::Invalid {
+                                sequence: sequence.collect(),
+                                position,
+                            });
+                        }
+                    };
+                    utf8.push(byte);
+                    end += 4;
+                    match core::str::from_utf8(&utf8) {
+                        Ok(decoded) => {
+                            ret.push_str(decoded);
+                            break;
+                        }
+                        Err(error) if error.error_len().is_none() => {}
+                        Err(_) => {
+                            return Err(UnescapeError::Invalid {
+                                sequence: s[position..end].to_owned(),
+                                position,
+                            })
+                        }
+                    }
+                }
+                position = end;
+                continue;
+            }
+            _ => ret.push(c),
+        }
+        position += c.len_utf8();
+    }
+
+    Ok(ret)
+}
+
+/// Escapes a path for use in a systemd unit name, like `systemd-escape --path` does.
+///
+/// Redundant slashes, including those at the start and end, are removed before escaping the path
+/// using [`systemd_escape()`]. The root directory becomes `"-"`.
+///
+/// ```
+/// use char_escape::presets::systemd_escape_path;
+///
+/// assert_eq!(systemd_escape_path("/mnt//data disk/"), r"mnt-data\x20disk");
+/// assert_eq!(systemd_escape_path("/"), "-");
+/// ```
+pub fn systemd_escape_path(path: &str) -> String {
+    let components: Vec<_> = path.split('/').filter(|c| !c.is_empty()).collect();
+    if components.is_empty() {
+        return "-".to_owned();
+    }
+
+    systemd_escape(&components.join("/"))
+}
+
+/// Reverses [`systemd_escape_path()`], like `systemd-escape --unescape --path` does.
+///
+/// # Errors
+///
+/// See [`systemd_unescape()`].
+///
+/// ```
+/// use char_escape::presets::systemd_unescape_path;
+///
+/// let path = systemd_unescape_path(r"mnt-data\x20disk").expect("is properly escaped");
+/// assert_eq!(path, "/mnt/data disk");
+/// assert_eq!(systemd_unescape_path("-").expect("is properly escaped"), "/");
+/// ```
+pub fn systemd_unescape_path(s: &str) -> Result<String, UnescapeError> {
+    if s == "-" {
+        return Ok("/".to_owned());
+    }
+
+    systemd_unescape(s).map(|path| format!("/{path}"))
+}
+
+/// Decodes a single `\xHH` escape sequence at the start of `rest`.
+fn systemd_byte(rest: &str) -> Option<u8> {
+    rest.strip_prefix(r"\x")
+        .and_then(|digits| digits.get(..2))
+        .filter(|digits| digits.bytes().all(|byte| byte.is_ascii_hexdigit()))
+        .map(|digits| u8::from_str_radix(digits, 16).expect("consists of two hex digits"))
+}
+
+/// Escapes the content of double-quoted values in `.env` files.
+///
+/// `'\n'`, `'\r'` and `'\t'` are escaped as `\n`, `\r` and `\t`, and `'"'` and `'$'` using a
+/// backslash, so that the value isn't interpolated. Use [`quote_for_dotenv()`] to produce a whole
+/// value.
+///
+/// ```
+/// use char_escape::presets::DOTENV;
+///
+/// assert_eq!(DOTENV.escape("say \"$HOME\"\n"), r#"say \"\$HOME\"\n"#);
+/// ```
+pub const DOTENV: Escaper<'static> = escaper! {
+    '\n' => 'n',
+    '\r' => 'r',
+    '\t' => 't',
+    '"' => '"',
+    '$' => '$',
+};
+
+/// Quotes `s` as a value in a `.env` file.
+///
+/// Values consisting only of ASCII letters, digits and `'%'`, `'+'`, `','`, `'-'`, `'.'`, `'/'`,
+/// `':'`, `'@'` and `'_'` are returned as they are. Everything else is enclosed in double quotes
+/// and escaped using [`DOTENV`].
+///
+/// ```
+/// use char_escape::presets::quote_for_dotenv;
+///
+/// assert_eq!(quote_for_dotenv("localhost:5432"), "localhost:5432");
+/// assert_eq!(quote_for_dotenv("p@ss word$1\n"), r#""p@ss word\$1\n""#);
+/// assert_eq!(quote_for_dotenv(""), r#""""#);
+/// ```
+pub fn quote_for_dotenv(s: &str) -> Cow<'_, str> {
+    let is_safe = |c: char| c.is_ascii_alphanumeric() || "%+,-./:@_".contains(c);
+    if !s.is_empty() && s.chars().all(is_safe) {
+        return Cow::Borrowed(s);
+    }
+
+    let mut ret = String::with_capacity(s.len() + 2);
+    ret.push('"');
+    DOTENV.escape_into(s, &mut ret);
+    ret.push('"');
+
+    Cow::Owned(ret)
+}
+
+/// Parses a value of a `.env` file, i.e. everything after the `'='`.
+///
+/// Double-quoted values are unescaped using [`DOTENV`], single-quoted values are taken literally.
+/// Unquoted values end at a `'#'` that follows whitespace, which starts a comment. Whitespace
+/// around the value and after the closing quote is ignored.
+///
+/// # Errors
+///
+/// If a quote is not closed, if there is anything but a comment after the closing quote, or if
+/// the value contains an escape sequence that [`DOTENV`] doesn't accept. Values that would be
+/// interpolated, i.e. that contain an unescaped `'$'` outside of single quotes, are rejected as
+/// well.
+///
+/// ```
+/// use char_escape::presets::unquote_dotenv;
+///
+/// assert_eq!(unquote_dotenv(r#" "a \"b\"\n" # comment"#).expect("is valid"), "a \"b\"\n");
+/// assert_eq!(unquote_dotenv(" 'C:\\$dir' ").expect("is valid"), "C:\\$dir");
+/// assert_eq!(unquote_dotenv("plain value # comment").expect("is valid"), "plain value");
+/// assert!(unquote_dotenv(r#""open"#).is_err());
+/// assert!(unquote_dotenv("$HOME").is_err());
+/// ```
+pub fn unquote_dotenv(s: &str) -> Result<String, UnescapeError> {
+    let value = s.trim_start();
+    let start = s.len() - value.len();
+
+    let (unquoted, rest) = if let Some(inner) = value.strip_prefix('"') {
+        let mut escaped = false;
+        let mut end = None;
+        for (i, c) in inner.char_indices() {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => {
+                    end = Some(i);
+                    break;
+                }
+                '$' => return Err(invalid(c, start + 1 + i)),
+                _ => {}
+            }
+        }
+        let Some(end) = end else {
+            return Err(UnescapeError::Incomplete { position: start });
+        };
+        let unquoted = DOTENV
+            .unescape(&inner[..end])
+            .map_err(|error| match error {
+                UnescapeError::Invalid { sequence, position } => UnescapeError::Invalid {
+                    sequence,
+                    position: start + 1 + position,
+                },
+                UnescapeError::Incomplete { position } => UnescapeError::Incomplete {
+                    position: start + 1 + position,
+                },
+            })?;
+        (unquoted, &inner[end + 1..])
+    } else if let Some(inner) = value.strip_prefix('\'') {
+        let Some(end) = inner.find('\'') else {
+            return Err(UnescapeError::Incomplete { position: start });
+        };
+        (inner[..end].to_owned(), &inner[end + 1..])
+    } else {
+        let mut prev = ' ';
+        let end = value
+            .char_indices()
+            .find(|&(_, c)| {
+                let comment = c == '#' && prev.is_whitespace();
+                prev = c;
+                comment
+            })
+            .map_or(value.len(), |(i, _)| i);
+        let unquoted = value[..end].trim_end();
+        if let Some(i) = unquoted.find('$') {
+            return Err(invalid('$', start + i));
+        }
+        (unquoted.to_owned(), "")
+    };
+
+    let rest = rest.trim_start();
+    match rest.chars().next() {
+        None | Some('#') => Ok(unquoted),
+        Some(c) => Err(invalid(c, s.len() - rest.len())),
+    }
+}
+
+/// Returns an [`UnescapeError::Invalid`] for a single [`char`].
+fn invalid(c: char, position: usize) -> UnescapeError {
+    UnescapeError::Invalid {
+        sequence: c.to_string(),
+        position,
+    }
+}
+
+/// Escapes words for the POSIX shell using backslashes.
+///
+/// Every ASCII [`char`] that has a special meaning to `/bin/sh` is preceded by a backslash. A
+/// newline can't be escaped this way, since the shell removes a backslash followed by a newline.
+/// Therefore control characters and non-ASCII [`char`]s are [rejected](Fallback::Reject), so use
+/// [`try_escape()`](Escaper::try_escape) or, to handle every string, [`quote_for_sh()`].
+///
+/// Unescaping only accepts the escape sequences produced by escaping. Use [`unquote_sh()`] for
+/// words written by hand.
+///
+/// ```
+/// use char_escape::presets::SH;
+///
+/// assert_eq!(SH.escape("it's $HOME"), r"it\'s\ \$HOME");
+/// assert!(SH.try_escape("line\nbreak").is_err());
+/// ```
+pub const SH: Escaper<'static> = escaper! {
+    self = [
+        '\t', ' ', '!', '"', '#', '$', '&', '\'', '(', ')', '*', ';', '<', '>', '?', '[', ']', '^',
+        '`', '{', '|', '}', '~',
+    ],
+}
+.with_fallback(Fallback::Reject);
+
+/// Quotes `s` for the POSIX shell, so that it is passed to a command as a single argument.
+///
+/// Strings consisting only of ASCII letters, digits and `'%'`, `'+'`, `','`, `'-'`, `'.'`,
+/// `'/'`, `':'`, `'='`, `'@'` and `'_'` are returned as they are. Everything else is enclosed in
+/// single quotes, which keep every [`char`] literally. Single quotes inside are written as
+/// `'\''`, i.e. the quoted string is closed, followed by an escaped quote, and reopened.
+///
+/// ```
+/// use char_escape::presets::quote_for_sh;
+///
+/// assert_eq!(quote_for_sh("file-1.txt"), "file-1.txt");
+/// assert_eq!(quote_for_sh("it's $HOME\n"), "'it'\\''s $HOME\n'");
+/// assert_eq!(quote_for_sh(""), "''");
+/// ```
+pub fn quote_for_sh(s: &str) -> Cow<'_, str> {
+    let is_safe = |c: char| c.is_ascii_alphanumeric() || "%+,-./:=@_".contains(c);
+    if !s.is_empty() && s.chars().all(is_safe) {
+        return Cow::Borrowed(s);
+    }
+
+    let mut ret = String::with_capacity(s.len() + 2);
+    ret.push('\'');
+    for c in s.chars() {
+        match c {
+            '\'' => ret.push_str(r"'\''"),
+            c => ret.push(c),
+        }
+    }
+    ret.push('\'');
+
+    Cow::Owned(ret)
+}
+
+/// Removes the quotes and backslashes from a single word of a POSIX shell command.
+///
+/// This is the inverse of [`quote_for_sh()`] and [`SH`], but also accepts other ways of quoting,
+/// e.g. double quotes or a mix of quoted and unquoted parts.
+///
+/// # Errors
+///
+/// If a quote is not closed or the word ends in a backslash, [`UnescapeError::Incomplete`] is
+/// returned. [`char`]s that would make the shell split the word or expand parts of it, e.g. an
+/// unquoted space or `'$'`, result in [`UnescapeError::Invalid`].
+///
+/// ```
+/// use char_escape::presets::unquote_sh;
+///
+/// let word = r#"'it'\''s'" \$ \"x\""\ b"#;
+/// assert_eq!(unquote_sh(word).expect("is a single word"), r#"it's $ "x" b"#);
+/// assert!(unquote_sh("'open").is_err());
+/// assert!(unquote_sh("two words").is_err());
+/// assert!(unquote_sh("\"$HOME\"").is_err());
+/// ```
+pub fn unquote_sh(s: &str) -> Result<String, UnescapeError> {
+    let mut ret = String::with_capacity(s.len());
+    let mut chars = s.char_indices().peekable();
+    while let Some((position, c)) = chars.next() {
+        match c {
+            '\'' => loop {
+                match chars.next() {
+                    Some((_, '\'')) => break,
+                    Some((_, c)) => ret.push(c),
+                    None => return Err(UnescapeError::Incomplete { position }),
+                }
+            },
+            '"' => loop {
+                match chars.next() {
+                    Some((_, '"')) => break,
+                    Some((escape, '\\')) => match chars.next() {
+                        Some((_, '\n')) => {}
+                        Some((_, c @ ('$' | '`' | '"' | '\\'))) => ret.push(c),
+                        Some((_, c)) => {
+                            ret.push('\\');
+                            ret.push(c);
+                        }
+                        None => return Err(UnescapeError::Incomplete { position: escape }),
+                    },
+                    Some((position, c @ ('$' | '`'))) => return Err(invalid(c, position)),
+                    Some((_, c)) => ret.push(c),
+                    None => return Err(UnescapeError::Incomplete { position }),
+                }
+            },
+            '\\' => match chars.next() {
+                Some((_, '\n')) => {}
+                Some((_, c)) => ret.push(c),
+                None => return Err(UnescapeError::Incomplete { position }),
+            },
+            '#' if position == 0 => return Err(invalid(c, position)),
+            '\t' | '\n' | ' ' | '$' | '&' | '(' | ')' | '*' | ';' | '<' | '>' | '?' | '[' | '`'
+            | '|' => return Err(invalid(c, position)),
+            c => ret.push(c),
+        }
+    }
+
+    Ok(ret)
+}
+
+/// Escapes the content of PowerShell double-quoted strings using backticks.
+///
+/// `'$'`, `` '`' `` and `'"'` are escaped, so that variables and subexpressions are not expanded,
+/// and so are the typographic double quotes `'“'`, `'”'` and `'„'`, which PowerShell treats like
+/// `'"'`. Control characters that have an escape sequence, e.g. `` `n `` and `` `0 ``, are escaped
+/// as well.
+///
+/// ```
+/// use char_escape::presets::POWERSHELL;
+///
+/// let escaped = POWERSHELL.escape("Cost: $5 \"`\"\n");
+/// assert_eq!(escaped, "Cost: `$5 `\"```\"`n");
+/// assert_eq!(POWERSHELL.unescape(&escaped).expect("is properly escaped"), "Cost: $5 \"`\"\n");
+/// ```
+pub const POWERSHELL: Escaper<'static> = escaper! {
+    escape_char = '`',
+    rules = [
+        '\0' => '0',
+        '\x07' => 'a',
+        '\x08' => 'b',
+        '\x1b' => 'e',
+        '\x0c' => 'f',
+        '\n' => 'n',
+        '\r' => 'r',
+        '\t' => 't',
+        '\x0b' => 'v',
+    ],
+    self = ['$', '"', '“', '”', '„'],
+};
+
+/// Escapes the metacharacters of `cmd.exe` using carets.
+///
+/// `'&'`, `'|'`, `'<'`, `'>'`, `'('`, `')'`, `'"'` and `'^'` are preceded by a caret. `'%'` can
+/// only be escaped in batch files, see [`batch()`]. Like for [`SH`], newlines can't be escaped, so
+/// control characters and non-ASCII [`char`]s are [rejected](Fallback::Reject).
+///
+/// Delayed expansion isn't supported, since it requires `'!'` to be escaped twice.
+///
+/// ```
+/// use char_escape::presets::CMD;
+///
+/// assert_eq!(CMD.escape(r#"echo "a" & b > c"#), r#"echo ^"a^" ^& b ^> c"#);
+/// assert_eq!(CMD.unescape("a^^b^|c").expect("is properly escaped"), "a^b|c");
+/// ```
+pub const CMD: Escaper<'static> = escaper! {
+    escape_char = '^',
+    self = ['&', '|', '<', '>', '(', ')', '"'],
+}
+.with_fallback(Fallback::Reject);
+
+/// Escapes text for commands in batch files.
+///
+/// Like [`CMD`], but `'%'` is doubled first, so that it isn't taken for the start of a variable.
+/// Unescaping removes the carets first and then halves the `'%'`s.
+///
+/// ```
+/// use char_escape::presets::batch;
+///
+/// let batch = batch();
+///
+/// assert_eq!(batch.escape("echo 100% & done"), "echo 100%% ^& done");
+/// assert_eq!(batch.unescape("50%%^|50%%").expect("is properly escaped"), "50%|50%");
+/// assert!(batch.unescape("%PATH%").is_err());
+/// ```
+pub fn batch() -> Chain<'static> {
+    BATCH_PERCENT.then(CMD)
+}
+
+/// Doubles `'%'`, the first layer of [`batch()`].
+const BATCH_PERCENT: Escaper<'static> = escaper! {
+    escape_char = '%',
+    rules = [],
+};
+
+/// The `\xHH` notation of YAML, Python, JavaScript and protobuf.
+const YAML_HEX: Notation = Notation::FixedHex {
+    prefix: 'x',
+    digits: 2,
+};
+
+/// The [`ClassRule`]s of [`PYTHON`].
+const PYTHON_CLASS_RULES: &[ClassRule] = &[
+    ClassRule::range('\0'..='\x1f', YAML_HEX),
+    ClassRule::range('\x7f'..='\u{a0}', YAML_HEX),
+    ClassRule::range('\u{ad}'..='\u{ad}', YAML_HEX),
+    ClassRule::range('\u{2028}'..='\u{2029}', UNICODE_4),
+    decode_only(UNICODE_8),
+    decode_only(C_OCTAL),
+    decode_only(Notation::Named),
+];
+
+/// The [`ClassRule`]s of [`JS_STRING`] and [`JS_TEMPLATE`].
+const JS_CLASS_RULES: &[ClassRule] = &[
+    ClassRule::range('\0'..='\x1f', YAML_HEX),
+    ClassRule::range('<'..='<', YAML_HEX),
+    // before `Notation::Utf16`, which rejects `\u{…}`
+    decode_only(Notation::Unicode),
+    ClassRule::range('\u{2028}'..='\u{2029}', Notation::Utf16),
+];
+
+/// The octal notation of C, Python and protobuf, with one to three digits.
+const C_OCTAL: Notation = Notation::Octal {
+    max_digits: 3,
+    strict: false,
+};
+
+/// The `\xXXXX` notation of INI files.
+const INI_HEX: Notation = Notation::FixedHex {
+    prefix: 'x',
+    digits: 4,
+};
+
+/// The `\uXXXX` notation of YAML, TOML, Python and protobuf.
+const UNICODE_4: Notation = Notation::FixedHex {
+    prefix: 'u',
+    digits: 4,
+};
+
+/// The `\UXXXXXXXX` notation of YAML, TOML, Python and protobuf.
+const UNICODE_8: Notation = Notation::FixedHex {
+    prefix: 'U',
+    digits: 8,
+};
+
+/// A [`ClassRule`] that percent-encodes every [`char`] for which `reserved` returns `true`.
+///
+/// `reserved` must return `true` for `'%'`, since the URL presets don't have a [`Rule`] for the
+/// escape character.
+const fn percent(reserved: fn(char) -> bool) -> ClassRule {
+    ClassRule {
+        class: CharClass::Predicate(reserved),
+        notation: Notation::Percent,
+    }
+}
+
+/// The path percent-encode set, and `'%'`.
+fn is_path_reserved(c: char) -> bool {
+    is_query_reserved(c) || matches!(c, '?' | '`' | '^' | '{' | '}')
+}
+
+/// The query percent-encode set, and `'%'`.
+fn is_query_reserved(c: char) -> bool {
+    is_c0_reserved(c) || matches!(c, ' ' | '"' | '#' | '%' | '<' | '>')
+}
+
+/// The fragment percent-encode set, and `'%'`.
+fn is_fragment_reserved(c: char) -> bool {
+    is_c0_reserved(c) || matches!(c, ' ' | '"' | '%' | '<' | '>' | '`')
+}
+
+/// The `application/x-www-form-urlencoded` percent-encode set.
+fn is_form_reserved(c: char) -> bool {
+    !(c.is_ascii_alphanumeric() || matches!(c, '*' | '-' | '.' | '_'))
+}
+
+/// The [`char`]s that quoted-printable encodes, i.e. everything except `' '`, `'\t'` and the
+/// printable ASCII characters other than `'='`.
+fn is_quoted_printable_reserved(c: char) -> bool {
+    !matches!(c, '\t' | ' '..='<' | '>'..='~')
+}
+
+/// The control characters that affect a terminal, i.e. all except `'\n'` and `'\t'`.
+fn is_terminal_control(c: char) -> bool {
+    c.is_control() && !matches!(c, '\n' | '\t')
+}
+
+/// The C0 control percent-encode set.
+fn is_c0_reserved(c: char) -> bool {
+    c.is_ascii_control() || !c.is_ascii()
+}
+
+/// A [`ClassRule`] that doesn't escape anything, but lets unescaping accept `\x00` to `\x7f`.
+const DECODE_ONLY_ASCII_HEX: ClassRule = decode_only(Notation::AsciiHex);
+
+/// A [`ClassRule`] that doesn't escape anything, but lets unescaping accept `notation`.
+const fn decode_only(notation: Notation) -> ClassRule {
+    ClassRule {
+        class: CharClass::Predicate(nothing),
+        notation,
+    }
+}
+
+/// A [`CharClass::Predicate`] that doesn't contain any [`char`].
+fn nothing(_: char) -> bool {
+    false
+}
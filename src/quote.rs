@@ -0,0 +1,173 @@
+use {crate::UnescapeError, std::borrow::Cow};
+
+/// Escape and unescape strings by enclosing them in quotes and doubling the quotes inside.
+///
+/// Unlike [`Escaper`](crate::Escaper), a [`Quoter`] doesn't have an escape character. Instead,
+/// the whole string is quoted, as in CSV fields or SQL string literals.
+///
+/// By default, every string is quoted. Using [`Quoter::with_special()`], strings are only quoted
+/// if they contain the quote or one of the special [`char`]s. Unescaping accepts both quoted and
+/// unquoted strings in that case.
+///
+/// ```
+/// # use char_escape::Quoter;
+/// #
+/// const SQL_STRING: Quoter<'static> = Quoter::new('\'');
+///
+/// assert_eq!(SQL_STRING.escape("it's"), "'it''s'");
+/// assert_eq!(SQL_STRING.unescape("'it''s'").expect("is properly quoted"), "it's");
+///
+/// assert!(SQL_STRING.unescape("it's").is_err());
+/// assert!(SQL_STRING.unescape("'it's'").is_err());
+/// ```
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Quoter<'a> {
+    quote: char,
+    special: Option<&'a [char]>,
+}
+
+impl<'a> Quoter<'a> {
+    /// Create a new [`Quoter`] that quotes every string using `quote`.
+    pub const fn new(quote: char) -> Self {
+        Self {
+            quote,
+            special: None,
+        }
+    }
+
+    /// Only quote strings that contain the quote or one of the `special` [`char`]s.
+    ///
+    /// ```
+    /// # use char_escape::Quoter;
+    /// #
+    /// const QUOTER: Quoter<'static> = Quoter::new('"').with_special(&[',']);
+    ///
+    /// assert_eq!(QUOTER.escape("plain"), "plain");
+    /// assert_eq!(QUOTER.escape("a,b"), r#""a,b""#);
+    /// assert_eq!(QUOTER.escape(r#"say "hi""#), r#""say ""hi""""#);
+    ///
+    /// assert_eq!(QUOTER.unescape("plain").expect("is properly quoted"), "plain");
+    /// assert_eq!(QUOTER.unescape(r#""plain""#).expect("is properly quoted"), "plain");
+    /// ```
+    #[must_use]
+    pub const fn with_special(self, special: &'a [char]) -> Self {
+        Self {
+            special: Some(special),
+            ..self
+        }
+    }
+
+    /// Returns the quote.
+    pub const fn quote(&self) -> char {
+        self.quote
+    }
+
+    /// Returns the [`char`]s that make a string require quotes, or [`None`] if every string is
+    /// quoted.
+    pub const fn special(&self) -> Option<&'a [char]> {
+        self.special
+    }
+
+    /// Check if the given string would be quoted.
+    pub fn needs_escaping(&self, s: &str) -> bool {
+        match self.special {
+            Some(special) => s.contains(|c| c == self.quote || special.contains(&c)),
+            None => true,
+        }
+    }
+
+    /// Escapes a string.
+    ///
+    /// If the string doesn't need to be quoted, it is returned as is.
+    pub fn escape<'s>(&self, s: &'s str) -> Cow<'s, str> {
+        if self.needs_escaping(s) {
+            let mut ret = String::with_capacity(s.len() + 2);
+            self.quote_into(s, &mut ret);
+            Cow::Owned(ret)
+        } else {
+            Cow::Borrowed(s)
+        }
+    }
+
+    /// Escapes a string and appends the result to `out`.
+    pub fn escape_into(&self, s: &str, out: &mut String) {
+        if self.needs_escaping(s) {
+            self.quote_into(s, out);
+        } else {
+            out.push_str(s);
+        }
+    }
+
+    /// Quotes `s` and appends the result to `out`.
+    fn quote_into(&self, s: &str, out: &mut String) {
+        out.push(self.quote);
+        for c in s.chars() {
+            if c == self.quote {
+                out.push(c);
+            }
+            out.push(c);
+        }
+        out.push(self.quote);
+    }
+
+    /// Unescapes a string.
+    ///
+    /// Strings without quotes inside are returned as is.
+    ///
+    /// # Errors
+    ///
+    /// If `s` is not properly quoted, i.e. if it contains a quote that is not doubled or if it
+    /// is missing the closing quote. If every string is quoted, `s` must also start with a quote.
+    /// Unquoted strings containing special [`char`]s are accepted.
+    pub fn unescape<'s>(&self, s: &'s str) -> Result<Cow<'s, str>, UnescapeError> {
+        let Some(inner) = s.strip_prefix(self.quote) else {
+            return if let Some(position) = s.find(self.quote) {
+                Err(UnescapeError::Invalid {
+                    sequence: self.quote.to_string(),
+                    position,
+                })
+            } else if self.special.is_none() {
+                Err(match s.chars().next() {
+                    Some(first) => UnescapeError::Invalid {
+                        sequence: first.to_string(),
+                        position: 0,
+                    },
+                    None => UnescapeError::Incomplete { position: 0 },
+                })
+            } else {
+                Ok(Cow::Borrowed(s))
+            };
+        };
+
+        let offset = self.quote.len_utf8();
+        let mut ret = String::new();
+        let mut start = 0;
+        let mut quotes = inner.match_indices(self.quote);
+        while let Some((i, _)) = quotes.next() {
+            let after = i + offset;
+            if after == inner.len() {
+                let unquoted = &inner[..i];
+                return Ok(if start == 0 {
+                    Cow::Borrowed(unquoted)
+                } else {
+                    ret.push_str(&unquoted[start..]);
+                    Cow::Owned(ret)
+                });
+            }
+            match quotes.next() {
+                Some((next, _)) if next == after => {
+                    ret.push_str(&inner[start..after]);
+                    start = next + offset;
+                }
+                _ => {
+                    return Err(UnescapeError::Invalid {
+                        sequence: self.quote.to_string(),
+                        position: offset + i,
+                    })
+                }
+            }
+        }
+
+        Err(UnescapeError::Incomplete { position: 0 })
+    }
+}
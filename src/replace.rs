@@ -0,0 +1,251 @@
+use std::borrow::Cow;
+
+/// Defines how one specific substring should be escaped.
+///
+/// Escaping replaces `unescaped` by `escaped` and unescaping replaces `escaped` by `unescaped`.
+///
+/// See [`Replacer`] for more information.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Replacement<'a> {
+    #[allow(missing_docs)]
+    pub unescaped: &'a str,
+    #[allow(missing_docs)]
+    pub escaped: &'a str,
+}
+
+impl<'a> From<(&'a str, &'a str)> for Replacement<'a> {
+    fn from((unescaped, escaped): (&'a str, &'a str)) -> Self {
+        Self { unescaped, escaped }
+    }
+}
+
+/// Escape and unescape strings by replacing substrings with other substrings.
+///
+/// Unlike [`Escaper`](crate::Escaper), a [`Replacer`] doesn't have a dedicated escape character.
+/// This makes it possible to express entity-style escaping, as used in HTML and XML.
+///
+/// Both escaping and unescaping scan the string from left to right. At every position, the
+/// longest matching substring is replaced, text that doesn't match any [`Replacement`] is kept as
+/// is. Replacements with an empty `unescaped` or `escaped` string are ignored in the respective
+/// direction.
+///
+/// For unescaping to reverse escaping, it must not be possible to confuse the escaped forms with
+/// unescaped text. For entity-style escaping, this is achieved by escaping the character that
+/// starts every entity (`'&'`).
+///
+/// ```
+/// # use char_escape::{Replacement, Replacer};
+/// #
+/// const HTML: Replacer<'static> = Replacer::new(&[
+///     Replacement { unescaped: "&", escaped: "&amp;" },
+///     Replacement { unescaped: "<", escaped: "&lt;" },
+///     Replacement { unescaped: ">", escaped: "&gt;" },
+/// ]);
+///
+/// let unescaped = "<b>Tom & Jerry</b>";
+/// let escaped = "&lt;b&gt;Tom &amp; Jerry&lt;/b&gt;";
+///
+/// assert_eq!(HTML.escape(unescaped), escaped);
+/// assert_eq!(HTML.unescape(escaped), unescaped);
+///
+/// // unknown entities are kept as they are
+/// assert_eq!(HTML.unescape("&amp;nbsp; &nbsp;"), "&nbsp; &nbsp;");
+/// ```
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Replacer<'a> {
+    replacements: &'a [Replacement<'a>],
+    aliases: &'a [Replacement<'a>],
+    numeric_references: bool,
+}
+
+impl<'a> Replacer<'a> {
+    /// Create a new [`Replacer`].
+    pub const fn new(replacements: &'a [Replacement<'a>]) -> Self {
+        Self {
+            replacements,
+            aliases: &[],
+            numeric_references: false,
+        }
+    }
+
+    /// Adds replacements that are only used for unescaping.
+    ///
+    /// Any aliases that were previously added using this method are replaced. If an alias and
+    /// one of the [replacements](Self::replacements) match equally long substrings, the
+    /// replacement is used.
+    ///
+    /// ```
+    /// # use char_escape::{Replacement, Replacer};
+    /// #
+    /// const REPLACER: Replacer<'static> = Replacer::new(&[
+    ///     Replacement { unescaped: "&", escaped: "&amp;" },
+    ///     Replacement { unescaped: "'", escaped: "&#39;" },
+    /// ])
+    /// .with_aliases(&[Replacement { unescaped: "'", escaped: "&apos;" }]);
+    ///
+    /// assert_eq!(REPLACER.escape("'&'"), "&#39;&amp;&#39;");
+    /// assert_eq!(REPLACER.unescape("&apos;&#39;"), "''");
+    /// ```
+    #[must_use]
+    pub const fn with_aliases(self, aliases: &'a [Replacement<'a>]) -> Self {
+        Self { aliases, ..self }
+    }
+
+    /// Sets whether unescaping decodes numeric character references, i.e. `&#` followed by a
+    /// decimal code point, or by `x` and a hexadecimal code point, and a `;`.
+    ///
+    /// References to code points that are not a valid [`char`] are kept as they are.
+    ///
+    /// ```
+    /// # use char_escape::{Replacement, Replacer};
+    /// #
+    /// const REPLACER: Replacer<'static> = Replacer::new(&[
+    ///     Replacement { unescaped: "&", escaped: "&amp;" },
+    /// ])
+    /// .with_numeric_references(true);
+    ///
+    /// assert_eq!(REPLACER.unescape("&#65;&#x42;&#xd800;&amp;#67;"), "AB&#xd800;&#67;");
+    /// ```
+    #[must_use]
+    pub const fn with_numeric_references(self, numeric_references: bool) -> Self {
+        Self {
+            numeric_references,
+            ..self
+        }
+    }
+
+    /// Returns the replacements used for escaping and unescaping.
+    pub const fn replacements(&self) -> &'a [Replacement<'a>] {
+        self.replacements
+    }
+
+    /// Returns the replacements that are only used for unescaping.
+    pub const fn aliases(&self) -> &'a [Replacement<'a>] {
+        self.aliases
+    }
+
+    /// Returns `true` if unescaping decodes numeric character references.
+    pub const fn numeric_references(&self) -> bool {
+        self.numeric_references
+    }
+
+    /// Escapes a string.
+    ///
+    /// If nothing needs to be replaced, the string is returned as is.
+    pub fn escape<'s>(&self, s: &'s str) -> Cow<'s, str> {
+        replace(s, |rest| {
+            longest_match(rest, self.replacements, |r| (r.unescaped, r.escaped))
+                .map(|(len, to)| (len, Replaced::Str(to)))
+        })
+    }
+
+    /// Escapes a string and appends the result to `out`.
+    pub fn escape_into(&self, s: &str, out: &mut String) {
+        out.push_str(&self.escape(s));
+    }
+
+    /// Unescapes a string.
+    ///
+    /// If nothing needs to be replaced, the string is returned as is.
+    pub fn unescape<'s>(&self, s: &'s str) -> Cow<'s, str> {
+        let direction = |r: &Replacement<'a>| (r.escaped, r.unescaped);
+        replace(s, |rest| {
+            let replacement = longest_match(rest, self.replacements, direction)
+                .map(|(len, to)| (len, Replaced::Str(to)));
+            let alias = longest_match(rest, self.aliases, direction)
+                .map(|(len, to)| (len, Replaced::Str(to)));
+            let reference = self
+                .numeric_references
+                .then(|| numeric_reference(rest))
+                .flatten()
+                .map(|(len, c)| (len, Replaced::Char(c)));
+
+            // the longest match wins, ties are won by the earlier candidate
+            [replacement, alias, reference]
+                .into_iter()
+                .flatten()
+                .reduce(|longest, next| if next.0 > longest.0 { next } else { longest })
+        })
+    }
+
+    /// Unescapes a string and appends the result to `out`.
+    pub fn unescape_into(&self, s: &str, out: &mut String) {
+        out.push_str(&self.unescape(s));
+    }
+}
+
+/// What a match is replaced with.
+enum Replaced<'r> {
+    Str(&'r str),
+    Char(char),
+}
+
+/// Returns the length of the longest substring at the start of `rest` that one of the
+/// `replacements` applies to and what to replace it with.
+///
+/// `direction` returns the substring to search for and the substring to replace it with.
+fn longest_match<'r>(
+    rest: &str,
+    replacements: &[Replacement<'r>],
+    direction: impl Fn(&Replacement<'r>) -> (&'r str, &'r str),
+) -> Option<(usize, &'r str)> {
+    replacements
+        .iter()
+        .map(direction)
+        .filter(|(from, _)| !from.is_empty() && rest.starts_with(from))
+        .max_by_key(|(from, _)| from.len())
+        .map(|(from, to)| (from.len(), to))
+}
+
+/// Decodes the numeric character reference at the start of `rest`.
+///
+/// Returns its length and the [`char`] it refers to.
+fn numeric_reference(rest: &str) -> Option<(usize, char)> {
+    let digits = rest.strip_prefix("&#")?;
+    let (digits, radix, prefix_len) = match digits.strip_prefix(['x', 'X']) {
+        Some(digits) => (digits, 16, 3),
+        None => (digits, 10, 2),
+    };
+    let end = digits.find(';')?;
+    if end == 0 || !digits[..end].chars().all(|c| c.is_digit(radix)) {
+        return None;
+    }
+    let c = u32::from_str_radix(&digits[..end], radix)
+        .ok()
+        .and_then(char::from_u32)?;
+
+    Some((prefix_len + end + 1, c))
+}
+
+/// Replaces the longest match at every position of `s`.
+///
+/// `matches` returns the length of the match at the start of the given string and what to
+/// replace it with.
+fn replace<'s, 'r>(
+    s: &'s str,
+    matches: impl Fn(&str) -> Option<(usize, Replaced<'r>)>,
+) -> Cow<'s, str> {
+    let mut replaced = String::new();
+    let mut start = 0;
+    let mut i = 0;
+    while let Some(c) = s[i..].chars().next() {
+        if let Some((len, to)) = matches(&s[i..]) {
+            replaced.push_str(&s[start..i]);
+            match to {
+                Replaced::Str(to) => replaced.push_str(to),
+                Replaced::Char(to) => replaced.push(to),
+            }
+            i += len;
+            start = i;
+        } else {
+            i += c.len_utf8();
+        }
+    }
+
+    if start == 0 {
+        Cow::Borrowed(s)
+    } else {
+        replaced.push_str(&s[start..]);
+        Cow::Owned(replaced)
+    }
+}
@@ -0,0 +1,299 @@
+use core::mem::size_of;
+
+/// The number of bytes in a word.
+#[cfg(not(all(feature = "simd", target_arch = "x86_64")))]
+const WORD_LEN: usize = size_of::<usize>();
+
+/// A word with every byte set to `0x01`.
+#[cfg(not(all(feature = "simd", target_arch = "x86_64")))]
+const LO: usize = usize::from_ne_bytes([0x01; WORD_LEN]);
+
+/// A word with every byte set to `0x80`.
+#[cfg(not(all(feature = "simd", target_arch = "x86_64")))]
+const HI: usize = usize::from_ne_bytes([0x80; WORD_LEN]);
+
+/// Up to three ASCII bytes to search for, like `memchr3()`.
+///
+/// Since the bytes are ASCII, they never occur inside the UTF-8 encoding of another [`char`], so
+/// every match is a [`char`] boundary.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Needles {
+    /// The bytes, padded by repeating the first one.
+    bytes: [u8; 3],
+    /// The number of distinct bytes.
+    len: usize,
+}
+
+impl Needles {
+    /// Collects the distinct `chars`.
+    ///
+    /// Returns [`None`] if there are more than three of them or one of them is not ASCII.
+    pub(crate) fn new(chars: impl IntoIterator<Item = char>) -> Option<Self> {
+        let mut needles = Self {
+            bytes: [0; 3],
+            len: 0,
+        };
+        for c in chars {
+            let byte = u8::try_from(c).ok().filter(u8::is_ascii)?;
+            if needles.bytes[..needles.len].contains(&byte) {
+                continue;
+            }
+            *needles.bytes.get_mut(needles.len)? = byte;
+            needles.len += 1;
+        }
+        let first = needles.bytes[0];
+        needles.bytes[needles.len..].fill(first);
+
+        Some(needles)
+    }
+
+    /// Returns the index of the first byte of `haystack` that is one of the needles.
+    ///
+    /// Compares a whole block at a time and only looks at single bytes once a block contains a
+    /// match.
+    pub(crate) fn find(&self, haystack: &[u8]) -> Option<usize> {
+        if self.len == 0 {
+            return None;
+        }
+
+        let offset = self.skip_blocks(haystack);
+        haystack[offset..]
+            .iter()
+            .position(|byte| self.bytes.contains(byte))
+            .map(|i| offset + i)
+    }
+
+    /// Returns the length of the blocks at the start of `haystack` that contain no needle.
+    ///
+    /// A block is a word.
+    #[cfg(not(all(feature = "simd", target_arch = "x86_64")))]
+    fn skip_blocks(&self, haystack: &[u8]) -> usize {
+        let splats = self.bytes.map(|byte| LO * usize::from(byte));
+        let mut offset = 0;
+        for chunk in haystack.chunks_exact(WORD_LEN) {
+            let word = usize::from_ne_bytes(chunk.try_into().expect("has the length of a word"));
+            if splats.iter().any(|&splat| has_zero_byte(word ^ splat)) {
+                break;
+            }
+            offset += WORD_LEN;
+        }
+
+        offset
+    }
+
+    /// Returns the length of the blocks at the start of `haystack` that contain no needle.
+    ///
+    /// A block consists of 16 bytes, which are compared using SSE2.
+    #[cfg(all(feature = "simd", target_arch = "x86_64"))]
+    fn skip_blocks(&self, haystack: &[u8]) -> usize {
+        // SAFETY: SSE2 is part of the `x86_64` baseline, so every `x86_64` CPU supports it.
+        unsafe { self.skip_blocks_sse2(haystack) }
+    }
+
+    /// See [`skip_blocks()`](Self::skip_blocks).
+    #[cfg(all(feature = "simd", target_arch = "x86_64"))]
+    #[target_feature(enable = "sse2")]
+    fn skip_blocks_sse2(&self, haystack: &[u8]) -> usize {
+        use core::arch::x86_64::{
+            __m128i, _mm_cmpeq_epi8, _mm_loadu_si128, _mm_movemask_epi8, _mm_or_si128,
+            _mm_set1_epi8, _mm_setzero_si128,
+        };
+
+        const BLOCK_LEN: usize = size_of::<__m128i>();
+
+        let splats = self
+            .bytes
+            .map(|byte| _mm_set1_epi8(i8::from_ne_bytes([byte])));
+        let mut offset = 0;
+        for chunk in haystack.chunks_exact(BLOCK_LEN) {
+            // SAFETY: `chunk` consists of `BLOCK_LEN` bytes and `_mm_loadu_si128()` doesn't
+            // require any alignment.
+            let block = unsafe { _mm_loadu_si128(chunk.as_ptr().cast()) };
+            let matches = splats.iter().fold(_mm_setzero_si128(), |matches, &splat| {
+                _mm_or_si128(matches, _mm_cmpeq_epi8(block, splat))
+            });
+            if _mm_movemask_epi8(matches) != 0 {
+                break;
+            }
+            offset += BLOCK_LEN;
+        }
+
+        offset
+    }
+}
+
+/// A set of ASCII bytes to search for, if there are too many for [`Needles`], optionally together
+/// with all bytes that are not ASCII.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct AsciiSet {
+    /// Bit `i` is set if the byte `i` is in the set.
+    mask: u128,
+    /// Whether the bytes that are not ASCII are in the set.
+    non_ascii: bool,
+    /// Entry `i` has bit `j` set if the byte `16 * j + i` is in the set.
+    #[cfg(all(feature = "simd", target_arch = "x86_64"))]
+    low_nibbles: [u8; 16],
+}
+
+impl AsciiSet {
+    /// Collects `chars`.
+    ///
+    /// Returns [`None`] if one of them is not ASCII.
+    pub(crate) fn new(chars: impl IntoIterator<Item = char>) -> Option<Self> {
+        let mut mask = 0;
+        for c in chars {
+            let byte = u8::try_from(c).ok().filter(u8::is_ascii)?;
+            mask |= 1 << byte;
+        }
+
+        Some(Self::from_mask(mask, false))
+    }
+
+    /// Creates the set of the ASCII bytes whose bits are set in `mask`, and of all bytes that are
+    /// not ASCII if `non_ascii` is `true`.
+    pub(crate) fn from_mask(mask: u128, non_ascii: bool) -> Self {
+        #[cfg(all(feature = "simd", target_arch = "x86_64"))]
+        let low_nibbles = {
+            let mut low_nibbles = [0; 16];
+            for byte in (0..=127_u8).filter(|byte| mask & (1 << byte) != 0) {
+                low_nibbles[usize::from(byte & 0x0f)] |= 1 << (byte >> 4);
+            }
+            low_nibbles
+        };
+
+        Self {
+            mask,
+            non_ascii,
+            #[cfg(all(feature = "simd", target_arch = "x86_64"))]
+            low_nibbles,
+        }
+    }
+
+    /// Returns `true` if `byte` is in the set.
+    pub(crate) const fn contains(self, byte: u8) -> bool {
+        if byte.is_ascii() {
+            self.mask & (1 << byte) != 0
+        } else {
+            self.non_ascii
+        }
+    }
+
+    /// Returns the index of the first byte of `haystack` that is in the set.
+    pub(crate) fn find(self, haystack: &[u8]) -> Option<usize> {
+        let offset = self.skip_blocks(haystack);
+        haystack[offset..]
+            .iter()
+            .position(|&byte| self.contains(byte))
+            .map(|i| offset + i)
+    }
+
+    /// Returns the length of the blocks at the start of `haystack` that contain no byte of the
+    /// set.
+    ///
+    /// Without SIMD, every byte is looked up on its own, so there are no blocks to skip.
+    #[cfg(not(all(feature = "simd", target_arch = "x86_64")))]
+    #[allow(clippy::unused_self)]
+    fn skip_blocks(self, _haystack: &[u8]) -> usize {
+        0
+    }
+
+    /// Returns the length of the blocks at the start of `haystack` that contain no byte of the
+    /// set.
+    ///
+    /// A block consists of 16 bytes, which are looked up using SSSE3 if the CPU supports it.
+    #[cfg(all(feature = "simd", target_arch = "x86_64"))]
+    fn skip_blocks(self, haystack: &[u8]) -> usize {
+        if std::is_x86_feature_detected!("ssse3") {
+            // SAFETY: the CPU supports SSSE3, as checked above.
+            unsafe { self.skip_blocks_ssse3(haystack) }
+        } else {
+            0
+        }
+    }
+
+    /// See [`skip_blocks()`](Self::skip_blocks).
+    ///
+    /// Each byte is split into its nibbles. Looking up the low nibble in
+    /// [`low_nibbles`](Self::low_nibbles) gives the high nibbles of the bytes in the set with that
+    /// low nibble, each as a bit, which is compared with the bit of the high nibble.
+    #[cfg(all(feature = "simd", target_arch = "x86_64"))]
+    #[target_feature(enable = "ssse3")]
+    fn skip_blocks_ssse3(self, haystack: &[u8]) -> usize {
+        use core::arch::x86_64::{
+            __m128i, _mm_and_si128, _mm_cmpeq_epi8, _mm_loadu_si128, _mm_movemask_epi8,
+            _mm_set1_epi8, _mm_setzero_si128, _mm_shuffle_epi8, _mm_srli_epi16,
+        };
+
+        const BLOCK_LEN: usize = size_of::<__m128i>();
+
+        // SAFETY: `low_nibbles` consists of `BLOCK_LEN` bytes and `_mm_loadu_si128()` doesn't
+        // require any alignment.
+        let low_nibbles = unsafe { _mm_loadu_si128(self.low_nibbles.as_ptr().cast()) };
+        // the bit of each high nibble, none for bytes that are not ASCII
+        let high_nibbles: [u8; BLOCK_LEN] = [1, 2, 4, 8, 16, 32, 64, 128, 0, 0, 0, 0, 0, 0, 0, 0];
+        // SAFETY: `high_nibbles` consists of `BLOCK_LEN` bytes and `_mm_loadu_si128()` doesn't
+        // require any alignment.
+        let high_nibbles = unsafe { _mm_loadu_si128(high_nibbles.as_ptr().cast()) };
+        let nibble = _mm_set1_epi8(0x0f);
+
+        let mut offset = 0;
+        for chunk in haystack.chunks_exact(BLOCK_LEN) {
+            // SAFETY: `chunk` consists of `BLOCK_LEN` bytes and `_mm_loadu_si128()` doesn't
+            // require any alignment.
+            let block = unsafe { _mm_loadu_si128(chunk.as_ptr().cast()) };
+            let low = _mm_and_si128(block, nibble);
+            let high = _mm_and_si128(_mm_srli_epi16::<4>(block), nibble);
+            let bits = _mm_and_si128(
+                _mm_shuffle_epi8(low_nibbles, low),
+                _mm_shuffle_epi8(high_nibbles, high),
+            );
+            let mut matches =
+                !_mm_movemask_epi8(_mm_cmpeq_epi8(bits, _mm_setzero_si128())) & 0xffff;
+            if self.non_ascii {
+                // the highest bit of every byte that is not ASCII is set
+                matches |= _mm_movemask_epi8(block);
+            }
+            if matches != 0 {
+                break;
+            }
+            offset += BLOCK_LEN;
+        }
+
+        offset
+    }
+}
+
+/// Searches for the ASCII bytes that need to be escaped, using [`Needles`] if there are few
+/// enough of them.
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum ByteSearch {
+    /// Up to three bytes, compared a whole block at a time.
+    Needles(Needles),
+    /// Any number of bytes, looked up one at a time.
+    Set(AsciiSet),
+}
+
+impl ByteSearch {
+    /// Collects the `chars`.
+    ///
+    /// Returns [`None`] if one of them is not ASCII.
+    pub(crate) fn new(chars: impl IntoIterator<Item = char> + Clone) -> Option<Self> {
+        Needles::new(chars.clone())
+            .map(Self::Needles)
+            .or_else(|| AsciiSet::new(chars).map(Self::Set))
+    }
+
+    /// Returns the index of the first byte of `haystack` that is searched for.
+    pub(crate) fn find(self, haystack: &[u8]) -> Option<usize> {
+        match self {
+            Self::Needles(needles) => needles.find(haystack),
+            Self::Set(set) => set.find(haystack),
+        }
+    }
+}
+
+/// Returns `true` if one of the bytes of `word` is zero.
+#[cfg(not(all(feature = "simd", target_arch = "x86_64")))]
+const fn has_zero_byte(word: usize) -> bool {
+    word.wrapping_sub(LO) & !word & HI != 0
+}
@@ -0,0 +1,104 @@
+use {
+    crate::MAX_UTF8_LEN,
+    core::{
+        fmt::{self, Display},
+        ops::Deref,
+    },
+    smallvec::SmallVec,
+};
+
+/// The number of bytes a [`SmallString`] stores without allocating.
+pub const SMALL_STRING_LEN: usize = 64;
+
+/// A string that stores up to [`SMALL_STRING_LEN`] bytes inline, without allocating.
+///
+/// Returned by [`Escaper::escape_small()`](crate::Escaper::escape_small) and
+/// [`Escaper::unescape_small()`](crate::Escaper::unescape_small). Longer strings are moved to the
+/// heap. It dereferences to a [`str`]. Requires the `smallvec` feature.
+///
+/// ```
+/// # use char_escape::presets::JSON;
+/// #
+/// let escaped = JSON.escape_small("short\n");
+///
+/// assert_eq!(escaped, r"short\n");
+/// assert!(escaped.is_inline());
+/// ```
+#[derive(Clone, Default, Eq, Hash, PartialEq)]
+pub struct SmallString {
+    bytes: SmallVec<[u8; SMALL_STRING_LEN]>,
+}
+
+impl SmallString {
+    /// Create an empty [`SmallString`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns `true` if the string is stored inline, i.e. no memory was allocated.
+    pub fn is_inline(&self) -> bool {
+        !self.bytes.spilled()
+    }
+
+    /// Returns the string as a [`str`].
+    pub fn as_str(&self) -> &str {
+        // SAFETY: `bytes` starts out empty and only complete `str`s are appended to it by
+        // `push_str()`, so it is valid UTF-8.
+        unsafe { core::str::from_utf8_unchecked(&self.bytes) }
+    }
+
+    /// Appends `s` to the end of the string.
+    pub fn push_str(&mut self, s: &str) {
+        self.bytes.extend_from_slice(s.as_bytes());
+    }
+
+    /// Appends `c` to the end of the string.
+    pub fn push(&mut self, c: char) {
+        self.push_str(c.encode_utf8(&mut [0; MAX_UTF8_LEN]));
+    }
+}
+
+impl Deref for SmallString {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl fmt::Debug for SmallString {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl Display for SmallString {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl fmt::Write for SmallString {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.push_str(s);
+        Ok(())
+    }
+}
+
+impl PartialEq<str> for SmallString {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl PartialEq<&str> for SmallString {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+impl From<SmallString> for String {
+    fn from(s: SmallString) -> Self {
+        s.as_str().to_owned()
+    }
+}
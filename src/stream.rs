@@ -0,0 +1,278 @@
+use {
+    crate::{Escaper, TrailingEscape, UnescapeError, MAX_UTF8_LEN},
+    core::fmt::{self, Display},
+    std::error::Error,
+};
+
+/// Escapes a UTF-8 encoded string that is split into multiple chunks of bytes.
+///
+/// This is useful inside of codecs, proxies and similar places where data arrives as bytes in
+/// pieces of arbitrary size. A chunk may end in the middle of a UTF-8 encoded [`char`], the rest
+/// of the [`char`] is then expected at the start of the next chunk.
+///
+/// If the chunks are [`str`]s, simply use [`Escaper::escape_into()`] on every chunk instead.
+///
+/// ```
+/// # use char_escape::{escaper, EscaperState};
+/// #
+/// let escaper = escaper! {
+///     'ä' => 'a',
+/// };
+///
+/// let mut state = EscaperState::new(escaper);
+/// let mut escaped = String::new();
+///
+/// let bytes = "Bär".as_bytes();
+/// for chunk in [&bytes[..2], &bytes[2..]] {
+///     state.feed(chunk, &mut escaped).expect("is valid UTF-8");
+/// }
+/// state.finish().expect("is valid UTF-8");
+///
+/// assert_eq!(escaped, r"B\ar");
+/// ```
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct EscaperState<'a> {
+    escaper: Escaper<'a>,
+    // an incomplete UTF-8 sequence at the end of the previous chunk
+    pending: [u8; MAX_UTF8_LEN],
+    pending_len: usize,
+    // the last char that was escaped
+    prev: Option<char>,
+}
+
+impl<'a> EscaperState<'a> {
+    /// Create a new [`EscaperState`] that escapes according to the rules of `escaper`.
+    pub const fn new(escaper: Escaper<'a>) -> Self {
+        Self {
+            escaper,
+            pending: [0; MAX_UTF8_LEN],
+            pending_len: 0,
+            prev: None,
+        }
+    }
+
+    /// Escapes the next chunk and appends the result to `out`.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the chunk contains invalid UTF-8. The [`char`]s escaped before the invalid UTF-8
+    /// was encountered may have been appended to `out`.
+    ///
+    /// After an error has occurred, the state should not be used anymore.
+    pub fn feed(&mut self, mut chunk: &[u8], out: &mut String) -> Result<(), InvalidUtf8> {
+        while self.pending_len != 0 {
+            let Some((&byte, rest)) = chunk.split_first() else {
+                return Ok(());
+            };
+            chunk = rest;
+
+            self.pending[self.pending_len] = byte;
+            self.pending_len += 1;
+            let pending = self.pending;
+            match core::str::from_utf8(&pending[..self.pending_len]) {
+                Ok(c) => {
+                    self.escape_into(c, out);
+                    self.pending_len = 0;
+                }
+                Err(error) if error.error_len().is_none() => {}
+                Err(_) => return Err(InvalidUtf8::new()),
+            }
+        }
+
+        let complete = crate::complete_utf8_prefix(chunk).map_err(|_| InvalidUtf8::new())?;
+        self.escape_into(complete, out);
+
+        let incomplete = &chunk[complete.len()..];
+        self.pending[..incomplete.len()].copy_from_slice(incomplete);
+        self.pending_len = incomplete.len();
+
+        Ok(())
+    }
+
+    /// Escapes `s`, which directly follows the previously escaped [`char`]s.
+    fn escape_into(&mut self, s: &str, out: &mut String) {
+        self.escaper.escape_into_after(self.prev, s, out);
+        self.prev = s.chars().next_back().or(self.prev);
+    }
+
+    /// Signals that there are no more chunks.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the last chunk ended with an incomplete UTF-8 sequence.
+    pub fn finish(self) -> Result<(), InvalidUtf8> {
+        if self.pending_len == 0 {
+            Ok(())
+        } else {
+            Err(InvalidUtf8::new())
+        }
+    }
+}
+
+/// Unescapes a string that is split into multiple chunks.
+///
+/// This is useful if the escaped string arrives in pieces, e.g. over the network. A chunk may end
+/// in the middle of an escape sequence, the rest of the escape sequence is then expected at the
+/// start of the next chunk.
+///
+/// ```
+/// # use char_escape::{escaper, UnescaperState};
+/// #
+/// let escaper = escaper! {
+///     '\n' => 'n',
+/// };
+///
+/// let mut state = UnescaperState::new(escaper);
+/// let mut unescaped = String::new();
+///
+/// for chunk in ["one\\", "ntwo\\n", "three"] {
+///     state.feed(chunk, &mut unescaped).expect("is properly escaped");
+/// }
+/// state.finish(&mut unescaped).expect("is properly escaped");
+///
+/// assert_eq!(unescaped, "one\ntwo\nthree");
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct UnescaperState<'a> {
+    escaper: Escaper<'a>,
+    // the number of bytes fed before `pending`
+    offset: usize,
+    // an escape sequence, starting with the escape char, that might be continued in the next chunk
+    pending: String,
+}
+
+impl<'a> UnescaperState<'a> {
+    /// Create a new [`UnescaperState`] that unescapes according to the rules of `escaper`.
+    pub const fn new(escaper: Escaper<'a>) -> Self {
+        Self {
+            escaper,
+            offset: 0,
+            pending: String::new(),
+        }
+    }
+
+    /// Unescapes the next chunk and appends the result to `out`.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the chunk contains an invalid escape sequence. The [`char`]s unescaped before the
+    /// invalid escape sequence was encountered have been appended to `out`. The position of the
+    /// error is relative to the start of the first chunk.
+    ///
+    /// After an error has occurred, the state should not be used anymore.
+    pub fn feed(&mut self, chunk: &str, out: &mut String) -> Result<(), UnescapeError> {
+        if self.pending.is_empty() {
+            self.unescape(chunk, false, out)
+        } else {
+            let mut text = core::mem::take(&mut self.pending);
+            text.push_str(chunk);
+            self.unescape(&text, false, out)
+        }
+    }
+
+    /// Signals that there are no more chunks.
+    ///
+    /// If the last chunk ended with an incomplete escape sequence, it is handled according to the
+    /// [trailing escape policy](Escaper::with_trailing_escape) and anything that is kept is
+    /// appended to `out`.
+    ///
+    /// # Errors
+    ///
+    /// Fails with [`UnescapeError::Incomplete`] if the last chunk ended with an incomplete escape
+    /// sequence and the policy is [`TrailingEscape::Error`].
+    ///
+    /// ```
+    /// # use char_escape::{escaper, UnescapeError, UnescaperState};
+    /// #
+    /// let mut state = UnescaperState::new(escaper! { '\n' => 'n' });
+    /// let mut out = String::new();
+    ///
+    /// state.feed(r"line\", &mut out).expect("is properly escaped so far");
+    ///
+    /// assert_eq!(state.finish(&mut out), Err(UnescapeError::Incomplete { position: 4 }));
+    /// ```
+    pub fn finish(mut self, out: &mut String) -> Result<(), UnescapeError> {
+        let pending = core::mem::take(&mut self.pending);
+        self.unescape(&pending, true, out)
+    }
+
+    /// Unescapes `text`, which follows the bytes fed before `pending`, and appends the result to
+    /// `out`.
+    ///
+    /// If `at_end` is `false`, an escape sequence that might be continued is stored in `pending`.
+    /// Otherwise, an incomplete escape sequence is handled according to the trailing escape
+    /// policy.
+    fn unescape(
+        &mut self,
+        text: &str,
+        at_end: bool,
+        out: &mut String,
+    ) -> Result<(), UnescapeError> {
+        let mut i = 0;
+
+        while let Some(c) = text[i..].chars().next() {
+            let start = i;
+            i += c.len_utf8();
+
+            if c == self.escaper.escape_char() {
+                match self
+                    .escaper
+                    .unescape_sequence(&text[i..], self.offset + start, at_end)
+                {
+                    Ok((unescaped, len)) => {
+                        out.push(unescaped);
+                        i += len;
+                    }
+                    Err(UnescapeError::Incomplete { .. }) if !at_end => {
+                        self.offset += start;
+                        self.pending.push_str(&text[start..]);
+                        return Ok(());
+                    }
+                    Err(error @ UnescapeError::Incomplete { .. }) => {
+                        return match self.escaper.trailing_escape() {
+                            TrailingEscape::Error => Err(error),
+                            TrailingEscape::Drop => Ok(()),
+                            TrailingEscape::Keep => {
+                                out.push_str(&text[start..]);
+                                Ok(())
+                            }
+                        };
+                    }
+                    Err(error) => return Err(error),
+                }
+            } else {
+                out.push(c);
+            }
+        }
+
+        self.offset += text.len();
+
+        Ok(())
+    }
+}
+
+/// Results from feeding bytes that are not valid UTF-8 to an [`EscaperState`].
+#[non_exhaustive]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct InvalidUtf8 {}
+
+impl InvalidUtf8 {
+    /// Create a new [`InvalidUtf8`].
+    pub const fn new() -> Self {
+        Self {}
+    }
+}
+
+impl Default for InvalidUtf8 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Display for InvalidUtf8 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid UTF-8")
+    }
+}
+
+impl Error for InvalidUtf8 {}